@@ -2,6 +2,13 @@ const loginLink = document.getElementById("login-link");
 const logoutLink = document.getElementById("logout-link");
 const protectImg = document.getElementById("protected-img");
 
+// auth-service hands out a `csrf_token` cookie that must be echoed on state-changing
+// requests. Cookies are not scoped by port, so it is readable here.
+function csrfHeaders() {
+    const match = document.cookie.match(/(?:^|;\s*)csrf_token=([^;]*)/);
+    return match ? { 'X-CSRF-Token': decodeURIComponent(match[1]) } : {};
+}
+
 logoutLink.addEventListener("click", (e) => {
     e.preventDefault();
 
@@ -10,6 +17,7 @@ logoutLink.addEventListener("click", (e) => {
     fetch(url, {
         method: 'POST',
         credentials: 'include', // This will include cookies in the request
+        headers: csrfHeaders(),
     }).then(response => {
         if (response.ok) {
             loginLink.style.display = "block";
@@ -35,6 +43,11 @@ logoutLink.addEventListener("click", (e) => {
                     protectImg.src = "/assets/default.jpg";
                 }
             });
+        } else if (response.status === 403) {
+            // Logged in, but missing the role required to see the protected resource
+            loginLink.style.display = "none";
+            logoutLink.style.display = "block";
+            protectImg.src = "/assets/default.jpg";
         } else {
             loginLink.style.display = "block";
             logoutLink.style.display = "none";
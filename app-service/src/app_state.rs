@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use auth_client::AuthClient;
+use axum::extract::FromRef;
+
+use crate::{metrics, settings::Settings};
+
+#[derive(Clone)]
+pub struct AppState {
+    pub settings: Arc<Settings>,
+    // Shared by every call to auth-service, so connections are reused
+    pub http_client: reqwest::Client,
+    pub auth_client: AuthClient,
+}
+
+impl AppState {
+    pub fn new(settings: Settings) -> Result<Self, reqwest::Error> {
+        let http_client = reqwest::Client::builder()
+            .timeout(settings.auth_resilience.timeout)
+            .build()?;
+        let auth_client = settings
+            .auth_client(http_client.clone())
+            .with_observer(Arc::new(metrics::observe_auth_service_call));
+
+        Ok(Self {
+            settings: Arc::new(settings),
+            http_client,
+            auth_client,
+        })
+    }
+}
+
+// Lets handlers take `AuthenticatedUser`
+impl FromRef<AppState> for AuthClient {
+    fn from_ref(state: &AppState) -> Self {
+        state.auth_client.clone()
+    }
+}
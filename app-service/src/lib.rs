@@ -0,0 +1,53 @@
+use std::error::Error;
+
+use axum::{
+    middleware,
+    routing::{get, post},
+    serve::Serve,
+    Router,
+};
+use tokio::net::TcpListener;
+use tower_http::services::ServeDir;
+
+use app_state::AppState;
+use settings::Settings;
+
+pub mod app_state;
+pub mod metrics;
+pub mod routes;
+pub mod settings;
+
+// This struct encapsulates our application-related logic.
+pub struct Application {
+    server: Serve<TcpListener, Router, Router>,
+    // address is exposed as a public field
+    // so we have access to it in tests.
+    pub address: String,
+}
+
+impl Application {
+    pub async fn build(settings: Settings) -> Result<Self, Box<dyn Error>> {
+        let address = settings.listen_address;
+        let router = Router::new()
+            .nest_service("/assets", ServeDir::new("assets"))
+            .route("/", get(routes::root))
+            .route("/protected", get(routes::protected))
+            .route("/revocations", post(routes::revoke))
+            .route("/metrics", get(metrics::metrics))
+            .route("/health/live", get(routes::health_live))
+            .route("/health/ready", get(routes::health_ready))
+            .route_layer(middleware::from_fn(metrics::track_metrics))
+            .with_state(AppState::new(settings)?);
+
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?.to_string();
+        let server = axum::serve(listener, router);
+
+        Ok(Application { server, address })
+    }
+
+    pub async fn run(self) -> Result<(), std::io::Error> {
+        println!("listening on {}", &self.address);
+        self.server.await
+    }
+}
@@ -1,88 +1,118 @@
-use std::env;
+use std::{env, time::Duration};
 
-use askama::Template;
-use axum::{
-    http::StatusCode,
-    response::{Html, IntoResponse},
-    routing::get,
-    Json, Router,
+use app_service::{
+    settings::{
+        ServiceUrl, Settings, TokenVerification, DEFAULT_PROTECTED_REQUIRED_ROLE,
+        DEFAULT_PROTECTED_RESOURCE_URL,
+    },
+    Application,
 };
-use axum_extra::extract::CookieJar;
-use serde::Serialize;
-use tower_http::services::ServeDir;
+use auth_client::{CacheOptions, FailurePolicy, JwksOptions, ResilienceOptions, ServiceCredentials};
+
+const DEFAULT_LISTEN_ADDRESS: &str = "0.0.0.0:8000";
+const DEFAULT_AUTH_SERVICE_URL: &str = "http://localhost:3000";
 
 #[tokio::main]
 async fn main() {
-    let app = Router::new()
-        .nest_service("/assets", ServeDir::new("assets"))
-        .route("/", get(root))
-        .route("/protected", get(protected));
-
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:8000").await.unwrap();
+    let app = Application::build(load_settings())
+        .await
+        .expect("Failed to build app");
 
-    println!("listening on {}", listener.local_addr().unwrap());
-    axum::serve(listener, app).await.unwrap();
+    app.run().await.expect("Failed to run app");
 }
 
-#[derive(Template)]
-#[template(path = "index.html")]
-struct IndexTemplate {
-    login_link: String,
-    logout_link: String,
-}
+// Read every setting from the environment, refusing to start on invalid values
+fn load_settings() -> Settings {
+    let listen_address = env_var("APP_LISTEN_ADDRESS")
+        .unwrap_or(DEFAULT_LISTEN_ADDRESS.to_owned())
+        .parse()
+        .expect("Invalid APP_LISTEN_ADDRESS");
+
+    let auth_internal_url =
+        ServiceUrl::parse(&env_var("AUTH_SERVICE_URL").unwrap_or(DEFAULT_AUTH_SERVICE_URL.to_owned()))
+            .expect("Invalid AUTH_SERVICE_URL");
+    // Browsers usually reach auth-service at another address than we do
+    let auth_public_url = match env_var("AUTH_SERVICE_PUBLIC_URL") {
+        Some(url) => ServiceUrl::parse(&url).expect("Invalid AUTH_SERVICE_PUBLIC_URL"),
+        None => auth_internal_url.clone(),
+    };
+
+    let protected_resource_url = env_var("PROTECTED_RESOURCE_URL")
+        .unwrap_or(DEFAULT_PROTECTED_RESOURCE_URL.to_owned())
+        .parse()
+        .expect("Invalid PROTECTED_RESOURCE_URL");
 
-async fn root() -> impl IntoResponse {
-    let mut address = env::var("AUTH_SERVICE_IP").unwrap_or("localhost".to_owned());
-    if address.is_empty() {
-        address = "localhost".to_owned();
+    Settings {
+        listen_address,
+        auth_internal_url,
+        auth_public_url,
+        auth_resilience: load_auth_resilience(),
+        protected_resource_url,
+        token_verification: load_token_verification(),
+        token_cache: load_token_cache(),
+        auth_cookie_name: env_var("AUTH_COOKIE_NAME"),
+        protected_required_role: env_var("PROTECTED_REQUIRED_ROLE")
+            .unwrap_or(DEFAULT_PROTECTED_REQUIRED_ROLE.to_owned()),
     }
-    let login_link = format!("http://{}:3000", address);
-    let logout_link = format!("http://{}:3000/logout", address);
+}
 
-    let template = IndexTemplate {
-        login_link,
-        logout_link,
-    };
-    Html(template.render().unwrap())
+// Verify user tokens with auth-service's `/verify-token`, authenticating as
+// `AUTH_CLIENT_ID`, or locally against the keys at `AUTH_JWKS_URL` when it is set
+fn load_token_verification() -> TokenVerification {
+    match env_var("AUTH_JWKS_URL") {
+        Some(url) => TokenVerification::Jwks {
+            url,
+            options: JwksOptions::default(),
+        },
+        // Missing credentials surface as failed verifications, reported per request
+        None => TokenVerification::Remote(ServiceCredentials {
+            client_id: env_var("AUTH_CLIENT_ID").unwrap_or_default(),
+            client_secret: env_var("AUTH_CLIENT_SECRET").unwrap_or_default(),
+        }),
+    }
 }
 
-async fn protected(jar: CookieJar) -> impl IntoResponse {
-    let jwt_cookie = match jar.get("jwt") {
-        Some(cookie) => cookie,
-        None => {
-            return StatusCode::UNAUTHORIZED.into_response();
-        }
+fn load_auth_resilience() -> ResilienceOptions {
+    let defaults = ResilienceOptions::default();
+    let millis = |name: &str, default: Duration| match env_var(name) {
+        Some(ms) => Duration::from_millis(ms.parse().unwrap_or_else(|_| panic!("Invalid {}", name))),
+        None => default,
     };
 
-    let api_client = reqwest::Client::builder().build().unwrap();
-
-    let verify_token_body = serde_json::json!({
-        "token": &jwt_cookie.value(),
-    });
-
-    let auth_hostname = env::var("AUTH_SERVICE_HOST_NAME").unwrap_or("0.0.0.0".to_owned());
-    let url = format!("http://{}:3000/verify-token", auth_hostname);
+    ResilienceOptions {
+        timeout: millis("AUTH_REQUEST_TIMEOUT_MS", defaults.timeout),
+        retries: env_var("AUTH_RETRIES")
+            .map(|retries| retries.parse().expect("Invalid AUTH_RETRIES"))
+            .unwrap_or(defaults.retries),
+        backoff: millis("AUTH_RETRY_BACKOFF_MS", defaults.backoff),
+        failure_threshold: env_var("AUTH_CIRCUIT_FAILURE_THRESHOLD")
+            .map(|threshold| threshold.parse().expect("Invalid AUTH_CIRCUIT_FAILURE_THRESHOLD"))
+            .unwrap_or(defaults.failure_threshold),
+        open_duration: millis("AUTH_CIRCUIT_OPEN_MS", defaults.open_duration),
+        failure_policy: match env_var("AUTH_FAILURE_POLICY").as_deref() {
+            None | Some("closed") => FailurePolicy::Closed,
+            Some("open") => FailurePolicy::Open,
+            Some(_) => panic!("Invalid AUTH_FAILURE_POLICY, expected `closed` or `open`"),
+        },
+    }
+}
 
-    let response = match api_client.post(&url).json(&verify_token_body).send().await {
-        Ok(response) => response,
-        Err(_) => {
-            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
-        }
+// Cache successful verifications for `AUTH_CACHE_TTL_SECS`, unless it is 0
+fn load_token_cache() -> Option<CacheOptions> {
+    let defaults = CacheOptions::default();
+    let ttl = match env_var("AUTH_CACHE_TTL_SECS") {
+        Some(secs) => Duration::from_secs(secs.parse().expect("Invalid AUTH_CACHE_TTL_SECS")),
+        None => defaults.ttl,
+    };
+    let max_entries = match env_var("AUTH_CACHE_MAX_ENTRIES") {
+        Some(max) => max.parse().expect("Invalid AUTH_CACHE_MAX_ENTRIES"),
+        None => defaults.max_entries,
     };
 
-    match response.status() {
-        reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::BAD_REQUEST => {
-            StatusCode::UNAUTHORIZED.into_response()
-        }
-        reqwest::StatusCode::OK => Json(ProtectedRouteResponse {
-            img_url: "https://i.ibb.co/YP90j68/Light-Live-Bootcamp-Certificate.png".to_owned(),
-        })
-        .into_response(),
-        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
-    }
+    (!ttl.is_zero()).then_some(CacheOptions { ttl, max_entries })
 }
 
-#[derive(Serialize)]
-pub struct ProtectedRouteResponse {
-    pub img_url: String,
+// Unset and empty variables alike fall back to the default
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
 }
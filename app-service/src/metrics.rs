@@ -0,0 +1,137 @@
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use auth_client::CacheStats;
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::header,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    core::Collector, proto::MetricFamily, Encoder, HistogramOpts, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+use crate::app_state::AppState;
+
+// Prometheus metrics of this process, served at `/metrics`
+struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    auth_service_request_duration: HistogramVec,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(|| {
+    let registry = Registry::new();
+
+    let http_requests = IntCounterVec::new(
+        Opts::new("http_requests_total", "HTTP requests by route and status"),
+        &["method", "route", "status"],
+    )
+    .unwrap();
+    let http_request_duration = HistogramVec::new(
+        HistogramOpts::new(
+            "http_request_duration_seconds",
+            "HTTP request latency by route",
+        ),
+        &["method", "route"],
+    )
+    .unwrap();
+    let auth_service_request_duration = HistogramVec::new(
+        HistogramOpts::new(
+            "auth_service_request_duration_seconds",
+            "Latency of calls to auth-service by endpoint and outcome",
+        ),
+        &["endpoint", "outcome"],
+    )
+    .unwrap();
+
+    registry.register(Box::new(http_requests.clone())).unwrap();
+    registry.register(Box::new(http_request_duration.clone())).unwrap();
+    registry
+        .register(Box::new(auth_service_request_duration.clone()))
+        .unwrap();
+
+    Metrics {
+        registry,
+        http_requests,
+        http_request_duration,
+        auth_service_request_duration,
+    }
+});
+
+// Record how long a call to auth-service took. `outcome` is the response status,
+// or "error" if no response came back.
+pub fn observe_auth_service_call(endpoint: &str, outcome: &str, elapsed: Duration) {
+    METRICS
+        .auth_service_request_duration
+        .with_label_values(&[endpoint, outcome])
+        .observe(elapsed.as_secs_f64());
+}
+
+// Count and time every request to a known route, labelled with the route template
+pub async fn track_metrics(request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or("unmatched".to_owned());
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let status = response.status().as_u16().to_string();
+    METRICS
+        .http_requests
+        .with_label_values(&[&method, &route, &status])
+        .inc();
+    METRICS
+        .http_request_duration
+        .with_label_values(&[&method, &route])
+        .observe(elapsed);
+
+    response
+}
+
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let mut families = METRICS.registry.gather();
+    if let Some(stats) = state.auth_client.cache_stats() {
+        families.extend(token_cache_metrics(stats));
+    }
+
+    let mut buffer = Vec::new();
+    TextEncoder::new()
+        .encode(&families, &mut buffer)
+        .expect("Failed to encode metrics");
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        String::from_utf8(buffer).expect("Metrics are not valid UTF-8"),
+    )
+}
+
+// The token cache keeps its own counts; they are read at scrape time
+fn token_cache_metrics(stats: CacheStats) -> Vec<MetricFamily> {
+    let hits = IntCounter::new(
+        "auth_token_cache_hits_total",
+        "Token verifications answered from the cache",
+    )
+    .unwrap();
+    hits.inc_by(stats.hits);
+    let misses = IntCounter::new(
+        "auth_token_cache_misses_total",
+        "Token verifications that had to ask auth-service",
+    )
+    .unwrap();
+    misses.inc_by(stats.misses);
+    let entries = IntGauge::new("auth_token_cache_entries", "Tokens in the cache").unwrap();
+    entries.set(stats.entries as i64);
+
+    [hits.collect(), misses.collect(), entries.collect()].concat()
+}
@@ -0,0 +1,71 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::Serialize;
+
+use crate::app_state::AppState;
+
+// auth-service counts as unreachable if it does not answer within this time
+const AUTH_SERVICE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    checks: BTreeMap<&'static str, DependencyHealth>,
+}
+
+#[derive(Serialize)]
+struct DependencyHealth {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+// The process is running and able to answer requests
+pub async fn health_live() -> impl IntoResponse {
+    Json(HealthResponse {
+        status: "up",
+        checks: BTreeMap::new(),
+    })
+}
+
+// Every protected request goes through auth-service, so we are only ready if it is reachable
+pub async fn health_ready(State(state): State<AppState>) -> impl IntoResponse {
+    let auth_service = check_auth_service(&state).await;
+    let healthy = auth_service.error.is_none();
+
+    let response = HealthResponse {
+        status: if healthy { "up" } else { "down" },
+        checks: BTreeMap::from([("auth_service", auth_service)]),
+    };
+
+    let status_code = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status_code, Json(response))
+}
+
+async fn check_auth_service(state: &AppState) -> DependencyHealth {
+    // Only reachability matters here: auth-service's own readiness is its concern
+    let error = match state
+        .http_client
+        .get(state.settings.auth_internal_url.join("/health/live"))
+        .timeout(AUTH_SERVICE_CHECK_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(response) if response.status().is_success() => None,
+        Ok(response) => Some(format!("unexpected status {}", response.status())),
+        Err(e) if e.is_timeout() => Some("timed out".to_owned()),
+        Err(e) => Some(e.to_string()),
+    };
+
+    DependencyHealth {
+        status: if error.is_none() { "up" } else { "down" },
+        error,
+    }
+}
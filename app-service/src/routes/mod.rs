@@ -0,0 +1,9 @@
+mod health;
+mod protected;
+mod revocations;
+mod root;
+
+pub use health::*;
+pub use protected::*;
+pub use revocations::*;
+pub use root::*;
@@ -0,0 +1,22 @@
+use auth_client::{AuthError, AuthenticatedUser};
+use axum::{extract::State, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+
+use crate::app_state::AppState;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProtectedRouteResponse {
+    pub img_url: String,
+}
+
+pub async fn protected(
+    State(state): State<AppState>,
+    user: AuthenticatedUser,
+) -> Result<impl IntoResponse, AuthError> {
+    // The user is authenticated at this point, so a missing role is a 403 rather than a 401
+    user.require_role(&state.settings.protected_required_role)?;
+
+    Ok(Json(ProtectedRouteResponse {
+        img_url: state.settings.protected_resource_url.to_string(),
+    }))
+}
@@ -0,0 +1,31 @@
+use auth_client::AuthError;
+use auth_sdk::{RevocationRequest, AUTH_SERVICE_CLIENT_ID};
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    Json,
+};
+
+use crate::app_state::AppState;
+
+// Signal from auth-service that a token was revoked, e.g. on logout, so its
+// cached verification must go. Only auth-service may send it, with a service
+// token it issued to itself.
+pub async fn revoke(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<RevocationRequest>,
+) -> Result<StatusCode, AuthError> {
+    let service_token = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(AuthError::MissingToken)?;
+
+    if state.auth_client.verify_service_token(service_token).await? != AUTH_SERVICE_CLIENT_ID {
+        return Err(AuthError::Forbidden);
+    }
+
+    state.auth_client.evict(&request.token);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,23 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    response::{Html, IntoResponse},
+};
+
+use crate::app_state::AppState;
+
+#[derive(Template)]
+#[template(path = "index.html")]
+struct IndexTemplate {
+    login_link: String,
+    logout_link: String,
+}
+
+pub async fn root(State(state): State<AppState>) -> impl IntoResponse {
+    let auth_public_url = &state.settings.auth_public_url;
+    let template = IndexTemplate {
+        login_link: auth_public_url.as_str().to_owned(),
+        logout_link: auth_public_url.join("/logout"),
+    };
+    Html(template.render().unwrap())
+}
@@ -0,0 +1,119 @@
+use std::net::SocketAddr;
+
+use auth_client::{AuthClient, CacheOptions, JwksOptions, ResilienceOptions, ServiceCredentials};
+use reqwest::Url;
+
+// Role a user must hold to access the protected resource, unless configured otherwise
+pub const DEFAULT_PROTECTED_REQUIRED_ROLE: &str = "user";
+pub const DEFAULT_PROTECTED_RESOURCE_URL: &str =
+    "https://i.ibb.co/YP90j68/Light-Live-Bootcamp-Certificate.png";
+
+// Base URL of auth-service: scheme, host, port and an optional path prefix, e.g.
+// `https://example.com/auth`. Paths of the API are appended to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceUrl(Url);
+
+impl ServiceUrl {
+    pub fn parse(url: &str) -> Result<Self, String> {
+        let parsed = Url::parse(url).map_err(|e| format!("invalid URL {:?}: {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("URL {:?} must use http or https", url));
+        }
+        if parsed.host_str().is_none() {
+            return Err(format!("URL {:?} has no host", url));
+        }
+        if parsed.query().is_some() || parsed.fragment().is_some() {
+            return Err(format!("URL {:?} must not have a query or fragment", url));
+        }
+        Ok(Self(parsed))
+    }
+
+    // The URL without a trailing slash, e.g. `http://auth-service:3000`
+    pub fn as_str(&self) -> &str {
+        self.0.as_str().trim_end_matches('/')
+    }
+
+    // `path` (starting with `/`) under this URL, keeping its path prefix
+    pub fn join(&self, path: &str) -> String {
+        format!("{}{}", self.as_str(), path)
+    }
+}
+
+// How user tokens are checked
+#[derive(Debug, Clone)]
+pub enum TokenVerification {
+    // With auth-service's `/verify-token`, authenticating as a registered service
+    Remote(ServiceCredentials),
+    // Locally, against the keys published at a JWKS URL
+    Jwks { url: String, options: JwksOptions },
+}
+
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub listen_address: SocketAddr,
+    // Where this service reaches auth-service, e.g. `http://auth-service:3000`
+    pub auth_internal_url: ServiceUrl,
+    // Where browsers reach auth-service, for the login and logout links
+    pub auth_public_url: ServiceUrl,
+    // Timeout, retries, circuit breaker and failure policy of calls to auth-service.
+    // The timeout bounds every call, health checks included.
+    pub auth_resilience: ResilienceOptions,
+    // What `/protected` hands out to authorized users
+    pub protected_resource_url: Url,
+    pub token_verification: TokenVerification,
+    // Successful verifications are remembered for a while when set
+    pub token_cache: Option<CacheOptions>,
+    // Name of auth-service's cookie, if not the default
+    pub auth_cookie_name: Option<String>,
+    pub protected_required_role: String,
+}
+
+impl Settings {
+    pub(crate) fn auth_client(&self, http_client: reqwest::Client) -> AuthClient {
+        let client = match &self.token_verification {
+            TokenVerification::Remote(credentials) => {
+                AuthClient::remote(self.auth_internal_url.as_str(), credentials.clone())
+            }
+            TokenVerification::Jwks { url, options } => AuthClient::jwks(url.clone(), options.clone()),
+        };
+        let client = client
+            .with_http_client(http_client)
+            .with_resilience(self.auth_resilience.clone());
+        let client = match &self.token_cache {
+            Some(options) => client.with_cache(options.clone()),
+            None => client,
+        };
+        match &self.auth_cookie_name {
+            Some(name) => client.with_cookie_name(name.clone()),
+            None => client,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_url_keeps_port_and_path_prefix() {
+        let url = ServiceUrl::parse("https://example.com:8443/auth/").unwrap();
+        assert_eq!(url.as_str(), "https://example.com:8443/auth");
+        assert_eq!(url.join("/logout"), "https://example.com:8443/auth/logout");
+
+        let url = ServiceUrl::parse("http://auth-service:3000").unwrap();
+        assert_eq!(url.join("/verify-token"), "http://auth-service:3000/verify-token");
+    }
+
+    #[test]
+    fn test_service_url_rejects_unusable_urls() {
+        for url in [
+            "auth-service:3000",
+            "ftp://auth-service",
+            "http://auth-service:3000?x=1",
+            "http://auth-service:3000/#top",
+            "not a url",
+        ] {
+            assert!(ServiceUrl::parse(url).is_err(), "{} was accepted", url);
+        }
+    }
+}
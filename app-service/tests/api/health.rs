@@ -0,0 +1,30 @@
+use crate::helpers::TestApp;
+
+#[tokio::test]
+async fn should_be_live_even_if_auth_service_is_down() {
+    let app = TestApp::with_auth_service_down().await;
+
+    let response = app.get_health("live").await;
+
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_be_ready_if_auth_service_is_reachable() {
+    let app = TestApp::new().await;
+
+    let response = app.get_health("ready").await;
+
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_not_be_ready_if_auth_service_is_down() {
+    let app = TestApp::with_auth_service_down().await;
+
+    let response = app.get_health("ready").await;
+
+    assert_eq!(response.status().as_u16(), 503);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["checks"]["auth_service"]["status"], "down");
+}
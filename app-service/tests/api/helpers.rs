@@ -0,0 +1,228 @@
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use app_service::{
+    settings::{ServiceUrl, Settings, TokenVerification, DEFAULT_PROTECTED_REQUIRED_ROLE},
+    Application,
+};
+use auth_client::{CacheOptions, ResilienceOptions, ServiceCredentials};
+use auth_sdk::{
+    Claims, IntrospectRequest, IntrospectResponse, TokenResponse, VerifyTokenRequest,
+    AUTH_SERVICE_CLIENT_ID,
+};
+use axum::{
+    extract::State,
+    http::{header::AUTHORIZATION, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::{get, post},
+    Form, Json, Router,
+};
+
+pub const CLIENT_ID: &str = "app-service";
+pub const CLIENT_SECRET: &str = "app-service-secret";
+
+// Tokens the stub auth-service accepts
+pub const USER_TOKEN: &str = "user-token";
+pub const GUEST_TOKEN: &str = "guest-token";
+
+pub const SERVICE_TOKEN: &str = "service-token";
+// Service token the stub issued to itself, to push revocations
+pub const AUTH_SERVICE_TOKEN: &str = "auth-service-token";
+
+// Where browsers are sent to log in
+pub const AUTH_PUBLIC_URL: &str = "https://example.com/auth";
+pub const PROTECTED_RESOURCE_URL: &str = "https://example.com/certificate.png";
+
+// What the stub auth-service has seen and done
+#[derive(Clone, Default)]
+pub struct StubAuthService {
+    // Calls received at `/verify-token`
+    verify_token_calls: Arc<AtomicUsize>,
+    // Tokens logged out since, which `/verify-token` rejects
+    revoked: Arc<Mutex<HashSet<String>>>,
+}
+
+pub struct TestApp {
+    pub address: String,
+    pub http_client: reqwest::Client,
+    pub auth_service: StubAuthService,
+}
+
+impl TestApp {
+    // app-service in front of a stub auth-service
+    pub async fn new() -> Self {
+        let auth_service = StubAuthService::default();
+        let auth_base_url = spawn_auth_service(auth_service.clone()).await;
+        Self::with_auth_base_url(auth_base_url, auth_service).await
+    }
+
+    // app-service whose auth-service does not answer
+    pub async fn with_auth_service_down() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        Self::with_auth_base_url(format!("http://{}", address), StubAuthService::default()).await
+    }
+
+    async fn with_auth_base_url(auth_base_url: String, auth_service: StubAuthService) -> Self {
+        let settings = Settings {
+            listen_address: "127.0.0.1:0".parse().unwrap(),
+            auth_internal_url: ServiceUrl::parse(&auth_base_url).unwrap(),
+            auth_public_url: ServiceUrl::parse(AUTH_PUBLIC_URL).unwrap(),
+            auth_resilience: ResilienceOptions {
+                timeout: Duration::from_secs(2),
+                retries: 0,
+                ..ResilienceOptions::default()
+            },
+            protected_resource_url: PROTECTED_RESOURCE_URL.parse().unwrap(),
+            token_verification: TokenVerification::Remote(ServiceCredentials {
+                client_id: CLIENT_ID.to_owned(),
+                client_secret: CLIENT_SECRET.to_owned(),
+            }),
+            token_cache: Some(CacheOptions::default()),
+            auth_cookie_name: None,
+            protected_required_role: DEFAULT_PROTECTED_REQUIRED_ROLE.to_owned(),
+        };
+
+        let app = Application::build(settings)
+            .await
+            .expect("Failed to build app");
+        let address = format!("http://{}", app.address.clone());
+
+        #[allow(clippy::let_underscore_future)]
+        let _ = tokio::spawn(app.run());
+
+        Self {
+            address,
+            http_client: reqwest::Client::new(),
+            auth_service,
+        }
+    }
+
+    pub async fn get_root(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_protected(&self, token: Option<&str>) -> reqwest::Response {
+        let mut request = self.http_client.get(format!("{}/protected", &self.address));
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        request.send().await.expect("Failed to execute request.")
+    }
+
+    // Pushes a revocation as the stub auth-service would, or with another service token
+    pub async fn post_revocation(&self, token: &str, service_token: Option<&str>) -> reqwest::Response {
+        let mut request = self
+            .http_client
+            .post(format!("{}/revocations", &self.address))
+            .json(&serde_json::json!({ "token": token }));
+        if let Some(service_token) = service_token {
+            request = request.bearer_auth(service_token);
+        }
+        request.send().await.expect("Failed to execute request.")
+    }
+
+    // Logs `token` out at the stub auth-service, without telling app-service
+    pub fn log_out(&self, token: &str) {
+        self.auth_service.revoked.lock().unwrap().insert(token.to_owned());
+    }
+
+    pub async fn get_metrics(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/metrics", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub fn verify_token_calls(&self) -> usize {
+        self.auth_service.verify_token_calls.load(Ordering::SeqCst)
+    }
+
+    pub async fn get_health(&self, probe: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/health/{}", &self.address, probe))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+}
+
+// Stub auth-service issuing a service token to our client and verifying the tokens above
+async fn spawn_auth_service(stub: StubAuthService) -> String {
+    async fn token() -> impl IntoResponse {
+        Json(TokenResponse {
+            access_token: SERVICE_TOKEN.to_owned(),
+            token_type: "Bearer".to_owned(),
+            expires_in: 900,
+            scope: "verify".to_owned(),
+        })
+    }
+
+    async fn verify_token(
+        State(stub): State<StubAuthService>,
+        headers: HeaderMap,
+        Json(request): Json<VerifyTokenRequest>,
+    ) -> impl IntoResponse {
+        stub.verify_token_calls.fetch_add(1, Ordering::SeqCst);
+        let bearer = format!("Bearer {}", SERVICE_TOKEN);
+        if headers.get(AUTHORIZATION).and_then(|v| v.to_str().ok()) != Some(&bearer) {
+            return StatusCode::FORBIDDEN.into_response();
+        }
+        if stub.revoked.lock().unwrap().contains(&request.token) {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+        let roles = match request.token.as_str() {
+            USER_TOKEN => vec!["user".to_owned()],
+            GUEST_TOKEN => Vec::new(),
+            _ => return StatusCode::UNAUTHORIZED.into_response(),
+        };
+        Json(Claims {
+            sub: "jane@example.com".to_owned(),
+            exp: 4_000_000_000,
+            iat: 1_700_000_000,
+            roles,
+            iss: None,
+            tenant: None,
+        })
+        .into_response()
+    }
+
+    // Tells our client which service a service token was issued to
+    async fn introspect(Form(request): Form<IntrospectRequest>) -> impl IntoResponse {
+        let client_id = match request.token.as_deref() {
+            Some(SERVICE_TOKEN) => CLIENT_ID,
+            Some(AUTH_SERVICE_TOKEN) => AUTH_SERVICE_CLIENT_ID,
+            _ => return Json(IntrospectResponse::default()),
+        };
+        Json(IntrospectResponse {
+            active: true,
+            sub: Some(client_id.to_owned()),
+            client_id: Some(client_id.to_owned()),
+            ..Default::default()
+        })
+    }
+
+    let router = Router::new()
+        .route("/token", post(token))
+        .route("/verify-token", post(verify_token))
+        .route("/introspect", post(introspect))
+        .route("/health/live", get(|| async { "up" }))
+        .with_state(stub);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+    address
+}
@@ -0,0 +1,5 @@
+mod health;
+mod helpers;
+mod protected;
+mod root;
+mod token_cache;
@@ -0,0 +1,70 @@
+use app_service::routes::ProtectedRouteResponse;
+
+use crate::helpers::{TestApp, GUEST_TOKEN, PROTECTED_RESOURCE_URL, USER_TOKEN};
+
+#[tokio::test]
+async fn should_return_401_without_a_token() {
+    let app = TestApp::new().await;
+
+    let response = app.get_protected(None).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_return_401_with_an_invalid_token() {
+    let app = TestApp::new().await;
+
+    let response = app.get_protected(Some("forged-token")).await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_return_200_with_a_valid_token() {
+    let app = TestApp::new().await;
+
+    let response = app.get_protected(Some(USER_TOKEN)).await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response
+        .json::<ProtectedRouteResponse>()
+        .await
+        .expect("Could not deserialize response body to ProtectedRouteResponse");
+    assert_eq!(body.img_url, PROTECTED_RESOURCE_URL);
+}
+
+#[tokio::test]
+async fn should_accept_the_auth_cookie() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .http_client
+        .get(format!("{}/protected", &app.address))
+        .header("cookie", format!("jwt={}", USER_TOKEN))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_return_403_without_the_required_role() {
+    let app = TestApp::new().await;
+
+    let response = app.get_protected(Some(GUEST_TOKEN)).await;
+
+    assert_eq!(response.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn should_return_503_if_auth_service_is_down() {
+    let app = TestApp::with_auth_service_down().await;
+
+    let response = app.get_protected(Some(USER_TOKEN)).await;
+
+    assert_eq!(response.status().as_u16(), 503);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["error"], "Authentication service unavailable, try again later");
+}
@@ -0,0 +1,13 @@
+use crate::helpers::{TestApp, AUTH_PUBLIC_URL};
+
+#[tokio::test]
+async fn root_links_to_auth_service() {
+    let app = TestApp::new().await;
+
+    let response = app.get_root().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response.text().await.unwrap();
+    assert!(body.contains(&format!("href=\"{}\"", AUTH_PUBLIC_URL)));
+    assert!(body.contains(&format!("href=\"{}/logout\"", AUTH_PUBLIC_URL)));
+}
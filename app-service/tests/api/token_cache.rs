@@ -0,0 +1,75 @@
+use crate::helpers::{TestApp, AUTH_SERVICE_TOKEN, SERVICE_TOKEN, USER_TOKEN};
+
+#[tokio::test]
+async fn should_verify_a_token_once_while_cached() {
+    let app = TestApp::new().await;
+
+    assert_eq!(app.get_protected(Some(USER_TOKEN)).await.status().as_u16(), 200);
+    assert_eq!(app.get_protected(Some(USER_TOKEN)).await.status().as_u16(), 200);
+
+    assert_eq!(app.verify_token_calls(), 1);
+}
+
+#[tokio::test]
+async fn should_not_cache_rejected_tokens() {
+    let app = TestApp::new().await;
+
+    assert_eq!(app.get_protected(Some("forged-token")).await.status().as_u16(), 401);
+    assert_eq!(app.get_protected(Some("forged-token")).await.status().as_u16(), 401);
+
+    assert_eq!(app.verify_token_calls(), 2);
+}
+
+#[tokio::test]
+async fn should_check_a_revoked_token_again() {
+    let app = TestApp::new().await;
+    app.get_protected(Some(USER_TOKEN)).await;
+
+    let response = app.post_revocation(USER_TOKEN, Some(AUTH_SERVICE_TOKEN)).await;
+    assert_eq!(response.status().as_u16(), 204);
+
+    app.get_protected(Some(USER_TOKEN)).await;
+    assert_eq!(app.verify_token_calls(), 2);
+}
+
+#[tokio::test]
+async fn should_reject_a_token_as_soon_as_its_logout_is_pushed() {
+    let app = TestApp::new().await;
+    assert_eq!(app.get_protected(Some(USER_TOKEN)).await.status().as_u16(), 200);
+
+    // Until auth-service pushes the logout, the cached verification stands
+    app.log_out(USER_TOKEN);
+    assert_eq!(app.get_protected(Some(USER_TOKEN)).await.status().as_u16(), 200);
+
+    app.post_revocation(USER_TOKEN, Some(AUTH_SERVICE_TOKEN)).await;
+    assert_eq!(app.get_protected(Some(USER_TOKEN)).await.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_only_take_revocations_from_auth_service() {
+    let app = TestApp::new().await;
+    app.get_protected(Some(USER_TOKEN)).await;
+
+    // Without a service token, with a user token, or with another service's
+    for (service_token, status) in [(None, 401), (Some(USER_TOKEN), 401), (Some(SERVICE_TOKEN), 403)] {
+        let response = app.post_revocation(USER_TOKEN, service_token).await;
+        assert_eq!(response.status().as_u16(), status, "Failed for {:?}", service_token);
+    }
+
+    // The cached verification is still used
+    app.get_protected(Some(USER_TOKEN)).await;
+    assert_eq!(app.verify_token_calls(), 1);
+}
+
+#[tokio::test]
+async fn should_expose_hit_and_miss_counters() {
+    let app = TestApp::new().await;
+    app.get_protected(Some(USER_TOKEN)).await;
+    app.get_protected(Some(USER_TOKEN)).await;
+
+    let body = app.get_metrics().await.text().await.unwrap();
+
+    assert!(body.contains("auth_token_cache_hits_total 1"));
+    assert!(body.contains("auth_token_cache_misses_total 1"));
+    assert!(body.contains("auth_token_cache_entries 1"));
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::Claims;
+
+// How long, and how many, successful verifications are remembered
+#[derive(Debug, Clone)]
+pub struct CacheOptions {
+    // Upper bound on how long a revoked token may still be accepted. Entries
+    // never outlive the token's own `exp`.
+    pub ttl: Duration,
+    // The least recently used entry is evicted to make room beyond this
+    pub max_entries: usize,
+}
+
+impl Default for CacheOptions {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(30),
+            max_entries: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+// Tokens are only kept as hashes, so a memory dump does not leak them
+type TokenHash = [u8; 32];
+
+struct Entry {
+    claims: Claims,
+    expires_at: Instant,
+    // Until then the entry is kept, stale, for `FailurePolicy::Open`
+    token_expires_at: Instant,
+    // Position in `State::recency`
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct State {
+    entries: HashMap<TokenHash, Entry>,
+    // Least recently used first
+    recency: BTreeMap<u64, TokenHash>,
+    clock: u64,
+}
+
+impl State {
+    fn touch(&mut self, key: TokenHash) {
+        self.clock += 1;
+        let clock = self.clock;
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.recency.remove(&entry.last_used);
+            entry.last_used = clock;
+            self.recency.insert(clock, key);
+        }
+    }
+
+    fn remove(&mut self, key: &TokenHash) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.recency.remove(&entry.last_used);
+        }
+    }
+}
+
+// Successful verifications, so that a token is not sent to auth-service on every
+// request. Failures are never cached.
+pub(crate) struct VerificationCache {
+    options: CacheOptions,
+    state: Mutex<State>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl VerificationCache {
+    pub(crate) fn new(options: CacheOptions) -> Self {
+        Self {
+            options,
+            state: Mutex::new(State::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub(crate) fn get(&self, token: &str) -> Option<Claims> {
+        let key = hash(token);
+        let mut state = self.state.lock().unwrap();
+
+        let now = Instant::now();
+        let claims = match state.entries.get(&key) {
+            Some(entry) if entry.expires_at > now => Some(entry.claims.clone()),
+            Some(entry) if entry.token_expires_at <= now => {
+                state.remove(&key);
+                None
+            }
+            _ => None,
+        };
+
+        match claims {
+            Some(claims) => {
+                state.touch(key);
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(claims)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    // A verification whose TTL is over, as long as the token itself has not expired
+    pub(crate) fn get_stale(&self, token: &str) -> Option<Claims> {
+        let state = self.state.lock().unwrap();
+        state
+            .entries
+            .get(&hash(token))
+            .filter(|entry| entry.token_expires_at > Instant::now())
+            .map(|entry| entry.claims.clone())
+    }
+
+    pub(crate) fn insert(&self, token: &str, claims: &Claims) {
+        let time_to_expiry = time_to_expiry(claims.exp);
+        let ttl = self.options.ttl.min(time_to_expiry);
+        if ttl.is_zero() || self.options.max_entries == 0 {
+            return;
+        }
+
+        let key = hash(token);
+        let mut state = self.state.lock().unwrap();
+        state.remove(&key);
+        while state.entries.len() >= self.options.max_entries {
+            let Some((_, oldest)) = state.recency.pop_first() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+        }
+
+        state.entries.insert(
+            key,
+            Entry {
+                claims: claims.clone(),
+                expires_at: Instant::now() + ttl,
+                token_expires_at: Instant::now() + time_to_expiry,
+                last_used: 0,
+            },
+        );
+        state.touch(key);
+    }
+
+    // Forget a token, e.g. once auth-service reports it revoked
+    pub(crate) fn remove(&self, token: &str) {
+        self.state.lock().unwrap().remove(&hash(token));
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.state.lock().unwrap().entries.len(),
+        }
+    }
+}
+
+fn hash(token: &str) -> TokenHash {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+fn time_to_expiry(exp: usize) -> Duration {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Duration::from_secs((exp as u64).saturating_sub(now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(sub: &str, exp_in: u64) -> Claims {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+        Claims {
+            sub: sub.to_owned(),
+            exp: (now + exp_in) as usize,
+            iat: now as usize,
+            roles: vec!["user".to_owned()],
+            iss: None,
+            tenant: None,
+        }
+    }
+
+    fn cache(max_entries: usize) -> VerificationCache {
+        VerificationCache::new(CacheOptions {
+            ttl: Duration::from_secs(60),
+            max_entries,
+        })
+    }
+
+    #[test]
+    fn test_counts_hits_and_misses() {
+        let cache = cache(10);
+        assert_eq!(cache.get("a"), None);
+
+        cache.insert("a", &claims("alice", 600));
+        assert_eq!(cache.get("a").map(|c| c.sub), Some("alice".to_owned()));
+        assert_eq!(cache.get("b"), None);
+
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 2,
+                entries: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_never_outlives_the_token() {
+        let cache = cache(10);
+
+        cache.insert("expired", &claims("alice", 0));
+        assert_eq!(cache.get("expired"), None);
+        assert_eq!(cache.stats().entries, 0);
+
+        cache.insert("short-lived", &claims("bob", 5));
+        let state = cache.state.lock().unwrap();
+        let expires_at = state.entries[&hash("short-lived")].expires_at;
+        assert!(expires_at <= Instant::now() + Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_keeps_stale_entries_until_the_token_expires() {
+        let cache = VerificationCache::new(CacheOptions {
+            ttl: Duration::from_millis(1),
+            max_entries: 10,
+        });
+        cache.insert("a", &claims("alice", 600));
+        std::thread::sleep(Duration::from_millis(5));
+
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get_stale("a").map(|c| c.sub), Some("alice".to_owned()));
+        assert_eq!(cache.get_stale("b"), None);
+    }
+
+    #[test]
+    fn test_evicts_the_least_recently_used_entry() {
+        let cache = cache(2);
+        cache.insert("a", &claims("alice", 600));
+        cache.insert("b", &claims("bob", 600));
+
+        // `a` is now more recently used than `b`
+        assert!(cache.get("a").is_some());
+        cache.insert("c", &claims("carol", 600));
+
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.stats().entries, 2);
+    }
+
+    #[test]
+    fn test_removes_revoked_tokens() {
+        let cache = cache(10);
+        cache.insert("a", &claims("alice", 600));
+
+        cache.remove("a");
+
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
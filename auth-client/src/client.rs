@@ -0,0 +1,198 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    cache::{CacheOptions, CacheStats, VerificationCache},
+    jwks::{JwksOptions, JwksVerifier},
+    remote::RemoteVerifier,
+    resilience::{backoff, CircuitBreaker, FailurePolicy, ResilienceOptions},
+    service_token::ServiceCredentials,
+    AuthError, Claims,
+};
+
+// Name auth-service issues its cookie under unless configured otherwise
+const DEFAULT_COOKIE_NAME: &str = "jwt";
+
+// Called after every outgoing request with the endpoint (`verify_token`, `token`,
+// `introspect` or `jwks`), the outcome (status code or `error`) and the latency, e.g. to
+// record metrics
+pub type CallObserver = Arc<dyn Fn(&'static str, &str, Duration) + Send + Sync>;
+
+enum Verifier {
+    Remote(RemoteVerifier),
+    Jwks(JwksVerifier),
+}
+
+// Checks user tokens issued by auth-service. Cheap to clone: clones share the
+// HTTP connection pool, the service token, the cached keys and the circuit breaker.
+#[derive(Clone)]
+pub struct AuthClient {
+    http_client: reqwest::Client,
+    verifier: Arc<Verifier>,
+    cookie_name: String,
+    observer: Option<CallObserver>,
+    cache: Option<Arc<VerificationCache>>,
+    resilience: ResilienceOptions,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl AuthClient {
+    // Verify every token with auth-service's `/verify-token`, authenticating as a
+    // registered service. `base_url` is e.g. `http://auth-service:3000`.
+    pub fn remote(base_url: impl Into<String>, credentials: ServiceCredentials) -> Self {
+        Self::with_verifier(Verifier::Remote(RemoteVerifier::new(
+            base_url.into(),
+            credentials,
+        )))
+    }
+
+    // Verify tokens locally against the key set published at `jwks_url`
+    pub fn jwks(jwks_url: impl Into<String>, options: JwksOptions) -> Self {
+        Self::with_verifier(Verifier::Jwks(JwksVerifier::new(jwks_url.into(), options)))
+    }
+
+    fn with_verifier(verifier: Verifier) -> Self {
+        let resilience = ResilienceOptions::default();
+        Self {
+            http_client: reqwest::Client::new(),
+            verifier: Arc::new(verifier),
+            cookie_name: DEFAULT_COOKIE_NAME.to_owned(),
+            observer: None,
+            cache: None,
+            breaker: Arc::new(CircuitBreaker::new(
+                resilience.failure_threshold,
+                resilience.open_duration,
+            )),
+            resilience,
+        }
+    }
+
+    // Use a preconfigured HTTP client, e.g. one with timeouts
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    // Read the token from this cookie when there is no `Authorization` header
+    pub fn with_cookie_name(mut self, cookie_name: impl Into<String>) -> Self {
+        self.cookie_name = cookie_name.into();
+        self
+    }
+
+    pub fn with_observer(mut self, observer: CallObserver) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    // Remember successful verifications for a while instead of checking every request
+    pub fn with_cache(mut self, options: CacheOptions) -> Self {
+        self.cache = Some(Arc::new(VerificationCache::new(options)));
+        self
+    }
+
+    // Timeouts, retries, circuit breaker and failure policy of the calls made to verify tokens
+    pub fn with_resilience(mut self, options: ResilienceOptions) -> Self {
+        self.breaker = Arc::new(CircuitBreaker::new(
+            options.failure_threshold,
+            options.open_duration,
+        ));
+        self.resilience = options;
+        self
+    }
+
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|cache| cache.stats())
+    }
+
+    // Stop accepting a cached verification of `token`, e.g. because it was revoked.
+    // It is checked again on its next use.
+    pub fn evict(&self, token: &str) {
+        if let Some(cache) = &self.cache {
+            cache.remove(token);
+        }
+    }
+
+    pub fn cookie_name(&self) -> &str {
+        &self.cookie_name
+    }
+
+    // The client id of the service `token` was issued to, e.g. to check that a
+    // call comes from auth-service. Service tokens are never cached.
+    pub async fn verify_service_token(&self, token: &str) -> Result<String, AuthError> {
+        match self.verifier.as_ref() {
+            Verifier::Remote(remote) => {
+                remote
+                    .verify_service_token(&self.http_client, token, self.observer.as_ref())
+                    .await
+            }
+            // Service tokens are signed with auth-service's secret, which is not
+            // published, so they can only be checked remotely
+            Verifier::Jwks(_) => Err(AuthError::InvalidToken),
+        }
+    }
+
+    pub async fn verify(&self, token: &str) -> Result<Claims, AuthError> {
+        if let Some(claims) = self.cache.as_ref().and_then(|cache| cache.get(token)) {
+            return Ok(claims);
+        }
+
+        match self.verify_with_retries(token).await {
+            Ok(claims) => {
+                if let Some(cache) = &self.cache {
+                    cache.insert(token, &claims);
+                }
+                Ok(claims)
+            }
+            Err(AuthError::Unavailable(cause)) => {
+                let stale = match self.resilience.failure_policy {
+                    FailurePolicy::Open => self.cache.as_ref().and_then(|cache| cache.get_stale(token)),
+                    FailurePolicy::Closed => None,
+                };
+                match stale {
+                    Some(claims) => {
+                        eprintln!("Accepting a previously verified token: {}", cause);
+                        Ok(claims)
+                    }
+                    None => Err(AuthError::Unavailable(cause)),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    // Only failures to reach a verdict are retried; a rejected token is final
+    async fn verify_with_retries(&self, token: &str) -> Result<Claims, AuthError> {
+        let mut attempt = 0;
+        loop {
+            if !self.breaker.allow() {
+                return Err(AuthError::Unavailable("circuit breaker is open".to_owned()));
+            }
+
+            let result = tokio::time::timeout(self.resilience.timeout, self.verify_once(token))
+                .await
+                .unwrap_or_else(|_| Err(AuthError::Unavailable("timed out".to_owned())));
+
+            match result {
+                Err(AuthError::Unavailable(cause)) => {
+                    self.breaker.record_failure();
+                    if attempt >= self.resilience.retries {
+                        return Err(AuthError::Unavailable(cause));
+                    }
+                    tokio::time::sleep(backoff(self.resilience.backoff, attempt)).await;
+                    attempt += 1;
+                }
+                result => {
+                    self.breaker.record_success();
+                    return result;
+                }
+            }
+        }
+    }
+
+    async fn verify_once(&self, token: &str) -> Result<Claims, AuthError> {
+        let observer = self.observer.as_ref();
+        match self.verifier.as_ref() {
+            Verifier::Remote(remote) => remote.verify(&self.http_client, token, observer).await,
+            Verifier::Jwks(jwks) => jwks.verify(&self.http_client, token, observer).await,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+// Why a request could not be authenticated. Every service maps these to the
+// same responses: 401 without a valid token, 403 without the required role,
+// and 503 when the token could not be checked at all.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthError {
+    MissingToken,
+    InvalidToken,
+    Forbidden,
+    // auth-service or the JWKS could not be reached, or answered unexpectedly
+    Unavailable(String),
+}
+
+impl AuthError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AuthError::MissingToken | AuthError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AuthError::Forbidden => StatusCode::FORBIDDEN,
+            AuthError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    // Message returned to the caller; the cause of `Unavailable` stays in the logs
+    pub fn message(&self) -> &'static str {
+        match self {
+            AuthError::MissingToken => "Missing auth token",
+            AuthError::InvalidToken => "Invalid auth token",
+            AuthError::Forbidden => "Forbidden",
+            AuthError::Unavailable(_) => "Authentication service unavailable, try again later",
+        }
+    }
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::Unavailable(cause) => write!(f, "{}: {}", self.message(), cause),
+            _ => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+// Same shape as auth-service's own error bodies
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: &'static str,
+}
+
+impl IntoResponse for AuthError {
+    fn into_response(self) -> Response {
+        if let AuthError::Unavailable(cause) = &self {
+            eprintln!("Failed to verify token: {}", cause);
+        }
+        let body = Json(ErrorResponse {
+            error: self.message(),
+        });
+        (self.status(), body).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_maps_errors_to_statuses() {
+        assert_eq!(AuthError::MissingToken.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(AuthError::InvalidToken.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(AuthError::Forbidden.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            AuthError::Unavailable("timeout".to_owned()).status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use axum::{
+    extract::{FromRef, FromRequestParts, Request, State},
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::CookieJar;
+
+use crate::{AuthClient, AuthError, Claims};
+
+// A request whose token has been verified. As an extractor it reads the token
+// from `Authorization: Bearer` or, failing that, the auth cookie, and rejects
+// the request with a 401 when it is missing or invalid.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser {
+    pub claims: Claims,
+    pub token: String,
+}
+
+impl AuthenticatedUser {
+    // 403 unless the user holds `role`
+    pub fn require_role(&self, role: &str) -> Result<(), AuthError> {
+        if self.claims.has_role(role) {
+            Ok(())
+        } else {
+            Err(AuthError::Forbidden)
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for AuthenticatedUser
+where
+    AuthClient: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        // Already verified by the `authenticate` middleware
+        if let Some(user) = parts.extensions.get::<AuthenticatedUser>() {
+            return Ok(user.clone());
+        }
+
+        let client = AuthClient::from_ref(state);
+        let user = verify_request(&client, &parts.headers).await?;
+        parts.extensions.insert(user.clone());
+        Ok(user)
+    }
+}
+
+// Middleware requiring a valid token on every route it wraps, for use with
+// `middleware::from_fn_with_state(auth_client, authenticate)`. Handlers can
+// still take `AuthenticatedUser` without verifying the token a second time.
+pub async fn authenticate(State(client): State<AuthClient>, mut request: Request, next: Next) -> Response {
+    match verify_request(&client, request.headers()).await {
+        Ok(user) => {
+            request.extensions_mut().insert(user);
+            next.run(request).await
+        }
+        Err(e) => e.into_response(),
+    }
+}
+
+async fn verify_request(client: &AuthClient, headers: &HeaderMap) -> Result<AuthenticatedUser, AuthError> {
+    let token = request_token(headers, client.cookie_name()).ok_or(AuthError::MissingToken)?;
+    let claims = client.verify(&token).await?;
+    Ok(AuthenticatedUser { claims, token })
+}
+
+fn request_token(headers: &HeaderMap, cookie_name: &str) -> Option<String> {
+    let bearer = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty());
+    if let Some(token) = bearer {
+        return Some(token.to_owned());
+    }
+
+    CookieJar::from_headers(headers)
+        .get(cookie_name)
+        .map(|cookie| cookie.value().to_owned())
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_prefers_bearer_over_cookie() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(request_token(&headers, "jwt"), None);
+
+        headers.insert("cookie", HeaderValue::from_static("other=1; jwt=from-cookie"));
+        assert_eq!(request_token(&headers, "jwt"), Some("from-cookie".to_owned()));
+        assert_eq!(request_token(&headers, "session"), None);
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer from-header"));
+        assert_eq!(request_token(&headers, "jwt"), Some("from-header".to_owned()));
+    }
+
+    #[test]
+    fn test_ignores_empty_tokens() {
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer "));
+        headers.insert("cookie", HeaderValue::from_static("jwt="));
+
+        assert_eq!(request_token(&headers, "jwt"), None);
+    }
+}
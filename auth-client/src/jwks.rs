@@ -0,0 +1,177 @@
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use jsonwebtoken::{
+    decode, decode_header,
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, Validation,
+};
+use tokio::sync::RwLock;
+
+use crate::{client::CallObserver, AuthError, Claims};
+
+// How local verification treats the key set and the tokens it checks
+#[derive(Debug, Clone)]
+pub struct JwksOptions {
+    // Required `iss` claim, if any
+    pub issuer: Option<String>,
+    // Required `aud` claim, if any. Tokens carrying an audience are refused when unset.
+    pub audience: Option<String>,
+    // How long a fetched key set is used before it is fetched again
+    pub cache_ttl: Duration,
+    // Tokens signed with an unknown `kid` trigger a refetch, at most this often
+    pub min_refresh_interval: Duration,
+}
+
+impl Default for JwksOptions {
+    fn default() -> Self {
+        Self {
+            issuer: None,
+            audience: None,
+            cache_ttl: Duration::from_secs(300),
+            min_refresh_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+struct CachedKeys {
+    keys: JwkSet,
+    fetched_at: Instant,
+}
+
+// Verifies tokens locally with the public keys published at a JWKS URL. No round
+// trip per check, but revoked tokens stay valid here until they expire.
+pub(crate) struct JwksVerifier {
+    url: String,
+    options: JwksOptions,
+    cache: RwLock<Option<CachedKeys>>,
+}
+
+impl JwksVerifier {
+    pub(crate) fn new(url: String, options: JwksOptions) -> Self {
+        Self {
+            url,
+            options,
+            cache: RwLock::new(None),
+        }
+    }
+
+    pub(crate) async fn verify(
+        &self,
+        http_client: &reqwest::Client,
+        token: &str,
+        observer: Option<&CallObserver>,
+    ) -> Result<Claims, AuthError> {
+        let header = decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+
+        // A shared secret can never come from a public key set, so an HMAC token
+        // here would be forged with the public key as the secret
+        if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+            return Err(AuthError::InvalidToken);
+        }
+
+        let jwk = self
+            .find_key(http_client, header.kid.as_deref(), observer)
+            .await?
+            .ok_or(AuthError::InvalidToken)?;
+
+        if let Some(key_algorithm) = jwk.common.key_algorithm {
+            if Algorithm::from_str(&key_algorithm.to_string()).ok() != Some(header.alg) {
+                return Err(AuthError::InvalidToken);
+            }
+        }
+
+        let key = DecodingKey::from_jwk(&jwk).map_err(|_| AuthError::InvalidToken)?;
+
+        let mut validation = Validation::new(header.alg);
+        let mut required_claims = vec!["exp"];
+        if let Some(issuer) = &self.options.issuer {
+            validation.set_issuer(&[issuer]);
+            required_claims.push("iss");
+        }
+        match &self.options.audience {
+            Some(audience) => {
+                validation.set_audience(&[audience]);
+                required_claims.push("aud");
+            }
+            None => validation.validate_aud = false,
+        }
+        validation.set_required_spec_claims(&required_claims);
+
+        decode::<Claims>(token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|_| AuthError::InvalidToken)
+    }
+
+    // Look the key up in the cached set, refetching it when it is stale or, to
+    // pick up rotated keys, when the key is unknown
+    async fn find_key(
+        &self,
+        http_client: &reqwest::Client,
+        kid: Option<&str>,
+        observer: Option<&CallObserver>,
+    ) -> Result<Option<Jwk>, AuthError> {
+        {
+            let cache = self.cache.read().await;
+            if let Some(cached) = cache.as_ref() {
+                let age = cached.fetched_at.elapsed();
+                let key = lookup(&cached.keys, kid);
+                let fresh = age < self.options.cache_ttl;
+                if fresh && (key.is_some() || age < self.options.min_refresh_interval) {
+                    return Ok(key);
+                }
+            }
+        }
+
+        let mut cache = self.cache.write().await;
+        // Another request may have refreshed the set while we waited for the lock
+        if let Some(cached) = cache.as_ref() {
+            if cached.fetched_at.elapsed() < self.options.min_refresh_interval {
+                return Ok(lookup(&cached.keys, kid));
+            }
+        }
+
+        let keys = self.fetch(http_client, observer).await?;
+        let key = lookup(&keys, kid);
+        *cache = Some(CachedKeys {
+            keys,
+            fetched_at: Instant::now(),
+        });
+        Ok(key)
+    }
+
+    async fn fetch(
+        &self,
+        http_client: &reqwest::Client,
+        observer: Option<&CallObserver>,
+    ) -> Result<JwkSet, AuthError> {
+        let started = Instant::now();
+        let result = http_client.get(&self.url).send().await;
+        if let Some(observer) = observer {
+            let outcome = match &result {
+                Ok(r) => r.status().as_u16().to_string(),
+                Err(_) => "error".to_owned(),
+            };
+            observer("jwks", &outcome, started.elapsed());
+        }
+
+        let response = result
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AuthError::Unavailable(format!("failed to fetch JWKS: {}", e)))?;
+        response
+            .json::<JwkSet>()
+            .await
+            .map_err(|e| AuthError::Unavailable(format!("invalid JWKS: {}", e)))
+    }
+}
+
+// Tokens without a `kid` can only be matched when the set holds a single key
+fn lookup(keys: &JwkSet, kid: Option<&str>) -> Option<Jwk> {
+    match kid {
+        Some(kid) => keys.find(kid).cloned(),
+        None if keys.keys.len() == 1 => keys.keys.first().cloned(),
+        None => None,
+    }
+}
@@ -0,0 +1,34 @@
+// Authentication for services sitting behind auth-service.
+//
+// Build an `AuthClient` once, put it in the router state, and take
+// `AuthenticatedUser` in any handler that needs a logged-in user:
+//
+//     let auth = AuthClient::remote("http://auth-service:3000", credentials);
+//     let app = Router::new().route("/protected", get(handler)).with_state(auth);
+//
+//     async fn handler(user: AuthenticatedUser) -> Result<String, AuthError> {
+//         user.require_role("user")?;
+//         Ok(user.claims.sub)
+//     }
+//
+// Tokens are checked either remotely, by calling auth-service's `/verify-token`,
+// or locally against the signing keys auth-service publishes as a JWKS. Either
+// way, successful checks can be cached with `AuthClient::with_cache`.
+
+mod cache;
+mod client;
+mod error;
+mod extract;
+mod jwks;
+mod remote;
+mod resilience;
+mod service_token;
+
+pub use auth_sdk::Claims;
+pub use cache::{CacheOptions, CacheStats};
+pub use client::{AuthClient, CallObserver};
+pub use error::AuthError;
+pub use extract::{authenticate, AuthenticatedUser};
+pub use jwks::JwksOptions;
+pub use resilience::{FailurePolicy, ResilienceOptions};
+pub use service_token::{ServiceCredentials, ServiceTokenError};
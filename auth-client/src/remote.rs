@@ -0,0 +1,129 @@
+use std::time::Instant;
+
+use auth_sdk::{IntrospectResponse, VerifyTokenRequest};
+
+use crate::{
+    client::CallObserver,
+    service_token::{ServiceCredentials, ServiceTokenCache},
+    AuthError, Claims,
+};
+
+// Verifies tokens by asking auth-service's `/verify-token`. Always up to date,
+// including revocations, at the cost of a round trip per check.
+pub(crate) struct RemoteVerifier {
+    base_url: String,
+    service_token: ServiceTokenCache,
+}
+
+impl RemoteVerifier {
+    pub(crate) fn new(base_url: String, credentials: ServiceCredentials) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            service_token: ServiceTokenCache::new(credentials),
+        }
+    }
+
+    pub(crate) async fn verify(
+        &self,
+        http_client: &reqwest::Client,
+        token: &str,
+        observer: Option<&CallObserver>,
+    ) -> Result<Claims, AuthError> {
+        let url = format!("{}/verify-token", self.base_url);
+        let body = VerifyTokenRequest {
+            token: token.to_owned(),
+        };
+
+        // auth-service only verifies tokens for registered services. A 403 means our
+        // cached service token was refused, so fetch a fresh one and try once more.
+        for _ in 0..2 {
+            let service_token = self
+                .service_token
+                .get(http_client, &self.base_url, observer)
+                .await
+                .map_err(|e| AuthError::Unavailable(format!("no service token: {}", e)))?;
+
+            let started = Instant::now();
+            let result = http_client
+                .post(&url)
+                .bearer_auth(service_token)
+                .json(&body)
+                .send()
+                .await;
+            if let Some(observer) = observer {
+                let outcome = match &result {
+                    Ok(r) => r.status().as_u16().to_string(),
+                    Err(_) => "error".to_owned(),
+                };
+                observer("verify_token", &outcome, started.elapsed());
+            }
+            let response = result.map_err(|e| AuthError::Unavailable(e.to_string()))?;
+
+            match response.status() {
+                reqwest::StatusCode::FORBIDDEN => self.service_token.invalidate().await,
+                reqwest::StatusCode::OK => {
+                    return response
+                        .json::<Claims>()
+                        .await
+                        .map_err(|e| AuthError::Unavailable(e.to_string()));
+                }
+                reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::BAD_REQUEST => {
+                    return Err(AuthError::InvalidToken);
+                }
+                status => {
+                    return Err(AuthError::Unavailable(format!(
+                        "unexpected status {} from /verify-token",
+                        status
+                    )))
+                }
+            }
+        }
+
+        Err(AuthError::Unavailable(
+            "auth-service refused our service token".to_owned(),
+        ))
+    }
+
+    // Asks auth-service's `/introspect` who `token` was issued to, if it is an
+    // active service token
+    pub(crate) async fn verify_service_token(
+        &self,
+        http_client: &reqwest::Client,
+        token: &str,
+        observer: Option<&CallObserver>,
+    ) -> Result<String, AuthError> {
+        let credentials = self.service_token.credentials();
+
+        let started = Instant::now();
+        let result = http_client
+            .post(format!("{}/introspect", self.base_url))
+            .basic_auth(&credentials.client_id, Some(&credentials.client_secret))
+            .form(&[("token", token), ("token_type_hint", "service_token")])
+            .send()
+            .await;
+        if let Some(observer) = observer {
+            let outcome = match &result {
+                Ok(r) => r.status().as_u16().to_string(),
+                Err(_) => "error".to_owned(),
+            };
+            observer("introspect", &outcome, started.elapsed());
+        }
+        let response = result.map_err(|e| AuthError::Unavailable(e.to_string()))?;
+
+        if response.status() != reqwest::StatusCode::OK {
+            return Err(AuthError::Unavailable(format!(
+                "unexpected status {} from /introspect",
+                response.status()
+            )));
+        }
+        let description = response
+            .json::<IntrospectResponse>()
+            .await
+            .map_err(|e| AuthError::Unavailable(e.to_string()))?;
+
+        match (description.active, description.client_id) {
+            (true, Some(client_id)) => Ok(client_id),
+            _ => Err(AuthError::InvalidToken),
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// What happens to requests while tokens cannot be checked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    // Refuse them with a 503
+    #[default]
+    Closed,
+    // Keep accepting tokens that were verified before, from the cache, until
+    // they expire. Tokens never seen are still refused.
+    Open,
+}
+
+// How calls to auth-service or the JWKS cope with failures
+#[derive(Debug, Clone)]
+pub struct ResilienceOptions {
+    // Upper bound on each attempt
+    pub timeout: Duration,
+    // Further attempts after a failed one
+    pub retries: u32,
+    // Delay before the first retry, doubled for each one after, with full jitter
+    pub backoff: Duration,
+    // Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    // How long an open circuit fails fast before letting a trial call through
+    pub open_duration: Duration,
+    pub failure_policy: FailurePolicy,
+}
+
+impl Default for ResilienceOptions {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            retries: 2,
+            backoff: Duration::from_millis(100),
+            failure_threshold: 5,
+            open_duration: Duration::from_secs(10),
+            failure_policy: FailurePolicy::Closed,
+        }
+    }
+}
+
+// Random delay before retry number `attempt` (from 0)
+pub(crate) fn backoff(base: Duration, attempt: u32) -> Duration {
+    let cap = base.saturating_mul(2u32.saturating_pow(attempt));
+    let millis = u64::try_from(cap.as_millis()).unwrap_or(u64::MAX);
+    Duration::from_millis(rand::random_range(0..=millis))
+}
+
+#[derive(Debug, PartialEq)]
+enum BreakerState {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    // A single trial call is in flight; its outcome closes or reopens the circuit
+    HalfOpen { since: Instant },
+}
+
+// Stops calling a dependency that keeps failing, so requests fail fast instead
+// of each waiting for its own timeouts
+pub(crate) struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold,
+            open_duration,
+            state: Mutex::new(BreakerState::Closed { failures: 0 }),
+        }
+    }
+
+    // Whether a call may go ahead now
+    pub(crate) fn allow(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match *state {
+            BreakerState::Closed { .. } => true,
+            BreakerState::Open { until } if now >= until => {
+                *state = BreakerState::HalfOpen { since: now };
+                true
+            }
+            BreakerState::Open { .. } => false,
+            // The trial may have been abandoned with its request, so allow another in time
+            BreakerState::HalfOpen { since } if now >= since + self.open_duration => {
+                *state = BreakerState::HalfOpen { since: now };
+                true
+            }
+            BreakerState::HalfOpen { .. } => false,
+        }
+    }
+
+    pub(crate) fn record_success(&self) {
+        *self.state.lock().unwrap() = BreakerState::Closed { failures: 0 };
+    }
+
+    pub(crate) fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap();
+        let open = BreakerState::Open {
+            until: Instant::now() + self.open_duration,
+        };
+        *state = match *state {
+            BreakerState::Closed { failures } if failures + 1 < self.failure_threshold => {
+                BreakerState::Closed {
+                    failures: failures + 1,
+                }
+            }
+            BreakerState::Open { until } => BreakerState::Open { until },
+            _ => open,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(60));
+
+        breaker.record_failure();
+        breaker.record_failure();
+        breaker.record_success();
+        breaker.record_failure();
+        breaker.record_failure();
+        assert!(breaker.allow());
+
+        breaker.record_failure();
+        assert!(!breaker.allow());
+    }
+
+    #[test]
+    fn test_lets_one_trial_through_once_the_open_period_is_over() {
+        let breaker = CircuitBreaker::new(1, Duration::ZERO);
+        breaker.record_failure();
+
+        assert!(breaker.allow());
+        assert!(matches!(*breaker.state.lock().unwrap(), BreakerState::HalfOpen { .. }));
+
+        breaker.record_failure();
+        assert!(matches!(*breaker.state.lock().unwrap(), BreakerState::Open { .. }));
+
+        assert!(breaker.allow());
+        breaker.record_success();
+        assert_eq!(*breaker.state.lock().unwrap(), BreakerState::Closed { failures: 0 });
+    }
+
+    #[test]
+    fn test_backoff_stays_below_the_doubled_delay() {
+        let base = Duration::from_millis(100);
+        for attempt in 0..4 {
+            assert!(backoff(base, attempt) <= base * 2u32.pow(attempt));
+        }
+    }
+}
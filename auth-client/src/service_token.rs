@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use auth_sdk::TokenResponse;
+use tokio::sync::Mutex;
+
+use crate::client::CallObserver;
+
+// Refresh a little before expiry so a token never expires while in flight
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+// Credentials of the calling service, registered in auth-service's `SERVICE_CLIENTS`
+#[derive(Debug, Clone)]
+pub struct ServiceCredentials {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Debug)]
+pub enum ServiceTokenError {
+    Request(reqwest::Error),
+    Rejected(reqwest::StatusCode),
+}
+
+impl std::fmt::Display for ServiceTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ServiceTokenError::Request(e) => write!(f, "token request failed: {}", e),
+            ServiceTokenError::Rejected(status) => {
+                write!(f, "auth-service rejected our client credentials ({})", status)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ServiceTokenError {}
+
+struct CachedToken {
+    token: String,
+    expires_at: Instant,
+}
+
+// Service token used to authenticate to auth-service's internal endpoints,
+// obtained with the client credentials grant and reused until it nearly expires.
+pub(crate) struct ServiceTokenCache {
+    credentials: ServiceCredentials,
+    cached: Mutex<Option<CachedToken>>,
+}
+
+impl ServiceTokenCache {
+    pub(crate) fn new(credentials: ServiceCredentials) -> Self {
+        Self {
+            credentials,
+            cached: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn credentials(&self) -> &ServiceCredentials {
+        &self.credentials
+    }
+
+    // Return the cached service token, fetching a new one if there is none or it is about to expire
+    pub(crate) async fn get(
+        &self,
+        http_client: &reqwest::Client,
+        base_url: &str,
+        observer: Option<&CallObserver>,
+    ) -> Result<String, ServiceTokenError> {
+        let mut cached = self.cached.lock().await;
+
+        if let Some(token) = cached.as_ref() {
+            if Instant::now() + EXPIRY_MARGIN < token.expires_at {
+                return Ok(token.token.clone());
+            }
+        }
+
+        let started = Instant::now();
+        let response = http_client
+            .post(format!("{}/token", base_url))
+            .basic_auth(&self.credentials.client_id, Some(&self.credentials.client_secret))
+            .form(&[("grant_type", "client_credentials")])
+            .send()
+            .await;
+        if let Some(observer) = observer {
+            let outcome = match &response {
+                Ok(r) => r.status().as_u16().to_string(),
+                Err(_) => "error".to_owned(),
+            };
+            observer("token", &outcome, started.elapsed());
+        }
+        let response = response.map_err(ServiceTokenError::Request)?;
+
+        if !response.status().is_success() {
+            return Err(ServiceTokenError::Rejected(response.status()));
+        }
+
+        let body = response
+            .json::<TokenResponse>()
+            .await
+            .map_err(ServiceTokenError::Request)?;
+
+        *cached = Some(CachedToken {
+            token: body.access_token.clone(),
+            expires_at: Instant::now() + Duration::from_secs(body.expires_in as u64),
+        });
+
+        Ok(body.access_token)
+    }
+
+    // Drop the cached token, e.g. after auth-service refused it
+    pub(crate) async fn invalidate(&self) {
+        *self.cached.lock().await = None;
+    }
+}
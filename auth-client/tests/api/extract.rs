@@ -0,0 +1,119 @@
+use auth_client::{authenticate, AuthClient, AuthError, AuthenticatedUser, ServiceCredentials};
+use axum::{middleware, routing::get, Router};
+
+use crate::helpers::{
+    spawn, spawn_auth_service, Calls, ADMIN_TOKEN, CLIENT_ID, CLIENT_SECRET, USER_TOKEN,
+};
+
+async fn profile(user: AuthenticatedUser) -> String {
+    user.claims.sub
+}
+
+async fn admin(user: AuthenticatedUser) -> Result<&'static str, AuthError> {
+    user.require_role("admin")?;
+    Ok("welcome")
+}
+
+// A downstream service protecting `/profile` and `/admin` with the extractor and
+// everything under `/layered` with the middleware
+async fn spawn_service(calls: Calls) -> String {
+    let auth = AuthClient::remote(
+        spawn_auth_service(calls).await,
+        ServiceCredentials {
+            client_id: CLIENT_ID.to_owned(),
+            client_secret: CLIENT_SECRET.to_owned(),
+        },
+    );
+
+    let layered = Router::new()
+        .route("/layered", get(profile))
+        .route_layer(middleware::from_fn_with_state(auth.clone(), authenticate));
+    let router = Router::new()
+        .route("/profile", get(profile))
+        .route("/admin", get(admin))
+        .merge(layered)
+        .with_state(auth);
+    spawn(router).await
+}
+
+#[tokio::test]
+async fn should_return_401_without_valid_token() {
+    let address = spawn_service(Calls::default()).await;
+    let client = reqwest::Client::new();
+
+    for path in ["/profile", "/admin", "/layered"] {
+        let response = client.get(format!("{}{}", address, path)).send().await.unwrap();
+        assert_eq!(response.status().as_u16(), 401, "{}", path);
+        let body: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(body["error"], "Missing auth token");
+
+        let response = client
+            .get(format!("{}{}", address, path))
+            .bearer_auth("forged")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 401, "{}", path);
+    }
+}
+
+#[tokio::test]
+async fn should_accept_token_from_cookie_or_bearer() {
+    let address = spawn_service(Calls::default()).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/profile", address))
+        .header("cookie", format!("jwt={}", USER_TOKEN))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.text().await.unwrap(), "jane@example.com");
+
+    let response = client
+        .get(format!("{}/profile", address))
+        .bearer_auth(USER_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_return_403_without_required_role() {
+    let address = spawn_service(Calls::default()).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/admin", address))
+        .bearer_auth(USER_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = client
+        .get(format!("{}/admin", address))
+        .bearer_auth(ADMIN_TOKEN)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_verify_once_when_middleware_and_extractor_are_combined() {
+    let calls = Calls::default();
+    let address = spawn_service(calls.clone()).await;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/layered", address))
+        .bearer_auth(USER_TOKEN)
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(Calls::count(&calls.verify_token), 1);
+}
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use auth_client::{AuthClient, AuthError, JwksOptions};
+use jsonwebtoken::{encode, EncodingKey, Header};
+
+use crate::helpers::{now, sign, spawn_jwks, Calls, SIGNING_KEY_ID};
+
+fn claims(exp: u64) -> serde_json::Value {
+    serde_json::json!({
+        "sub": "jane@example.com",
+        "exp": exp,
+        "iat": now(),
+        "roles": ["user"],
+    })
+}
+
+#[tokio::test]
+async fn should_verify_token_signed_with_published_key() {
+    let client = AuthClient::jwks(spawn_jwks(Calls::default()).await, JwksOptions::default());
+    let token = sign(&claims(now() + 600), Some(SIGNING_KEY_ID));
+
+    let claims = client.verify(&token).await.unwrap();
+
+    assert_eq!(claims.sub, "jane@example.com");
+    assert!(claims.has_role("user"));
+}
+
+#[tokio::test]
+async fn should_reject_expired_and_tampered_tokens() {
+    let client = AuthClient::jwks(spawn_jwks(Calls::default()).await, JwksOptions::default());
+
+    let expired = sign(&claims(now() - 600), Some(SIGNING_KEY_ID));
+    assert_eq!(client.verify(&expired).await, Err(AuthError::InvalidToken));
+
+    let token = sign(&claims(now() + 600), Some(SIGNING_KEY_ID));
+    let (rest, _) = token.rsplit_once('.').unwrap();
+    let tampered = format!("{}.{}", rest, "A".repeat(86));
+    assert_eq!(client.verify(&tampered).await, Err(AuthError::InvalidToken));
+}
+
+#[tokio::test]
+async fn should_reject_hmac_tokens() {
+    let client = AuthClient::jwks(spawn_jwks(Calls::default()).await, JwksOptions::default());
+    let token = encode(
+        &Header::default(),
+        &claims(now() + 600),
+        &EncodingKey::from_secret(b"secret"),
+    )
+    .unwrap();
+
+    assert_eq!(client.verify(&token).await, Err(AuthError::InvalidToken));
+}
+
+#[tokio::test]
+async fn should_check_issuer_when_configured() {
+    let options = JwksOptions {
+        issuer: Some("https://auth.example.com".to_owned()),
+        ..JwksOptions::default()
+    };
+    let client = AuthClient::jwks(spawn_jwks(Calls::default()).await, options);
+
+    let mut with_issuer = claims(now() + 600);
+    with_issuer["iss"] = "https://auth.example.com".into();
+    assert!(client.verify(&sign(&with_issuer, Some(SIGNING_KEY_ID))).await.is_ok());
+
+    let without_issuer = sign(&claims(now() + 600), Some(SIGNING_KEY_ID));
+    assert_eq!(client.verify(&without_issuer).await, Err(AuthError::InvalidToken));
+}
+
+#[tokio::test]
+async fn should_cache_keys_and_refetch_for_unknown_kid_at_most_once_per_interval() {
+    let calls = Calls::default();
+    let options = JwksOptions {
+        min_refresh_interval: Duration::from_secs(60),
+        ..JwksOptions::default()
+    };
+    let client = AuthClient::jwks(spawn_jwks(calls.clone()).await, options);
+
+    let token = sign(&claims(now() + 600), Some(SIGNING_KEY_ID));
+    client.verify(&token).await.unwrap();
+    client.verify(&token).await.unwrap();
+    assert_eq!(Calls::count(&calls.jwks), 1);
+
+    let unknown = sign(&claims(now() + 600), Some("rotated-key"));
+    assert_eq!(client.verify(&unknown).await, Err(AuthError::InvalidToken));
+    assert_eq!(client.verify(&unknown).await, Err(AuthError::InvalidToken));
+    assert_eq!(Calls::count(&calls.jwks), 1);
+}
+
+#[tokio::test]
+async fn should_refetch_keys_once_stale() {
+    let calls = Calls::default();
+    let options = JwksOptions {
+        cache_ttl: Duration::ZERO,
+        min_refresh_interval: Duration::ZERO,
+        ..JwksOptions::default()
+    };
+    let client = AuthClient::jwks(spawn_jwks(calls.clone()).await, options);
+
+    let token = sign(&claims(now() + 600), None);
+    client.verify(&token).await.unwrap();
+    client.verify(&token).await.unwrap();
+
+    assert_eq!(Calls::count(&calls.jwks), 2);
+}
+
+#[tokio::test]
+async fn should_report_unavailable_when_jwks_cannot_be_fetched() {
+    let client = AuthClient::jwks("http://127.0.0.1:9/jwks.json", JwksOptions::default());
+    let token = sign(&claims(now() + 600), Some(SIGNING_KEY_ID));
+
+    assert!(matches!(client.verify(&token).await, Err(AuthError::Unavailable(_))));
+}
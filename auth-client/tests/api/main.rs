@@ -0,0 +1,5 @@
+mod extract;
+mod helpers;
+mod jwks;
+mod remote;
+mod resilience;
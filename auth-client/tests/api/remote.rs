@@ -0,0 +1,100 @@
+use auth_client::{AuthClient, AuthError, CacheOptions, CacheStats, ServiceCredentials};
+use auth_sdk::AUTH_SERVICE_CLIENT_ID;
+
+use crate::helpers::{
+    spawn_auth_service, Calls, ADMIN_TOKEN, AUTH_SERVICE_TOKEN, CLIENT_ID, CLIENT_SECRET,
+    USER_TOKEN,
+};
+
+fn credentials(secret: &str) -> ServiceCredentials {
+    ServiceCredentials {
+        client_id: CLIENT_ID.to_owned(),
+        client_secret: secret.to_owned(),
+    }
+}
+
+#[tokio::test]
+async fn should_return_claims_of_valid_token() {
+    let calls = Calls::default();
+    let client = AuthClient::remote(spawn_auth_service(calls.clone()).await, credentials(CLIENT_SECRET));
+
+    let claims = client.verify(ADMIN_TOKEN).await.unwrap();
+
+    assert_eq!(claims.sub, "jane@example.com");
+    assert!(claims.has_role("admin"));
+}
+
+#[tokio::test]
+async fn should_reject_invalid_token() {
+    let client = AuthClient::remote(spawn_auth_service(Calls::default()).await, credentials(CLIENT_SECRET));
+
+    assert_eq!(client.verify("forged").await, Err(AuthError::InvalidToken));
+}
+
+#[tokio::test]
+async fn should_reuse_service_token() {
+    let calls = Calls::default();
+    let client = AuthClient::remote(spawn_auth_service(calls.clone()).await, credentials(CLIENT_SECRET));
+
+    client.verify(USER_TOKEN).await.unwrap();
+    client.clone().verify(USER_TOKEN).await.unwrap();
+
+    assert_eq!(Calls::count(&calls.token), 1);
+    assert_eq!(Calls::count(&calls.verify_token), 2);
+}
+
+#[tokio::test]
+async fn should_cache_successful_verifications_until_evicted() {
+    let calls = Calls::default();
+    let client = AuthClient::remote(spawn_auth_service(calls.clone()).await, credentials(CLIENT_SECRET))
+        .with_cache(CacheOptions::default());
+
+    client.verify(USER_TOKEN).await.unwrap();
+    client.clone().verify(USER_TOKEN).await.unwrap();
+    assert_eq!(Calls::count(&calls.verify_token), 1);
+
+    // Failures are checked every time
+    assert!(client.verify("forged").await.is_err());
+    assert!(client.verify("forged").await.is_err());
+    assert_eq!(Calls::count(&calls.verify_token), 3);
+
+    client.evict(USER_TOKEN);
+    client.verify(USER_TOKEN).await.unwrap();
+    assert_eq!(Calls::count(&calls.verify_token), 4);
+
+    assert_eq!(
+        client.cache_stats(),
+        Some(CacheStats {
+            hits: 1,
+            misses: 4,
+            entries: 1
+        })
+    );
+}
+
+#[tokio::test]
+async fn should_report_unavailable_when_credentials_are_rejected() {
+    let client = AuthClient::remote(spawn_auth_service(Calls::default()).await, credentials("wrong"));
+
+    assert!(matches!(client.verify(USER_TOKEN).await, Err(AuthError::Unavailable(_))));
+}
+
+#[tokio::test]
+async fn should_report_unavailable_when_auth_service_is_down() {
+    // Nothing listens on port 9 (discard) in the test environment
+    let client = AuthClient::remote("http://127.0.0.1:9", credentials(CLIENT_SECRET));
+
+    assert!(matches!(client.verify(USER_TOKEN).await, Err(AuthError::Unavailable(_))));
+}
+
+#[tokio::test]
+async fn should_tell_which_service_a_service_token_belongs_to() {
+    let client = AuthClient::remote(spawn_auth_service(Calls::default()).await, credentials(CLIENT_SECRET));
+
+    assert_eq!(
+        client.verify_service_token(AUTH_SERVICE_TOKEN).await,
+        Ok(AUTH_SERVICE_CLIENT_ID.to_owned())
+    );
+    // A user token is no service token
+    assert_eq!(client.verify_service_token(USER_TOKEN).await, Err(AuthError::InvalidToken));
+}
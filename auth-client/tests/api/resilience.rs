@@ -0,0 +1,126 @@
+use std::{
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
+};
+
+use auth_client::{
+    AuthClient, AuthError, CacheOptions, FailurePolicy, ResilienceOptions, ServiceCredentials,
+};
+
+use crate::helpers::{
+    spawn_auth_service, Calls, ADMIN_TOKEN, CLIENT_ID, CLIENT_SECRET, SLOW_TOKEN, USER_TOKEN,
+};
+
+fn options() -> ResilienceOptions {
+    ResilienceOptions {
+        timeout: Duration::from_secs(5),
+        retries: 0,
+        backoff: Duration::from_millis(1),
+        failure_threshold: 100,
+        open_duration: Duration::from_secs(60),
+        failure_policy: FailurePolicy::Closed,
+    }
+}
+
+async fn client(calls: &Calls, options: ResilienceOptions) -> AuthClient {
+    let credentials = ServiceCredentials {
+        client_id: CLIENT_ID.to_owned(),
+        client_secret: CLIENT_SECRET.to_owned(),
+    };
+    AuthClient::remote(spawn_auth_service(calls.clone()).await, credentials).with_resilience(options)
+}
+
+#[tokio::test]
+async fn should_retry_transient_failures() {
+    let calls = Calls::default();
+    let client = client(&calls, ResilienceOptions { retries: 2, ..options() }).await;
+    calls.outage.store(2, Ordering::SeqCst);
+
+    assert!(client.verify(USER_TOKEN).await.is_ok());
+    assert_eq!(Calls::count(&calls.verify_token), 3);
+}
+
+#[tokio::test]
+async fn should_give_up_after_the_last_retry() {
+    let calls = Calls::default();
+    let client = client(&calls, ResilienceOptions { retries: 1, ..options() }).await;
+    calls.outage.store(5, Ordering::SeqCst);
+
+    assert!(matches!(client.verify(USER_TOKEN).await, Err(AuthError::Unavailable(_))));
+    assert_eq!(Calls::count(&calls.verify_token), 2);
+}
+
+#[tokio::test]
+async fn should_not_retry_rejected_tokens() {
+    let calls = Calls::default();
+    let client = client(&calls, ResilienceOptions { retries: 2, ..options() }).await;
+
+    assert_eq!(client.verify("forged").await, Err(AuthError::InvalidToken));
+    assert_eq!(Calls::count(&calls.verify_token), 1);
+}
+
+#[tokio::test]
+async fn should_time_out_slow_calls() {
+    let calls = Calls::default();
+    let timeout = Duration::from_millis(100);
+    let client = client(&calls, ResilienceOptions { timeout, ..options() }).await;
+
+    let started = Instant::now();
+    let result = client.verify(SLOW_TOKEN).await;
+
+    assert!(matches!(result, Err(AuthError::Unavailable(_))));
+    assert!(started.elapsed() < Duration::from_millis(900));
+}
+
+#[tokio::test]
+async fn should_fail_fast_while_the_circuit_is_open() {
+    let calls = Calls::default();
+    let client = client(&calls, ResilienceOptions { failure_threshold: 2, ..options() }).await;
+    calls.outage.store(100, Ordering::SeqCst);
+
+    for _ in 0..5 {
+        assert!(matches!(client.verify(USER_TOKEN).await, Err(AuthError::Unavailable(_))));
+    }
+
+    assert_eq!(Calls::count(&calls.verify_token), 2);
+}
+
+#[tokio::test]
+async fn should_close_the_circuit_once_auth_service_recovers() {
+    let calls = Calls::default();
+    let options = ResilienceOptions {
+        failure_threshold: 1,
+        open_duration: Duration::from_millis(50),
+        ..options()
+    };
+    let client = client(&calls, options).await;
+    calls.outage.store(1, Ordering::SeqCst);
+
+    assert!(client.verify(USER_TOKEN).await.is_err());
+    assert!(client.verify(USER_TOKEN).await.is_err());
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    assert!(client.verify(USER_TOKEN).await.is_ok());
+    assert!(client.verify(USER_TOKEN).await.is_ok());
+}
+
+#[tokio::test]
+async fn should_accept_previously_verified_tokens_when_failing_open() {
+    let cache = CacheOptions {
+        ttl: Duration::from_millis(1),
+        max_entries: 10,
+    };
+    for (failure_policy, accepted) in [(FailurePolicy::Open, true), (FailurePolicy::Closed, false)] {
+        let calls = Calls::default();
+        let options = ResilienceOptions { failure_policy, ..options() };
+        let client = client(&calls, options).await.with_cache(cache.clone());
+        client.verify(USER_TOKEN).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(5)).await;
+
+        calls.outage.store(100, Ordering::SeqCst);
+
+        assert_eq!(client.verify(USER_TOKEN).await.is_ok(), accepted, "{:?}", failure_policy);
+        // A token never verified before is refused either way
+        assert!(matches!(client.verify(ADMIN_TOKEN).await, Err(AuthError::Unavailable(_))));
+    }
+}
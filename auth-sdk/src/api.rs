@@ -0,0 +1,381 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+// Request and response bodies of the auth-service API. auth-service serializes
+// exactly these types, so the server and its clients cannot disagree on them.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SignupRequest {
+    pub email: String,
+    pub password: String,
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+}
+
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct SignupResponse {
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct LoginRequest {
+    pub email: String,
+    pub password: String,
+}
+
+// The login route can return 2 possible success responses.
+// This enum models each response!
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LoginResponse {
+    RegularAuth,
+    TwoFactorAuth(TwoFactorAuthResponse),
+}
+
+// If a user requires 2FA, this JSON body should be returned!
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TwoFactorAuthResponse {
+    pub message: String,
+    #[serde(rename = "loginAttemptId")]
+    pub login_attempt_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MagicLinkRequest {
+    pub email: String,
+}
+
+// The same answer whether or not an account exists for the email
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MagicLinkResponse {
+    pub message: String,
+}
+
+// The token from an emailed login link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VerifyMagicLinkRequest {
+    pub token: String,
+}
+
+// An upstream identity provider users can log in with, at `/login/oidc/{id}`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct IdentityProviderResponse {
+    pub id: String,
+    pub name: String,
+}
+
+// A public key that verifies the auth tokens of a tenant (RFC 7517). Only the
+// Ed25519 keys auth-service signs with are published.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct JsonWebKey {
+    pub kty: String,
+    pub crv: String,
+    // The public key, base64url-encoded
+    pub x: String,
+    // Names the tenant, like the `kid` header of its tokens
+    pub kid: String,
+    pub alg: String,
+    #[serde(rename = "use")]
+    pub key_use: String,
+}
+
+// The keys answered by `/.well-known/jwks.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct JsonWebKeySet {
+    pub keys: Vec<JsonWebKey>,
+}
+
+// What the identity provider appended to the redirect back to the login page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct OidcCallbackRequest {
+    pub code: String,
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Verify2FARequest {
+    pub email: String,
+    #[serde(rename = "loginAttemptId")]
+    pub login_attempt_id: String,
+    #[serde(rename = "2FACode")]
+    pub two_fa_code: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct VerifyTokenRequest {
+    pub token: String,
+}
+
+// Claims of a user token, as answered by `/verify-token`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+    // Absent from tokens of issuers that do not set it
+    #[serde(default)]
+    pub iat: usize,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    // The issuing tenant, see auth-service's `TENANTS`; older tokens lack both
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+impl Claims {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+}
+
+// Describes a token, as answered by `/introspect`. Anything that does not
+// validate is simply reported as inactive, without saying why.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct IntrospectResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    // The client a service token was issued to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    // The issuing tenant of a user token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+}
+
+// RFC 7662 introspection request, sent by a registered client as a form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct IntrospectRequest {
+    pub token: Option<String>,
+    // `access_token`, `api_key` or `service_token`: the kind of token to look
+    // for first. Every kind is recognized whatever the hint.
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TokenRequest {
+    pub grant_type: String,
+    pub scope: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+// RFC 7009 revocation request, sent by a registered client as a form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RevokeRequest {
+    pub token: Option<String>,
+    // `access_token` or `api_key`: the kind of token to look for first
+    pub token_type_hint: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+// Client id of the service tokens auth-service issues to itself, e.g. to push
+// revocations to other services. No registered client can take it.
+pub const AUTH_SERVICE_CLIENT_ID: &str = "auth-service";
+
+// Pushed by auth-service, with its service token, to the services that cache
+// verifications once a token was revoked, e.g. by logging out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationRequest {
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct TokenResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: usize,
+    pub scope: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub expires_in_days: u32,
+}
+
+// Describes a stored key. The secret is never part of this response.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ApiKeyResponse {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+// Returned once, when the key is created. Only the hash of `secret` is kept.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct CreateApiKeyResponse {
+    #[serde(flatten)]
+    pub key: ApiKeyResponse,
+    pub secret: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorResponse {
+    pub error: String,
+    // Everything that is wrong with a rejected password, for the UI to explain
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reasons: Vec<PasswordRejection>,
+}
+
+// Why a password was refused. Serialized with a `code` tag so the UI can
+// explain each problem, e.g. `{"code": "too_short", "min_length": 8}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum PasswordRejection {
+    TooShort {
+        min_length: usize,
+    },
+    TooLong {
+        max_length: usize,
+    },
+    // Strength as estimated by zxcvbn, from 0 (guessable in 10^3 tries) to 4 (over 10^10)
+    TooWeak {
+        score: u8,
+        min_score: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        warning: Option<String>,
+        suggestions: Vec<String>,
+    },
+    ContainsEmail,
+    Breached,
+}
+
+// The errors auth-service reports in the `error` field of an `ErrorResponse`.
+// The OAuth endpoints report RFC 6749 error codes there instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    UserAlreadyExists,
+    InvalidCredentials,
+    WeakPassword,
+    IncorrectCredentials,
+    MissingToken,
+    InvalidToken,
+    InvalidApiKeyRequest,
+    ApiKeyNotFound,
+    ServiceAuthRequired,
+    Forbidden,
+    CsrfCheckFailed,
+    TenantNotFound,
+    IdentityProviderNotFound,
+    IdentityProviderFailed,
+    UnverifiedEmail,
+    DirectoryUnavailable,
+    ManagedByDirectory,
+    UnexpectedError,
+}
+
+impl ErrorKind {
+    const ALL: [ErrorKind; 18] = [
+        ErrorKind::UserAlreadyExists,
+        ErrorKind::InvalidCredentials,
+        ErrorKind::WeakPassword,
+        ErrorKind::IncorrectCredentials,
+        ErrorKind::MissingToken,
+        ErrorKind::InvalidToken,
+        ErrorKind::InvalidApiKeyRequest,
+        ErrorKind::ApiKeyNotFound,
+        ErrorKind::ServiceAuthRequired,
+        ErrorKind::Forbidden,
+        ErrorKind::CsrfCheckFailed,
+        ErrorKind::TenantNotFound,
+        ErrorKind::IdentityProviderNotFound,
+        ErrorKind::IdentityProviderFailed,
+        ErrorKind::UnverifiedEmail,
+        ErrorKind::DirectoryUnavailable,
+        ErrorKind::ManagedByDirectory,
+        ErrorKind::UnexpectedError,
+    ];
+
+    pub fn message(&self) -> &'static str {
+        match self {
+            ErrorKind::UserAlreadyExists => "User already exists",
+            ErrorKind::InvalidCredentials => "Invalid credentials",
+            ErrorKind::WeakPassword => "Password does not meet the password policy",
+            ErrorKind::IncorrectCredentials => "Incorrect credentials",
+            ErrorKind::MissingToken => "Missing auth token",
+            ErrorKind::InvalidToken => "Invalid auth token",
+            ErrorKind::InvalidApiKeyRequest => "Invalid API key request",
+            ErrorKind::ApiKeyNotFound => "API key not found",
+            ErrorKind::ServiceAuthRequired => "Service authentication required",
+            ErrorKind::Forbidden => "Forbidden",
+            ErrorKind::CsrfCheckFailed => "CSRF check failed",
+            ErrorKind::TenantNotFound => "Unknown tenant",
+            ErrorKind::IdentityProviderNotFound => "Unknown identity provider",
+            ErrorKind::IdentityProviderFailed => "Login with the identity provider failed",
+            ErrorKind::UnverifiedEmail => "The identity provider did not verify the email",
+            ErrorKind::DirectoryUnavailable => "The user directory is unavailable",
+            ErrorKind::ManagedByDirectory => "Accounts with this email are managed by a directory",
+            ErrorKind::UnexpectedError => "Unexpected error",
+        }
+    }
+
+    pub fn from_message(message: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.message() == message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kind_round_trips_through_its_message() {
+        for kind in ErrorKind::ALL {
+            assert_eq!(ErrorKind::from_message(kind.message()), Some(kind));
+        }
+        assert_eq!(ErrorKind::from_message("invalid_client"), None);
+    }
+
+    #[test]
+    fn test_regular_login_response_is_null() {
+        let json = serde_json::to_value(LoginResponse::RegularAuth).unwrap();
+        assert_eq!(json, serde_json::Value::Null);
+
+        let json = serde_json::json!({ "message": "2FA required", "loginAttemptId": "abc" });
+        let response: LoginResponse = serde_json::from_value(json).unwrap();
+        assert!(matches!(response, LoginResponse::TwoFactorAuth(r) if r.login_attempt_id == "abc"));
+    }
+}
@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use reqwest::{
+    cookie::{CookieStore, Jar},
+    RequestBuilder, Response, StatusCode, Url,
+};
+
+use crate::{
+    ApiKeyResponse, Claims, ClientError, CreateApiKeyRequest, CreateApiKeyResponse,
+    IntrospectRequest, IntrospectResponse, MagicLinkRequest, MagicLinkResponse, RevokeRequest, LoginRequest, SignupRequest, SignupResponse, TokenResponse,
+    TwoFactorAuthResponse, Verify2FARequest, VerifyMagicLinkRequest, VerifyTokenRequest,
+};
+
+// Name auth-service issues its cookie under unless configured otherwise
+const DEFAULT_COOKIE_NAME: &str = "jwt";
+const CSRF_COOKIE_NAME: &str = "csrf_token";
+const CSRF_HEADER_NAME: &str = "x-csrf-token";
+
+#[derive(Debug)]
+pub enum LoginOutcome {
+    // The auth cookie is set; the client is logged in
+    LoggedIn,
+    // A code was emailed; complete the login with `verify_2fa`
+    TwoFactorRequired(TwoFactorAuthResponse),
+}
+
+// Client of the auth-service HTTP API. Like a browser it keeps the cookies
+// auth-service sets, so after `login` the session is used by later calls, and
+// it echoes the CSRF cookie in the header cookie-authenticated requests need.
+// Cheap to clone: clones share the connection pool and the cookies.
+#[derive(Clone)]
+pub struct AuthServiceClient {
+    base_url: Url,
+    http_client: reqwest::Client,
+    cookies: Arc<Jar>,
+    cookie_name: String,
+}
+
+impl AuthServiceClient {
+    // `base_url` is e.g. `http://auth-service:3000`
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        let base_url = Url::parse(base_url.trim_end_matches('/'))
+            .map_err(|_| ClientError::InvalidBaseUrl(base_url.to_owned()))?;
+        let cookies = Arc::new(Jar::default());
+        let http_client = reqwest::Client::builder()
+            .cookie_provider(cookies.clone())
+            .build()?;
+
+        Ok(Self {
+            base_url,
+            http_client,
+            cookies,
+            cookie_name: DEFAULT_COOKIE_NAME.to_owned(),
+        })
+    }
+
+    // The auth cookie name auth-service is configured with, if not `jwt`
+    pub fn with_cookie_name(mut self, cookie_name: impl Into<String>) -> Self {
+        self.cookie_name = cookie_name.into();
+        self
+    }
+
+    // The session token from the auth cookie, if logged in
+    pub fn auth_token(&self) -> Option<String> {
+        self.cookie(&self.cookie_name)
+    }
+
+    pub async fn signup(&self, request: &SignupRequest) -> Result<SignupResponse, ClientError> {
+        let response = self.send(self.post("/signup").json(request)).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn login(&self, request: &LoginRequest) -> Result<LoginOutcome, ClientError> {
+        let response = self.send(self.post("/login").json(request)).await?;
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => Ok(LoginOutcome::TwoFactorRequired(response.json().await?)),
+            _ => Ok(LoginOutcome::LoggedIn),
+        }
+    }
+
+    // Emails a login link, if an account exists for `email`
+    pub async fn request_magic_link(&self, email: &str) -> Result<MagicLinkResponse, ClientError> {
+        let request = MagicLinkRequest {
+            email: email.to_owned(),
+        };
+        let response = self.send(self.post("/login/magic-link").json(&request)).await?;
+        Ok(response.json().await?)
+    }
+
+    // Logs in with the token of an emailed login link
+    pub async fn verify_magic_link(&self, token: &str) -> Result<LoginOutcome, ClientError> {
+        let request = VerifyMagicLinkRequest {
+            token: token.to_owned(),
+        };
+        let response = self
+            .send(self.post("/login/magic-link/verify").json(&request))
+            .await?;
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT => Ok(LoginOutcome::TwoFactorRequired(response.json().await?)),
+            _ => Ok(LoginOutcome::LoggedIn),
+        }
+    }
+
+    pub async fn verify_2fa(&self, request: &Verify2FARequest) -> Result<(), ClientError> {
+        self.send(self.post("/verify-2fa").json(request)).await?;
+        Ok(())
+    }
+
+    // Ends the session: the token is banned and the auth cookie removed
+    pub async fn logout(&self) -> Result<(), ClientError> {
+        self.send(self.post("/logout")).await?;
+        Ok(())
+    }
+
+    // Verifies somebody else's token. Only registered services may do this, so
+    // `service_token` must come from `client_credentials`.
+    pub async fn verify_token(&self, service_token: &str, token: &str) -> Result<Claims, ClientError> {
+        let request = VerifyTokenRequest {
+            token: token.to_owned(),
+        };
+        let response = self
+            .send(self.post("/verify-token").bearer_auth(service_token).json(&request))
+            .await?;
+        Ok(response.json().await?)
+    }
+
+    // Describes somebody else's token (RFC 7662), authenticating as a registered client
+    pub async fn introspect_token(
+        &self,
+        client_id: &str,
+        client_secret: &str,
+        token: &str,
+    ) -> Result<IntrospectResponse, ClientError> {
+        let form = IntrospectRequest {
+            token: Some(token.to_owned()),
+            token_type_hint: None,
+            client_id: None,
+            client_secret: None,
+        };
+        let request = self
+            .post("/introspect")
+            .basic_auth(client_id, Some(client_secret))
+            .form(&form);
+        let response = self.send(request).await?;
+        Ok(response.json().await?)
+    }
+
+    // Revokes a session token or API key (RFC 7009), authenticating as a
+    // registered client. Tokens that are not valid are not an error.
+    pub async fn revoke_token(
+        &self,
+        client_id: &str,
+        client_secret: &str,
+        token: &str,
+        token_type_hint: Option<&str>,
+    ) -> Result<(), ClientError> {
+        let form = RevokeRequest {
+            token: Some(token.to_owned()),
+            token_type_hint: token_type_hint.map(str::to_owned),
+            client_id: None,
+            client_secret: None,
+        };
+        let request = self
+            .post("/revoke")
+            .basic_auth(client_id, Some(client_secret))
+            .form(&form);
+        self.send(request).await?;
+        Ok(())
+    }
+
+    // Obtains a service token with the OAuth 2.0 client credentials grant
+    pub async fn client_credentials(
+        &self,
+        client_id: &str,
+        client_secret: &str,
+        scope: Option<&str>,
+    ) -> Result<TokenResponse, ClientError> {
+        let mut form = vec![("grant_type", "client_credentials")];
+        if let Some(scope) = scope {
+            form.push(("scope", scope));
+        }
+        let request = self
+            .post("/token")
+            .basic_auth(client_id, Some(client_secret))
+            .form(&form);
+        let response = self.send(request).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn create_api_key(
+        &self,
+        request: &CreateApiKeyRequest,
+    ) -> Result<CreateApiKeyResponse, ClientError> {
+        let response = self.send(self.post("/api-keys").json(request)).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKeyResponse>, ClientError> {
+        let response = self.send(self.http_client.get(self.url("/api-keys"))).await?;
+        Ok(response.json().await?)
+    }
+
+    pub async fn revoke_api_key(&self, id: &str) -> Result<(), ClientError> {
+        let request = self.http_client.delete(self.url(&format!("/api-keys/{}", id)));
+        self.send(self.with_csrf_token(request)).await?;
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url.as_str().trim_end_matches('/'), path)
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        self.with_csrf_token(self.http_client.post(self.url(path)))
+    }
+
+    // auth-service checks the CSRF token of every unsafe request authenticated
+    // by cookie. It hands the token out as a cookie on the first response.
+    fn with_csrf_token(&self, request: RequestBuilder) -> RequestBuilder {
+        match self.cookie(CSRF_COOKIE_NAME) {
+            Some(token) => request.header(CSRF_HEADER_NAME, token),
+            None => request,
+        }
+    }
+
+    fn cookie(&self, name: &str) -> Option<String> {
+        let cookies = self.cookies.cookies(&self.base_url)?;
+        cookies
+            .to_str()
+            .ok()?
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .find(|(cookie_name, _)| *cookie_name == name)
+            .map(|(_, value)| value.to_owned())
+            .filter(|value| !value.is_empty())
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<Response, ClientError> {
+        let response = request.send().await?;
+        if response.status().is_success() {
+            Ok(response)
+        } else {
+            Err(ClientError::from_response(response).await)
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{ErrorKind, ErrorResponse, PasswordRejection};
+
+#[derive(Debug)]
+pub enum ClientError {
+    // auth-service refused the request with one of its documented errors
+    Api {
+        status: u16,
+        kind: ErrorKind,
+        // Why the password was refused, for `ErrorKind::WeakPassword`
+        reasons: Vec<PasswordRejection>,
+    },
+    // An error body this client does not know, e.g. the RFC 6749 error codes of `/token`
+    Rejected { status: u16, error: String },
+    // A response that is not an error body at all, e.g. a 422 for a malformed request
+    UnexpectedResponse { status: u16, body: String },
+    InvalidBaseUrl(String),
+    Http(reqwest::Error),
+}
+
+impl ClientError {
+    pub(crate) async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return ClientError::Http(e),
+        };
+
+        match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(error) => match ErrorKind::from_message(&error.error) {
+                Some(kind) => ClientError::Api {
+                    status,
+                    kind,
+                    reasons: error.reasons,
+                },
+                None => ClientError::Rejected {
+                    status,
+                    error: error.error,
+                },
+            },
+            Err(_) => ClientError::UnexpectedResponse { status, body },
+        }
+    }
+
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            ClientError::Api { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    // Status code of the response, if auth-service answered at all
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Api { status, .. }
+            | ClientError::Rejected { status, .. }
+            | ClientError::UnexpectedResponse { status, .. } => Some(*status),
+            ClientError::InvalidBaseUrl(_) => None,
+            ClientError::Http(e) => e.status().map(|status| status.as_u16()),
+        }
+    }
+}
+
+impl std::fmt::Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Api { status, kind, .. } => write!(f, "{} ({})", kind.message(), status),
+            ClientError::Rejected { status, error } => write!(f, "{} ({})", error, status),
+            ClientError::UnexpectedResponse { status, body } => {
+                write!(f, "unexpected response ({}): {}", status, body)
+            }
+            ClientError::InvalidBaseUrl(url) => write!(f, "invalid base URL: {}", url),
+            ClientError::Http(e) => write!(f, "request failed: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(e: reqwest::Error) -> Self {
+        ClientError::Http(e)
+    }
+}
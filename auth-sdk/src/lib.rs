@@ -0,0 +1,24 @@
+// Types and client of the auth-service HTTP API.
+//
+// The request and response bodies are shared with auth-service itself, which
+// builds this crate without the `client` feature. With it, `AuthServiceClient`
+// offers a typed method per endpoint:
+//
+//     let client = AuthServiceClient::new("http://localhost:3000")?;
+//     match client.login(&LoginRequest { email, password }).await? {
+//         LoginOutcome::LoggedIn => {}
+//         LoginOutcome::TwoFactorRequired(attempt) => { /* ask for the emailed code */ }
+//     }
+//     client.logout().await?;
+
+mod api;
+#[cfg(feature = "client")]
+mod client;
+#[cfg(feature = "client")]
+mod error;
+
+pub use api::*;
+#[cfg(feature = "client")]
+pub use client::{AuthServiceClient, LoginOutcome};
+#[cfg(feature = "client")]
+pub use error::ClientError;
@@ -32,6 +32,30 @@ signupLoginLink.addEventListener("click", (e) => {
 
 // -----------------------------------------------------
 
+// The page signs in to the tenant named by `?tenant=`, the default one without it
+const tenant = new URLSearchParams(window.location.search).get("tenant");
+function tenantPath(path) {
+    return tenant ? `/t/${encodeURIComponent(tenant)}${path}` : path;
+}
+
+// State-changing requests must echo the `csrf_token` cookie in this header
+// Fills an error alert with `message` as text, never as markup: the message may
+// come from the URL or the server
+function showError(element, message) {
+    const span = document.createElement("span");
+    const label = document.createElement("strong");
+    label.textContent = "Error: ";
+    span.append(label, message);
+    element.replaceChildren(span);
+}
+
+function csrfHeaders() {
+    const match = document.cookie.match(/(?:^|;\s*)csrf_token=([^;]*)/);
+    return match ? { 'X-CSRF-Token': decodeURIComponent(match[1]) } : {};
+}
+
+// -----------------------------------------------------
+
 const loginForm = document.getElementById("login-form");
 const loginButton = document.getElementById("login-form-submit");
 const loginErrAlter = document.getElementById("login-err-alert");
@@ -42,10 +66,11 @@ loginButton.addEventListener("click", (e) => {
     const email = loginForm.email.value;
     const password = loginForm.password.value;
 
-    fetch('/login', {
+    fetch(tenantPath('/login'), {
         method: 'POST',
         headers: {
             'Content-Type': 'application/json',
+            ...csrfHeaders(),
         },
         body: JSON.stringify({ email, password }),
     }).then(response => {
@@ -81,6 +106,176 @@ loginButton.addEventListener("click", (e) => {
     });
 });
 
+const magicLinkButton = document.getElementById("magic-link-submit");
+const loginInfoAlert = document.getElementById("login-info-alert");
+
+magicLinkButton.addEventListener("click", (e) => {
+    e.preventDefault();
+
+    const email = loginForm.email.value;
+
+    fetch(tenantPath('/login/magic-link'), {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+            ...csrfHeaders(),
+        },
+        body: JSON.stringify({ email }),
+    }).then(response => {
+        response.json().then(data => {
+            if (response.status === 202) {
+                loginErrAlter.style.display = "none";
+                loginInfoAlert.textContent = data.message;
+                loginInfoAlert.style.display = "block";
+            } else {
+                loginInfoAlert.style.display = "none";
+                showError(loginErrAlter, data.error);
+                loginErrAlter.style.display = "block";
+            }
+        });
+    });
+});
+
+// Links emailed by /login/magic-link open this page with the token in the URL.
+// The token is posted rather than followed, so link scanners cannot use it up.
+function completeMagicLinkLogin() {
+    const params = new URLSearchParams(window.location.search);
+    const token = params.get("magic_token");
+    if (!token) {
+        return;
+    }
+
+    // Keep the token out of the history and of anything the page links to
+    params.delete("magic_token");
+    const query = params.toString();
+    history.replaceState(null, "", window.location.pathname + (query ? `?${query}` : ""));
+
+    fetch(tenantPath('/login/magic-link/verify'), {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+            ...csrfHeaders(),
+        },
+        body: JSON.stringify({ token }),
+    }).then(response => {
+        if (response.status === 206) {
+            // The 2FA form needs the email, which is the subject of the link token
+            const payload = token.split(".")[1].replace(/-/g, "+").replace(/_/g, "/");
+            TwoFAForm.email.value = JSON.parse(atob(payload)).sub;
+            response.json().then(data => {
+                TwoFAForm.login_attempt_id.value = data.loginAttemptId;
+            });
+
+            loginSection.style.display = "none";
+            twoFASection.style.display = "block";
+            signupSection.style.display = "none";
+        } else if (response.status === 200) {
+            alert("You have successfully logged in.");
+        } else {
+            response.json().then(data => {
+                showError(loginErrAlter, `The login link is invalid, expired or was already used (${data.error})`);
+                loginErrAlter.style.display = "block";
+            });
+        }
+    });
+}
+
+// Offer a "Sign in with ..." link for each identity provider the tenant can use
+const oidcProviders = document.getElementById("oidc-providers");
+
+function showIdentityProviders() {
+    fetch(tenantPath('/login/oidc')).then(response => {
+        if (response.status !== 200) {
+            return;
+        }
+        response.json().then(providers => {
+            for (const provider of providers) {
+                const link = document.createElement("a");
+                link.className = "btn btn-outline-dark d-block w-100";
+                link.href = tenantPath(`/login/oidc/${encodeURIComponent(provider.id)}`);
+                link.textContent = `Sign in with ${provider.name}`;
+
+                const row = document.createElement("div");
+                row.className = "mb-3";
+                row.appendChild(link);
+                oidcProviders.appendChild(row);
+            }
+        });
+    });
+}
+
+// Identity providers send the browser back to this page with `code` and `state`,
+// or with `error` if the login did not happen there.
+function completeOidcLogin() {
+    const params = new URLSearchParams(window.location.search);
+    const code = params.get("code");
+    const state = params.get("state");
+    const error = params.get("error");
+    if (!state || (!code && !error)) {
+        return;
+    }
+
+    // The code is good for one use; keep it out of the history
+    for (const name of ["code", "state", "error", "error_description", "iss", "session_state"]) {
+        params.delete(name);
+    }
+    const query = params.toString();
+    history.replaceState(null, "", window.location.pathname + (query ? `?${query}` : ""));
+
+    if (error) {
+        showError(loginErrAlter, `The identity provider did not log you in (${error})`);
+        loginErrAlter.style.display = "block";
+        return;
+    }
+
+    fetch(tenantPath('/login/oidc/callback'), {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+            ...csrfHeaders(),
+        },
+        body: JSON.stringify({ code, state }),
+    }).then(response => {
+        if (response.status === 206) {
+            // Only the server knows which email the provider gave, so ask for it
+            TwoFAForm.email.type = "email";
+            TwoFAForm.email.placeholder = "Email the code was sent to";
+            response.json().then(data => {
+                TwoFAForm.login_attempt_id.value = data.loginAttemptId;
+            });
+
+            loginSection.style.display = "none";
+            twoFASection.style.display = "block";
+            signupSection.style.display = "none";
+        } else if (response.status === 200) {
+            alert("You have successfully logged in.");
+        } else {
+            response.json().then(data => {
+                showError(loginErrAlter, data.error);
+                loginErrAlter.style.display = "block";
+            });
+        }
+    });
+}
+
+// Turn a rejection reason returned by /signup into a sentence the user can act on
+function describePasswordRejection(reason) {
+    switch (reason.code) {
+        case "too_short":
+            return `Use at least ${reason.min_length} characters.`;
+        case "too_long":
+            return `Use at most ${reason.max_length} characters.`;
+        case "too_weak":
+            return [reason.warning || "This password is too easy to guess.", ...reason.suggestions].join(" ");
+        case "contains_email":
+            return "Don't use your email address in your password.";
+        case "breached":
+            return "This password has appeared in a data breach. Choose a different one.";
+        default:
+            return reason.code;
+    }
+}
+
 const signupForm = document.getElementById("signup-form");
 const signupButton = document.getElementById("signup-form-submit");
 const signupErrAlter = document.getElementById("signup-err-alert");
@@ -92,10 +287,11 @@ signupButton.addEventListener("click", (e) => {
     const password = signupForm.password.value;
     const requires2FA = signupForm.twoFA.checked;
 
-    fetch('/signup', {
+    fetch(tenantPath('/signup'), {
         method: 'POST',
         headers: {
             'Content-Type': 'application/json',
+            ...csrfHeaders(),
         },
         body: JSON.stringify({ email, password, requires2FA }),
     }).then(response => {
@@ -113,6 +309,16 @@ signupButton.addEventListener("click", (e) => {
                 let error_msg = data.error;
                 if (error_msg !== undefined && error_msg !== null && error_msg !== "") {
                     signupErrAlter.innerHTML = `<span><strong>Error: </strong>${error_msg}</span>`;
+                    const reasons = (data.reasons || []).map(describePasswordRejection);
+                    if (reasons.length > 0) {
+                        const list = document.createElement("ul");
+                        reasons.forEach(reason => {
+                            const item = document.createElement("li");
+                            item.textContent = reason;
+                            list.appendChild(item);
+                        });
+                        signupErrAlter.appendChild(list);
+                    }
                     signupErrAlter.style.display = "block";
                 } else {
                     signupErrAlter.style.display = "none";
@@ -133,10 +339,11 @@ TwoFAButton.addEventListener("click", (e) => {
     const loginAttemptId = TwoFAForm.login_attempt_id.value;
     const TwoFACode = TwoFAForm.email_code.value;
 
-    fetch('/verify-2fa', {
+    fetch(tenantPath('/verify-2fa'), {
         method: 'POST',
         headers: {
             'Content-Type': 'application/json',
+            ...csrfHeaders(),
         },
         body: JSON.stringify({ email, loginAttemptId, "2FACode": TwoFACode }),
     }).then(response => {
@@ -161,4 +368,8 @@ TwoFAButton.addEventListener("click", (e) => {
             });
         }
     });
-});
\ No newline at end of file
+});
+
+completeMagicLinkLogin();
+showIdentityProviders();
+completeOidcLogin();
@@ -0,0 +1,14 @@
+use std::{env, error::Error, path::PathBuf};
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // Use the bundled protoc, so building needs nothing installed besides Rust
+    env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    tonic_prost_build::configure()
+        // Served by the reflection service, see `grpc::proto::FILE_DESCRIPTOR_SET`
+        .file_descriptor_set_path(out_dir.join("auth_descriptor.bin"))
+        .compile_protos(&["proto/auth.proto"], &["proto"])?;
+
+    Ok(())
+}
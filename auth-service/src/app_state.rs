@@ -1,18 +1,122 @@
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::{domain::data_stores::UserStore};
+use crate::domain::{
+    audit::AuditSink,
+    data_stores::{ApiKeyStore, BannedTokenStore, ClientStore, TwoFACodeStore, UserStore},
+    email_client::EmailClient,
+    tenant::TenantRegistry,
+};
+use crate::utils::{
+    cookies::CookieSettings, cors::CorsConfig, csrf::CsrfConfig, ldap::LdapConfig,
+    magic_link::MagicLinkConfig, metrics::Metrics, oidc::OidcConfig,
+    revocations::RevocationNotifier,
+};
 
 // Using a type alias to improve readability!
 pub type UserStoreType = Arc<RwLock<dyn UserStore + Send + Sync>>;
+pub type BannedTokenStoreType = Arc<RwLock<dyn BannedTokenStore + Send + Sync>>;
+pub type ApiKeyStoreType = Arc<RwLock<dyn ApiKeyStore + Send + Sync>>;
+pub type ClientStoreType = Arc<RwLock<dyn ClientStore + Send + Sync>>;
+pub type TwoFACodeStoreType = Arc<RwLock<dyn TwoFACodeStore + Send + Sync>>;
+pub type EmailClientType = Arc<dyn EmailClient + Send + Sync>;
+pub type AuditSinkType = Arc<dyn AuditSink + Send + Sync>;
 
 #[derive(Clone)]
 pub struct AppState {
     pub user_store: UserStoreType,
+    pub banned_token_store: BannedTokenStoreType,
+    pub two_fa_code_store: TwoFACodeStoreType,
+    pub api_key_store: ApiKeyStoreType,
+    pub client_store: ClientStoreType,
+    pub email_client: EmailClientType,
+    pub audit_sink: AuditSinkType,
+    pub metrics: Arc<Metrics>,
+    pub tenants: Arc<TenantRegistry>,
+    pub csrf_config: Arc<CsrfConfig>,
+    pub cors_config: Arc<CorsConfig>,
+    pub cookie_settings: Arc<CookieSettings>,
+    pub magic_link_config: Arc<MagicLinkConfig>,
+    pub oidc_config: Arc<OidcConfig>,
+    pub ldap_config: Arc<LdapConfig>,
+    pub revocation_notifier: Arc<RevocationNotifier>,
 }
 
 impl AppState {
-    pub fn new(user_store: UserStoreType) -> Self {
-        Self { user_store }
+    pub fn new(
+        user_store: UserStoreType,
+        banned_token_store: BannedTokenStoreType,
+        two_fa_code_store: TwoFACodeStoreType,
+        api_key_store: ApiKeyStoreType,
+        client_store: ClientStoreType,
+        email_client: EmailClientType,
+        audit_sink: AuditSinkType,
+    ) -> Self {
+        Self {
+            user_store,
+            banned_token_store,
+            two_fa_code_store,
+            api_key_store,
+            client_store,
+            email_client,
+            audit_sink,
+            metrics: Arc::new(Metrics::new()),
+            tenants: Arc::new(TenantRegistry::default()),
+            csrf_config: Arc::new(CsrfConfig::default()),
+            cors_config: Arc::new(CorsConfig::default()),
+            cookie_settings: Arc::new(CookieSettings::default()),
+            magic_link_config: Arc::new(MagicLinkConfig::default()),
+            oidc_config: Arc::new(OidcConfig::default()),
+            ldap_config: Arc::new(LdapConfig::default()),
+            revocation_notifier: Arc::new(RevocationNotifier::default()),
+        }
     }
-}
\ No newline at end of file
+
+    // Host more tenants, or give the default one another password policy, see `domain::tenant`
+    pub fn with_tenants(mut self, tenants: TenantRegistry) -> Self {
+        self.tenants = Arc::new(tenants);
+        self
+    }
+
+    // Trust additional origins for state-changing requests, see `utils::csrf`
+    pub fn with_csrf_config(mut self, csrf_config: CsrfConfig) -> Self {
+        self.csrf_config = Arc::new(csrf_config);
+        self
+    }
+
+    // Allow cross-origin frontends, see `utils::cors`
+    pub fn with_cors_config(mut self, cors_config: CorsConfig) -> Self {
+        self.cors_config = Arc::new(cors_config);
+        self
+    }
+
+    // Issue the auth cookie with other attributes, see `utils::cookies`
+    pub fn with_cookie_settings(mut self, cookie_settings: CookieSettings) -> Self {
+        self.cookie_settings = Arc::new(cookie_settings);
+        self
+    }
+
+    // Point emailed login links somewhere else, see `utils::magic_link`
+    pub fn with_magic_link_config(mut self, magic_link_config: MagicLinkConfig) -> Self {
+        self.magic_link_config = Arc::new(magic_link_config);
+        self
+    }
+
+    // Let users log in with upstream identity providers, see `utils::oidc`
+    pub fn with_oidc_config(mut self, oidc_config: OidcConfig) -> Self {
+        self.oidc_config = Arc::new(oidc_config);
+        self
+    }
+
+    // Check the passwords of some email domains against LDAP directories, see `utils::ldap`
+    pub fn with_ldap_config(mut self, ldap_config: LdapConfig) -> Self {
+        self.ldap_config = Arc::new(ldap_config);
+        self
+    }
+
+    // Push revoked tokens to the services that cache verifications, see `utils::revocations`
+    pub fn with_revocation_notifier(mut self, revocation_notifier: RevocationNotifier) -> Self {
+        self.revocation_notifier = Arc::new(revocation_notifier);
+        self
+    }
+}
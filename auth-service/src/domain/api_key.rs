@@ -0,0 +1,238 @@
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use rand::{distr::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+
+use crate::domain::{email::Email, role::Role, tenant::TenantId};
+
+// Every API key secret starts with this prefix, which makes keys easy to tell
+// apart from JWTs and easy to spot if they leak into logs or repositories.
+pub const API_KEY_PREFIX: &str = "lgr_";
+
+const SECRET_LENGTH: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ApiKeyId(uuid::Uuid);
+
+impl ApiKeyId {
+    pub fn parse(id: String) -> Result<Self, String> {
+        let parsed_id = uuid::Uuid::parse_str(&id).map_err(|_| "Invalid API key id".to_owned())?;
+        Ok(Self(parsed_id))
+    }
+}
+
+impl Default for ApiKeyId {
+    fn default() -> Self {
+        Self(uuid::Uuid::new_v4())
+    }
+}
+
+impl std::fmt::Display for ApiKeyId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// The secret handed to the user exactly once, formatted as `lgr_<key id>_<random>`.
+// Embedding the id lets us find the stored key without scanning every hash.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiKeySecret(String);
+
+impl ApiKeySecret {
+    pub fn generate(id: &ApiKeyId) -> Self {
+        let random: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(SECRET_LENGTH)
+            .map(char::from)
+            .collect();
+        Self(format!("{}{}_{}", API_KEY_PREFIX, id.0.simple(), random))
+    }
+
+    pub fn parse(secret: String) -> Result<(ApiKeyId, Self), String> {
+        let (id, random) = secret
+            .strip_prefix(API_KEY_PREFIX)
+            .and_then(|rest| rest.split_once('_'))
+            .ok_or("Invalid API key format".to_owned())?;
+
+        if random.len() != SECRET_LENGTH || !random.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err("Invalid API key format".to_owned());
+        }
+
+        let id = ApiKeyId::parse(id.to_owned())?;
+        Ok((id, Self(secret)))
+    }
+
+    pub fn looks_like_api_key(token: &str) -> bool {
+        token.starts_with(API_KEY_PREFIX)
+    }
+
+    pub fn hash(&self) -> ApiKeyHash {
+        ApiKeyHash(hex::encode(Sha256::digest(self.0.as_bytes())))
+    }
+}
+
+impl AsRef<str> for ApiKeySecret {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// API key secrets are long and random, so a fast hash is enough to keep the
+// stored value useless to anyone who reads the store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiKeyHash(String);
+
+impl AsRef<str> for ApiKeyHash {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    id: ApiKeyId,
+    tenant: TenantId,
+    owner: Email,
+    name: String,
+    scopes: BTreeSet<Role>,
+    hash: ApiKeyHash,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    pub fn new(
+        id: ApiKeyId,
+        owner: Email,
+        name: String,
+        scopes: BTreeSet<Role>,
+        secret: &ApiKeySecret,
+        created_at: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            id,
+            tenant: TenantId::default(),
+            owner,
+            name,
+            scopes,
+            hash: secret.hash(),
+            created_at,
+            expires_at,
+            last_used_at: None,
+        }
+    }
+
+    // The tenant of the owner; keys start out in the default tenant
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    pub fn id(&self) -> &ApiKeyId {
+        &self.id
+    }
+
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+
+    pub fn owner(&self) -> &Email {
+        &self.owner
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn scopes(&self) -> &BTreeSet<Role> {
+        &self.scopes
+    }
+
+    pub fn created_at(&self) -> DateTime<Utc> {
+        self.created_at
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    pub fn last_used_at(&self) -> Option<DateTime<Utc>> {
+        self.last_used_at
+    }
+
+    pub fn set_last_used_at(&mut self, at: DateTime<Utc>) {
+        self.last_used_at = Some(at);
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at
+    }
+
+    pub fn matches(&self, secret: &ApiKeySecret) -> bool {
+        self.hash == secret.hash()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_key(secret: &ApiKeySecret, id: ApiKeyId) -> ApiKey {
+        let now = Utc::now();
+        ApiKey::new(
+            id,
+            Email::parse("test@example.com".to_owned()).unwrap(),
+            "ci".to_owned(),
+            BTreeSet::from([Role::user()]),
+            secret,
+            now,
+            now + chrono::Duration::days(1),
+        )
+    }
+
+    #[test]
+    fn test_generated_secret_round_trips_through_parse() {
+        let id = ApiKeyId::default();
+        let secret = ApiKeySecret::generate(&id);
+
+        assert!(ApiKeySecret::looks_like_api_key(secret.as_ref()));
+
+        let (parsed_id, parsed_secret) = ApiKeySecret::parse(secret.as_ref().to_owned()).unwrap();
+        assert_eq!(parsed_id, id);
+        assert_eq!(parsed_secret, secret);
+    }
+
+    #[test]
+    fn test_invalid_secret() {
+        assert!(ApiKeySecret::parse("not-a-key".to_owned()).is_err());
+        assert!(ApiKeySecret::parse(format!("{}abc_def", API_KEY_PREFIX)).is_err());
+
+        let id = ApiKeyId::default();
+        let secret = ApiKeySecret::generate(&id);
+        let truncated = &secret.as_ref()[..50];
+        assert!(ApiKeySecret::parse(truncated.to_owned()).is_err());
+    }
+
+    #[test]
+    fn test_key_stores_hash_and_matches_only_its_secret() {
+        let id = ApiKeyId::default();
+        let secret = ApiKeySecret::generate(&id);
+        let key = test_key(&secret, id);
+
+        assert_ne!(key.hash.as_ref(), secret.as_ref());
+        assert!(key.matches(&secret));
+        assert!(!key.matches(&ApiKeySecret::generate(&id)));
+    }
+
+    #[test]
+    fn test_is_expired() {
+        let id = ApiKeyId::default();
+        let key = test_key(&ApiKeySecret::generate(&id), id);
+
+        assert!(!key.is_expired(Utc::now()));
+        assert!(key.is_expired(Utc::now() + chrono::Duration::days(2)));
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// What happened. Serialized in snake_case, e.g. `login` or `api_key_created`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventType {
+    Signup,
+    Login,
+    MagicLinkRequested,
+    // An identity provider account linked to an existing user at login
+    IdentityLinked,
+    // Roles following the user's groups in an LDAP directory at login
+    RolesChanged,
+    Verify2fa,
+    Logout,
+    VerifyToken,
+    // A token revoked by a service, at `/revoke` or over gRPC
+    TokenRevoked,
+    Introspect,
+    ServiceToken,
+    ApiKeyCreated,
+    ApiKeysListed,
+    ApiKeyRevoked,
+    AuditEventsQueried,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+// One security-relevant event. Events are only ever appended, never changed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AuditEvent {
+    pub timestamp: DateTime<Utc>,
+    pub event_type: AuditEventType,
+    pub outcome: AuditOutcome,
+    // Short explanation, e.g. the error returned to the caller
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    // The account concerned. For failed attempts this is whatever the caller claimed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    // The tenant whose routes were called, absent for the default tenant's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct AuditQuery {
+    pub email: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: usize,
+}
+
+impl AuditQuery {
+    // `from` is inclusive and `to` exclusive
+    pub fn matches(&self, event: &AuditEvent) -> bool {
+        if let Some(email) = &self.email {
+            if event.email.as_deref() != Some(email.as_str()) {
+                return false;
+            }
+        }
+        if let Some(from) = self.from {
+            if event.timestamp < from {
+                return false;
+            }
+        }
+        if let Some(to) = self.to {
+            if event.timestamp >= to {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AuditSinkError {
+    UnexpectedError(String),
+}
+
+// This trait represents the interface all concrete audit sinks should implement.
+// It is append-only on purpose: there is no way to change or delete an event.
+#[async_trait::async_trait]
+pub trait AuditSink {
+    async fn record(&self, event: AuditEvent) -> Result<(), AuditSinkError>;
+    // Matching events, newest first, at most `query.limit` of them
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEvent>, AuditSinkError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event_at(timestamp: DateTime<Utc>, email: &str) -> AuditEvent {
+        AuditEvent {
+            timestamp,
+            event_type: AuditEventType::Login,
+            outcome: AuditOutcome::Success,
+            detail: None,
+            email: Some(email.to_owned()),
+            ip: None,
+            user_agent: None,
+            request_id: None,
+            tenant: None,
+        }
+    }
+
+    #[test]
+    fn test_event_serializes_to_snake_case() {
+        let mut event = event_at(Utc::now(), "test@example.com");
+        event.event_type = AuditEventType::ApiKeyCreated;
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["event_type"], "api_key_created");
+        assert_eq!(json["outcome"], "success");
+        assert!(json.get("ip").is_none());
+    }
+
+    #[test]
+    fn test_query_matches() {
+        let now = Utc::now();
+        let event = event_at(now, "test@example.com");
+
+        assert!(AuditQuery::default().matches(&event));
+
+        let query = AuditQuery {
+            email: Some("other@example.com".to_owned()),
+            ..Default::default()
+        };
+        assert!(!query.matches(&event));
+
+        let query = AuditQuery {
+            email: Some("test@example.com".to_owned()),
+            from: Some(now),
+            to: Some(now + chrono::Duration::seconds(1)),
+            ..Default::default()
+        };
+        assert!(query.matches(&event));
+
+        let query = AuditQuery {
+            to: Some(now),
+            ..Default::default()
+        };
+        assert!(!query.matches(&event));
+    }
+}
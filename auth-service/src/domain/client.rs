@@ -0,0 +1,212 @@
+use std::collections::BTreeSet;
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use auth_sdk::AUTH_SERVICE_CLIENT_ID;
+
+use crate::domain::role::Role;
+
+// Identifies a confidential client (another backend service) registered with us
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClientId(String);
+
+impl ClientId {
+    pub fn parse(id: String) -> Result<Self, String> {
+        if id.is_empty() || id.len() > 64 {
+            return Err("Client id must be between 1 and 64 characters long".to_owned());
+        }
+        if !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            return Err("Client id contains invalid characters".to_owned());
+        }
+        Ok(Self(id))
+    }
+}
+
+impl AsRef<str> for ClientId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// A client secret as presented by the client when it authenticates
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientSecret(String);
+
+impl ClientSecret {
+    pub fn parse(secret: String) -> Result<Self, String> {
+        if secret.is_empty() {
+            return Err("Client secret must not be empty".to_owned());
+        }
+        Ok(Self(secret))
+    }
+
+    pub fn hash(&self) -> ClientSecretHash {
+        ClientSecretHash(hex::encode(Sha256::digest(self.0.as_bytes())))
+    }
+}
+
+impl AsRef<str> for ClientSecret {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+// Hex-encoded SHA-256 of a client secret. Only this is ever configured or stored,
+// so client secrets must be long random strings, not passwords.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientSecretHash(String);
+
+impl ClientSecretHash {
+    pub fn parse(hash: String) -> Result<Self, String> {
+        let hash = hash.to_ascii_lowercase();
+        if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(Self(hash))
+        } else {
+            Err("Client secret hash must be a hex-encoded SHA-256 digest".to_owned())
+        }
+    }
+}
+
+impl AsRef<str> for ClientSecretHash {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    id: ClientId,
+    secret_hash: ClientSecretHash,
+    scopes: BTreeSet<Role>,
+}
+
+impl Client {
+    pub fn new(id: ClientId, secret_hash: ClientSecretHash, scopes: BTreeSet<Role>) -> Self {
+        Self {
+            id,
+            secret_hash,
+            scopes,
+        }
+    }
+
+    pub fn id(&self) -> &ClientId {
+        &self.id
+    }
+
+    // Scopes this client is allowed to request for its service tokens
+    pub fn scopes(&self) -> &BTreeSet<Role> {
+        &self.scopes
+    }
+
+    pub fn matches(&self, secret: &ClientSecret) -> bool {
+        self.secret_hash == secret.hash()
+    }
+}
+
+// Shape of one entry of the `SERVICE_CLIENTS` environment variable, e.g.
+// [{"clientId": "app-service", "secretSha256": "<hex digest>", "scopes": []}]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClientConfig {
+    client_id: String,
+    secret_sha256: String,
+    #[serde(default)]
+    scopes: Vec<String>,
+}
+
+pub fn parse_client_registry(json: &str) -> Result<Vec<Client>, String> {
+    let configs: Vec<ClientConfig> =
+        serde_json::from_str(json).map_err(|e| format!("Invalid client registry: {}", e))?;
+
+    configs
+        .into_iter()
+        .map(|config| {
+            let scopes = config
+                .scopes
+                .into_iter()
+                .map(Role::parse)
+                .collect::<Result<BTreeSet<Role>, String>>()?;
+            // Service tokens with this id are the ones auth-service signs for itself
+            if config.client_id == AUTH_SERVICE_CLIENT_ID {
+                return Err(format!("Client id {} is reserved", AUTH_SERVICE_CLIENT_ID));
+            }
+            Ok(Client::new(
+                ClientId::parse(config.client_id)?,
+                ClientSecretHash::parse(config.secret_sha256)?,
+                scopes,
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_client_id() {
+        assert!(ClientId::parse("app-service".to_owned()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_client_id() {
+        assert!(ClientId::parse("".to_owned()).is_err());
+        assert!(ClientId::parse("app service".to_owned()).is_err());
+        assert!(ClientId::parse("a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_secret_hash_parse() {
+        let hash = ClientSecret::parse("secret".to_owned()).unwrap().hash();
+        assert_eq!(
+            ClientSecretHash::parse(hash.as_ref().to_uppercase()).unwrap(),
+            hash
+        );
+        assert!(ClientSecretHash::parse("abc".to_owned()).is_err());
+        assert!(ClientSecretHash::parse("z".repeat(64)).is_err());
+    }
+
+    #[test]
+    fn test_client_matches_only_its_secret() {
+        let secret = ClientSecret::parse("a-long-random-secret".to_owned()).unwrap();
+        let client = Client::new(
+            ClientId::parse("app-service".to_owned()).unwrap(),
+            secret.hash(),
+            BTreeSet::new(),
+        );
+
+        assert!(client.matches(&secret));
+        assert!(!client.matches(&ClientSecret::parse("wrong".to_owned()).unwrap()));
+    }
+
+    #[test]
+    fn test_parse_client_registry() {
+        let hash = ClientSecret::parse("secret".to_owned()).unwrap().hash();
+        let json = format!(
+            r#"[{{"clientId": "app-service", "secretSha256": "{}", "scopes": ["verify"]}}]"#,
+            hash.as_ref()
+        );
+
+        let clients = parse_client_registry(&json).unwrap();
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].id().as_ref(), "app-service");
+        assert!(clients[0].scopes().contains(&Role::parse("verify".to_owned()).unwrap()));
+
+        assert!(parse_client_registry("[]").unwrap().is_empty());
+        assert!(parse_client_registry("not json").is_err());
+        assert!(parse_client_registry(
+            r#"[{"clientId": "app-service", "secretSha256": "plain-secret"}]"#
+        )
+        .is_err());
+
+        let reserved = format!(
+            r#"[{{"clientId": "auth-service", "secretSha256": "{}"}}]"#,
+            hash.as_ref()
+        );
+        assert!(parse_client_registry(&reserved).is_err());
+    }
+}
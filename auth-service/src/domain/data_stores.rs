@@ -1,10 +1,52 @@
-use crate::domain::{email::Email, password::Password, user::User};
+use std::collections::BTreeSet;
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+
+use crate::domain::{
+    api_key::{ApiKey, ApiKeyId},
+    client::{Client, ClientId},
+    email::Email,
+    identity_provider::ExternalIdentity,
+    password::Password,
+    role::Role,
+    tenant::TenantId,
+    user::User,
+};
+
+// Accounts are identified by tenant and email, so the same address can sign up
+// to every tenant. Banned tokens and service clients are deployment-wide: tokens
+// are unique whatever their tenant, and services are not tenants' users.
 
 #[async_trait::async_trait]
 pub trait UserStore {
     async fn add_user(&mut self, user: User) -> Result<(), UserStoreError>;
-    async fn get_user(&self, email: &Email) -> Result<&User, UserStoreError>;
-    async fn validate_user(&self, email: &Email, password: &Password) -> Result<(), UserStoreError>;
+    async fn get_user(&self, tenant: &TenantId, email: &Email) -> Result<&User, UserStoreError>;
+    async fn validate_user(
+        &self,
+        tenant: &TenantId,
+        email: &Email,
+        password: &Password,
+    ) -> Result<(), UserStoreError>;
+    async fn set_roles(
+        &mut self,
+        tenant: &TenantId,
+        email: &Email,
+        roles: BTreeSet<Role>,
+    ) -> Result<(), UserStoreError>;
+    // The user an identity provider account is linked to
+    async fn get_user_by_identity(
+        &self,
+        tenant: &TenantId,
+        identity: &ExternalIdentity,
+    ) -> Result<&User, UserStoreError>;
+    async fn link_identity(
+        &mut self,
+        tenant: &TenantId,
+        email: &Email,
+        identity: ExternalIdentity,
+    ) -> Result<(), UserStoreError>;
+    async fn count_users(&self) -> Result<usize, UserStoreError>;
 }
 
 #[derive(Debug, PartialEq)]
@@ -12,5 +54,161 @@ pub enum UserStoreError {
     UserAlreadyExists,
     UserNotFound,
     InvalidCredentials,
+    // The identity provider account is linked to another user already
+    IdentityAlreadyLinked,
+    UnexpectedError,
+}
+
+#[async_trait::async_trait]
+pub trait BannedTokenStore {
+    async fn add_token(&mut self, token: String) -> Result<(), BannedTokenStoreError>;
+    async fn contains_token(&self, token: &str) -> Result<bool, BannedTokenStoreError>;
+    async fn count_tokens(&self) -> Result<usize, BannedTokenStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BannedTokenStoreError {
+    UnexpectedError,
+}
+
+#[async_trait::async_trait]
+pub trait ApiKeyStore {
+    async fn add_key(&mut self, key: ApiKey) -> Result<(), ApiKeyStoreError>;
+    async fn get_key(&self, id: &ApiKeyId) -> Result<ApiKey, ApiKeyStoreError>;
+    async fn list_keys(&self, tenant: &TenantId, owner: &Email) -> Result<Vec<ApiKey>, ApiKeyStoreError>;
+    async fn revoke_key(
+        &mut self,
+        tenant: &TenantId,
+        owner: &Email,
+        id: &ApiKeyId,
+    ) -> Result<(), ApiKeyStoreError>;
+    async fn record_use(&mut self, id: &ApiKeyId, at: DateTime<Utc>) -> Result<(), ApiKeyStoreError>;
+    async fn count_keys(&self) -> Result<usize, ApiKeyStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ApiKeyStoreError {
+    KeyAlreadyExists,
+    KeyNotFound,
+    UnexpectedError,
+}
+
+// Registry of confidential clients allowed to use the client credentials grant
+#[async_trait::async_trait]
+pub trait ClientStore {
+    async fn add_client(&mut self, client: Client) -> Result<(), ClientStoreError>;
+    async fn get_client(&self, id: &ClientId) -> Result<Client, ClientStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClientStoreError {
+    ClientAlreadyExists,
+    ClientNotFound,
+    UnexpectedError,
+}
+
+// This trait represents the interface all concrete 2FA code stores should implement
+#[async_trait::async_trait]
+pub trait TwoFACodeStore {
+    async fn add_code(
+        &mut self,
+        tenant: TenantId,
+        email: Email,
+        login_attempt_id: LoginAttemptId,
+        code: TwoFACode,
+    ) -> Result<(), TwoFACodeStoreError>;
+    async fn remove_code(&mut self, tenant: &TenantId, email: &Email) -> Result<(), TwoFACodeStoreError>;
+    async fn get_code(
+        &self,
+        tenant: &TenantId,
+        email: &Email,
+    ) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError>;
+    async fn count_codes(&self) -> Result<usize, TwoFACodeStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TwoFACodeStoreError {
+    LoginAttemptIdNotFound,
     UnexpectedError,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginAttemptId(String);
+
+impl LoginAttemptId {
+    pub fn parse(id: String) -> Result<Self, String> {
+        // Use the `parse_str` function from the `uuid` crate to ensure `id` is a valid UUID
+        let parsed_id = uuid::Uuid::parse_str(&id).map_err(|_| "Invalid login attempt id".to_owned())?;
+        Ok(Self(parsed_id.to_string()))
+    }
+}
+
+impl Default for LoginAttemptId {
+    fn default() -> Self {
+        Self(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for LoginAttemptId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TwoFACode(String);
+
+impl TwoFACode {
+    pub fn parse(code: String) -> Result<Self, String> {
+        // Ensure `code` is a valid 6-digit code
+        if code.len() == 6 && code.chars().all(|c| c.is_ascii_digit()) {
+            Ok(Self(code))
+        } else {
+            Err("Invalid 2FA code".to_owned())
+        }
+    }
+}
+
+impl Default for TwoFACode {
+    fn default() -> Self {
+        // Use the `rand` crate to generate a random 2FA code.
+        // The code should be 6 digits (ex: 834629)
+        let code = rand::rng().random_range(0..1_000_000);
+        Self(format!("{:06}", code))
+    }
+}
+
+impl AsRef<str> for TwoFACode {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_login_attempt_id() {
+        let id = LoginAttemptId::default();
+        assert!(LoginAttemptId::parse(id.as_ref().to_owned()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_login_attempt_id() {
+        assert!(LoginAttemptId::parse("not-a-uuid".to_owned()).is_err());
+    }
+
+    #[test]
+    fn test_default_two_fa_code_is_valid() {
+        let code = TwoFACode::default();
+        assert!(TwoFACode::parse(code.as_ref().to_owned()).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_two_fa_code() {
+        assert!(TwoFACode::parse("12345".to_owned()).is_err());
+        assert!(TwoFACode::parse("12345a".to_owned()).is_err());
+        assert!(TwoFACode::parse("1234567".to_owned()).is_err());
+    }
+}
@@ -1,26 +1,220 @@
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct Email(String);
+use std::{
+    hash::{Hash, Hasher},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use crate::utils::constants::EMAIL_LOCAL_PART_CASE_SENSITIVE;
+
+// RFC 5321 limits: 64 octets for the local part, 255 for the domain on the wire
+// (253 as text), and 254 for the whole address once the angle brackets are removed.
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+const MAX_DOMAIN_LENGTH: usize = 253;
+const MAX_DOMAIN_LABEL_LENGTH: usize = 63;
+const MAX_EMAIL_LENGTH: usize = 254;
+
+// An RFC 5322 `addr-spec` (`local-part@domain`), without display names or comments.
+//
+// Two forms are kept. The normalized form identifies the account: it is what
+// `as_ref` returns, what equality and hashing use, and what ends up in tokens.
+// Its domain is lowercased and IDNs are converted to punycode; the local part is
+// lowercased too, unless `EMAIL_LOCAL_PART_CASE_SENSITIVE` is set. The display form
+// is the address as the user typed it, which is also where emails are sent.
+#[derive(Debug, Clone)]
+pub struct Email {
+    normalized: String,
+    display: String,
+}
 
 impl Email {
     pub fn parse(email: String) -> Result<Self, String> {
-        if email.contains('@') {
-            Ok(Email(email))
-        } else {
-            Err("Invalid email format".to_string())
+        Self::parse_with(email, *EMAIL_LOCAL_PART_CASE_SENSITIVE)
+    }
+
+    pub fn parse_with(email: String, case_sensitive_local_part: bool) -> Result<Self, String> {
+        let display = email.trim();
+
+        // The local part may itself contain '@' when quoted, the domain never does
+        let (local_part, domain) = display
+            .rsplit_once('@')
+            .ok_or("Invalid email format: missing '@'".to_owned())?;
+
+        let mut local_part = parse_local_part(local_part)?;
+        if !case_sensitive_local_part {
+            local_part = local_part.to_lowercase();
+        }
+        if local_part.len() > MAX_LOCAL_PART_LENGTH {
+            return Err("Invalid email format: local part is too long".to_owned());
+        }
+
+        let domain = parse_domain(domain)?;
+
+        let normalized = format!("{}@{}", local_part, domain);
+        if normalized.len() > MAX_EMAIL_LENGTH {
+            return Err("Invalid email format: address is too long".to_owned());
         }
+
+        Ok(Email {
+            normalized,
+            display: display.to_owned(),
+        })
+    }
+
+    // The address as the user entered it
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+
+    // The normalized domain, e.g. `example.com`
+    pub fn domain(&self) -> &str {
+        self.normalized
+            .rsplit_once('@')
+            .map_or("", |(_, domain)| domain)
+    }
+}
+
+impl PartialEq for Email {
+    fn eq(&self, other: &Self) -> bool {
+        self.normalized == other.normalized
+    }
+}
+
+impl Eq for Email {}
+
+impl Hash for Email {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized.hash(state);
     }
 }
 
 impl AsRef<str> for Email {
     fn as_ref(&self) -> &str {
-        &self.0
+        &self.normalized
+    }
+}
+
+// `atext` from RFC 5322, extended with non-ASCII characters as RFC 6532 allows
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c) || !c.is_ascii()
+}
+
+fn is_dot_atom(value: &str) -> bool {
+    !value.is_empty() && value.split('.').all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+// Accepts a dot-atom (`john.doe`) or a quoted string (`"john doe"`). Quoting
+// that is not needed is removed, so `"john"` and `john` are the same mailbox.
+fn parse_local_part(local_part: &str) -> Result<String, String> {
+    if local_part.is_empty() {
+        return Err("Invalid email format: missing local part".to_owned());
+    }
+
+    let Some(quoted) = local_part
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    else {
+        return if is_dot_atom(local_part) {
+            Ok(local_part.to_owned())
+        } else {
+            Err("Invalid email format: invalid character in local part".to_owned())
+        };
+    };
+
+    let mut content = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped) if escaped == ' ' || escaped == '\t' || escaped.is_ascii_graphic() => {
+                    content.push(escaped)
+                }
+                _ => return Err("Invalid email format: invalid escape in local part".to_owned()),
+            },
+            '"' => return Err("Invalid email format: unescaped quote in local part".to_owned()),
+            c if c == ' ' || c == '\t' || c.is_ascii_graphic() || !c.is_ascii() => content.push(c),
+            _ => return Err("Invalid email format: invalid character in local part".to_owned()),
+        }
+    }
+
+    if is_dot_atom(&content) {
+        return Ok(content);
+    }
+
+    let escaped: String = content
+        .chars()
+        .flat_map(|c| match c {
+            '"' | '\\' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect();
+    Ok(format!("\"{}\"", escaped))
+}
+
+// Accepts a host name, internationalized or not, or an address literal such as
+// `[192.0.2.1]` or `[IPv6:2001:db8::1]`. Host names are returned in lowercase ASCII.
+fn parse_domain(domain: &str) -> Result<String, String> {
+    if let Some(literal) = domain.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+        return parse_address_literal(literal);
+    }
+
+    let ascii = idna::domain_to_ascii_strict(domain)
+        .map_err(|_| "Invalid email format: invalid domain".to_owned())?;
+
+    if ascii.is_empty() || ascii.len() > MAX_DOMAIN_LENGTH {
+        return Err("Invalid email format: invalid domain length".to_owned());
+    }
+
+    let labels: Vec<&str> = ascii.split('.').collect();
+    // Dotless domains are valid in theory but never reachable over the internet
+    if labels.len() < 2 {
+        return Err("Invalid email format: domain must have at least two labels".to_owned());
+    }
+
+    for label in &labels {
+        let valid = !label.is_empty()
+            && label.len() <= MAX_DOMAIN_LABEL_LENGTH
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        if !valid {
+            return Err("Invalid email format: invalid domain".to_owned());
+        }
+    }
+
+    // A numeric top-level label means this is an IP address written without brackets
+    if labels.last().unwrap().chars().all(|c| c.is_ascii_digit()) {
+        return Err("Invalid email format: invalid domain".to_owned());
+    }
+
+    Ok(ascii.to_lowercase())
+}
+
+fn parse_address_literal(literal: &str) -> Result<String, String> {
+    let invalid = || "Invalid email format: invalid address literal".to_owned();
+
+    match literal.get(..5) {
+        Some(tag) if tag.eq_ignore_ascii_case("IPv6:") => {
+            let ip: Ipv6Addr = literal[5..].parse().map_err(|_| invalid())?;
+            Ok(format!("[IPv6:{}]", ip))
+        }
+        _ => {
+            let ip: Ipv4Addr = literal.parse().map_err(|_| invalid())?;
+            Ok(format!("[{}]", ip))
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use fake::{faker::internet::en::SafeEmail, Fake};
+    use quickcheck::Gen;
+    use quickcheck_macros::quickcheck;
+
     use super::*;
 
+    fn parse(email: &str) -> Result<Email, String> {
+        Email::parse_with(email.to_owned(), false)
+    }
+
     #[test]
     fn test_valid_email() {
         let email = Email::parse("test@example.com".to_string());
@@ -32,4 +226,122 @@ mod tests {
         let email = Email::parse("invalid_email".to_string());
         assert!(email.is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_rejects_malformed_addresses() {
+        let cases = [
+            "",
+            "@",
+            "a@",
+            "@example.com",
+            "a@@example.com",
+            "a b@example.com",
+            "a@exa mple.com",
+            ".a@example.com",
+            "a.@example.com",
+            "a..b@example.com",
+            "a@example",
+            "a@-example.com",
+            "a@example-.com",
+            "a@example..com",
+            "a@192.168.0.1",
+            "a@[300.1.1.1]",
+            "\"a\"b\"@example.com",
+            "Bob <bob@example.com>",
+            "a(comment)@example.com",
+        ];
+
+        for case in cases {
+            assert!(parse(case).is_err(), "{:?} should be rejected", case);
+        }
+    }
+
+    #[test]
+    fn test_accepts_rfc_5322_addresses() {
+        let cases = [
+            "simple@example.com",
+            "very.common@example.com",
+            "disposable.style.email.with+symbol@example.com",
+            "x@example.com",
+            "user-@example.org",
+            "!#$%&'*+-/=?^_`{|}~@example.org",
+            "\"john doe\"@example.org",
+            "\"a@b\"@example.org",
+            "a@[192.0.2.1]",
+            "a@[IPv6:2001:db8::1]",
+            "用户@例子.广告",
+        ];
+
+        for case in cases {
+            assert!(parse(case).is_ok(), "{:?} should be accepted", case);
+        }
+    }
+
+    #[test]
+    fn test_enforces_length_limits() {
+        let local_part = "a".repeat(MAX_LOCAL_PART_LENGTH);
+        assert!(parse(&format!("{}@example.com", local_part)).is_ok());
+        assert!(parse(&format!("a{}@example.com", local_part)).is_err());
+
+        let label = "a".repeat(MAX_DOMAIN_LABEL_LENGTH + 1);
+        assert!(parse(&format!("a@{}.com", label)).is_err());
+
+        let domain = format!("{}.com", vec!["a".repeat(60); 5].join("."));
+        assert!(parse(&format!("a@{}", domain)).is_err());
+    }
+
+    #[test]
+    fn test_normalizes_case_and_keeps_display_form() {
+        let email = parse("  Bob.Smith@Example.COM ").unwrap();
+
+        assert_eq!(email.as_ref(), "bob.smith@example.com");
+        assert_eq!(email.display(), "Bob.Smith@Example.COM");
+        assert_eq!(email.domain(), "example.com");
+        assert_eq!(email, parse("bob.smith@example.com").unwrap());
+    }
+
+    #[test]
+    fn test_case_sensitive_local_part_is_configurable() {
+        let email = Email::parse_with("Bob@Example.com".to_owned(), true).unwrap();
+
+        assert_eq!(email.as_ref(), "Bob@example.com");
+        assert_ne!(email, Email::parse_with("bob@example.com".to_owned(), true).unwrap());
+    }
+
+    #[test]
+    fn test_converts_idn_domains_to_punycode() {
+        let email = parse("user@Bücher.example").unwrap();
+
+        assert_eq!(email.as_ref(), "user@xn--bcher-kva.example");
+        assert_eq!(email, parse("user@xn--bcher-kva.example").unwrap());
+    }
+
+    #[test]
+    fn test_removes_unnecessary_quotes() {
+        assert_eq!(parse("\"john\"@example.com").unwrap().as_ref(), "john@example.com");
+        assert_eq!(
+            parse("\"john doe\"@example.com").unwrap().as_ref(),
+            "\"john doe\"@example.com"
+        );
+    }
+
+    #[derive(Debug, Clone)]
+    struct ValidEmailFixture(pub String);
+
+    impl quickcheck::Arbitrary for ValidEmailFixture {
+        fn arbitrary(_g: &mut Gen) -> Self {
+            Self(SafeEmail().fake())
+        }
+    }
+
+    #[quickcheck]
+    fn valid_emails_are_parsed_successfully(valid_email: ValidEmailFixture) -> bool {
+        parse(&valid_email.0).is_ok()
+    }
+
+    #[quickcheck]
+    fn normalization_is_idempotent(valid_email: ValidEmailFixture) -> bool {
+        let email = parse(&valid_email.0).unwrap();
+        parse(email.as_ref()).unwrap().as_ref() == email.as_ref()
+    }
+}
@@ -1,5 +1,81 @@
+use auth_sdk::ErrorKind;
+
+use super::password_policy::PasswordRejection;
+
 pub enum AuthAPIError {
     UserAlreadyExists,
     InvalidCredentials,
+    WeakPassword(Vec<PasswordRejection>),
+    IncorrectCredentials,
+    MissingToken,
+    InvalidToken,
+    InvalidApiKeyRequest,
+    ApiKeyNotFound,
+    ServiceAuthRequired,
+    Forbidden,
+    CsrfCheckFailed,
+    TenantNotFound,
+    IdentityProviderNotFound,
+    IdentityProviderFailed,
+    UnverifiedEmail,
+    DirectoryUnavailable,
+    ManagedByDirectory,
     UnexpectedError,
-}
\ No newline at end of file
+}
+
+impl AuthAPIError {
+    // Human readable message returned to the caller and written to the audit log
+    pub fn message(&self) -> &'static str {
+        self.kind().message()
+    }
+
+    // The kind clients see, shared with them so they can match on it
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            AuthAPIError::UserAlreadyExists => ErrorKind::UserAlreadyExists,
+            AuthAPIError::InvalidCredentials => ErrorKind::InvalidCredentials,
+            AuthAPIError::WeakPassword(_) => ErrorKind::WeakPassword,
+            AuthAPIError::IncorrectCredentials => ErrorKind::IncorrectCredentials,
+            AuthAPIError::MissingToken => ErrorKind::MissingToken,
+            AuthAPIError::InvalidToken => ErrorKind::InvalidToken,
+            AuthAPIError::InvalidApiKeyRequest => ErrorKind::InvalidApiKeyRequest,
+            AuthAPIError::ApiKeyNotFound => ErrorKind::ApiKeyNotFound,
+            AuthAPIError::ServiceAuthRequired => ErrorKind::ServiceAuthRequired,
+            AuthAPIError::Forbidden => ErrorKind::Forbidden,
+            AuthAPIError::CsrfCheckFailed => ErrorKind::CsrfCheckFailed,
+            AuthAPIError::TenantNotFound => ErrorKind::TenantNotFound,
+            AuthAPIError::IdentityProviderNotFound => ErrorKind::IdentityProviderNotFound,
+            AuthAPIError::IdentityProviderFailed => ErrorKind::IdentityProviderFailed,
+            AuthAPIError::UnverifiedEmail => ErrorKind::UnverifiedEmail,
+            AuthAPIError::DirectoryUnavailable => ErrorKind::DirectoryUnavailable,
+            AuthAPIError::ManagedByDirectory => ErrorKind::ManagedByDirectory,
+            AuthAPIError::UnexpectedError => ErrorKind::UnexpectedError,
+        }
+    }
+}
+
+// Errors of the OAuth 2.0 endpoints, reported with the error codes
+// defined in RFC 6749 section 5.2 rather than our usual messages.
+#[derive(Debug, PartialEq)]
+pub enum OAuthError {
+    InvalidRequest,
+    InvalidClient,
+    UnsupportedGrantType,
+    InvalidScope,
+    // RFC 7009: the token is valid but cannot be revoked, e.g. a service token
+    UnsupportedTokenType,
+    ServerError,
+}
+
+impl OAuthError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            OAuthError::InvalidRequest => "invalid_request",
+            OAuthError::InvalidClient => "invalid_client",
+            OAuthError::UnsupportedGrantType => "unsupported_grant_type",
+            OAuthError::InvalidScope => "invalid_scope",
+            OAuthError::UnsupportedTokenType => "unsupported_token_type",
+            OAuthError::ServerError => "server_error",
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use serde::Deserialize;
+
+// Scopes requested when a provider's configuration lists none
+const DEFAULT_SCOPES: [&str; 3] = ["openid", "email", "profile"];
+
+// Taken by the route that completes a login, `/login/oidc/callback`
+const RESERVED_PROVIDER_IDS: [&str; 1] = ["callback"];
+
+// Identifies an upstream identity provider. It appears in URLs, e.g.
+// `/login/oidc/corp`, and in the identities linked to users.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ProviderId(String);
+
+impl ProviderId {
+    pub fn parse(id: String) -> Result<Self, String> {
+        if id.is_empty() || id.len() > 63 {
+            return Err("Identity provider id must be between 1 and 63 characters long".to_owned());
+        }
+        if !id
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            || id.starts_with('-')
+        {
+            return Err("Identity provider id must be lowercase letters, digits and dashes".to_owned());
+        }
+        if RESERVED_PROVIDER_IDS.contains(&id.as_str()) {
+            return Err(format!("Identity provider id {} is reserved", id));
+        }
+        Ok(Self(id))
+    }
+}
+
+impl AsRef<str> for ProviderId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for ProviderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+// An account at an identity provider, as named by the `sub` of its ID tokens.
+// Subjects are only unique per provider.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ExternalIdentity {
+    provider: ProviderId,
+    subject: String,
+}
+
+impl ExternalIdentity {
+    pub fn new(provider: ProviderId, subject: String) -> Self {
+        Self { provider, subject }
+    }
+
+    pub fn provider(&self) -> &ProviderId {
+        &self.provider
+    }
+
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+}
+
+// An OpenID Connect provider we are registered with as a confidential client
+#[derive(Debug, Clone)]
+pub struct IdentityProvider {
+    id: ProviderId,
+    name: String,
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    client_id: String,
+    client_secret: String,
+    scopes: Vec<String>,
+}
+
+impl IdentityProvider {
+    pub fn id(&self) -> &ProviderId {
+        &self.id
+    }
+
+    // Shown on the login page, e.g. "Sign in with Corp SSO"
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Must equal the `iss` of the provider's ID tokens
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    pub fn authorization_endpoint(&self) -> &str {
+        &self.authorization_endpoint
+    }
+
+    pub fn token_endpoint(&self) -> &str {
+        &self.token_endpoint
+    }
+
+    pub fn jwks_uri(&self) -> &str {
+        &self.jwks_uri
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.client_id
+    }
+
+    pub fn client_secret(&self) -> &str {
+        &self.client_secret
+    }
+
+    pub fn scopes(&self) -> &[String] {
+        &self.scopes
+    }
+}
+
+// Shape of one entry of the `OIDC_PROVIDERS` environment variable, e.g.
+// [{"id": "corp", "name": "Corp SSO", "issuer": "https://idp.example.com",
+//   "authorizationEndpoint": "https://idp.example.com/authorize",
+//   "tokenEndpoint": "https://idp.example.com/token", "jwksUri": "https://idp.example.com/jwks",
+//   "clientId": "auth-service", "clientSecret": "<secret>"}]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IdentityProviderConfig {
+    id: String,
+    name: String,
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    client_id: String,
+    client_secret: String,
+    #[serde(default)]
+    scopes: Vec<String>,
+}
+
+pub fn parse_identity_providers(json: &str) -> Result<Vec<IdentityProvider>, String> {
+    let configs: Vec<IdentityProviderConfig> = serde_json::from_str(json)
+        .map_err(|e| format!("Invalid identity provider registry: {}", e))?;
+
+    configs
+        .into_iter()
+        .map(|config| {
+            let id = ProviderId::parse(config.id)?;
+            for url in [
+                &config.authorization_endpoint,
+                &config.token_endpoint,
+                &config.jwks_uri,
+            ] {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(format!("Endpoint of identity provider {} must be http(s): {}", id, url));
+                }
+            }
+
+            // The `openid` scope is what makes the provider issue an ID token
+            let mut scopes = config.scopes;
+            if scopes.is_empty() {
+                scopes = DEFAULT_SCOPES.iter().map(|scope| scope.to_string()).collect();
+            } else if !scopes.iter().any(|scope| scope == "openid") {
+                scopes.insert(0, "openid".to_owned());
+            }
+
+            Ok(IdentityProvider {
+                id,
+                name: config.name,
+                issuer: config.issuer,
+                authorization_endpoint: config.authorization_endpoint,
+                token_endpoint: config.token_endpoint,
+                jwks_uri: config.jwks_uri,
+                client_id: config.client_id,
+                client_secret: config.client_secret,
+                scopes,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(id: &str, endpoint: &str, scopes: &str) -> String {
+        format!(
+            r#"[{{"id": "{id}", "name": "Corp SSO", "issuer": "https://idp.example.com",
+                "authorizationEndpoint": "{endpoint}/authorize", "tokenEndpoint": "{endpoint}/token",
+                "jwksUri": "{endpoint}/jwks", "clientId": "auth-service", "clientSecret": "secret"{scopes}}}]"#
+        )
+    }
+
+    #[test]
+    fn test_provider_id() {
+        assert!(ProviderId::parse("corp".to_owned()).is_ok());
+        assert!(ProviderId::parse("corp-sso-2".to_owned()).is_ok());
+        assert!(ProviderId::parse("".to_owned()).is_err());
+        assert!(ProviderId::parse("Corp".to_owned()).is_err());
+        assert!(ProviderId::parse("corp/sso".to_owned()).is_err());
+        assert!(ProviderId::parse("callback".to_owned()).is_err());
+    }
+
+    #[test]
+    fn test_parse_identity_providers() {
+        let json = registry("corp", "https://idp.example.com", "");
+        let providers = parse_identity_providers(&json).unwrap();
+        assert_eq!(providers.len(), 1);
+        assert_eq!(providers[0].id().as_ref(), "corp");
+        assert_eq!(providers[0].token_endpoint(), "https://idp.example.com/token");
+        assert_eq!(providers[0].scopes(), ["openid", "email", "profile"]);
+
+        let json = registry("corp", "https://idp.example.com", r#", "scopes": ["email"]"#);
+        let providers = parse_identity_providers(&json).unwrap();
+        assert_eq!(providers[0].scopes(), ["openid", "email"]);
+    }
+
+    #[test]
+    fn test_parse_invalid_identity_providers() {
+        assert!(parse_identity_providers(&registry("Corp", "https://idp.example.com", "")).is_err());
+        assert!(parse_identity_providers(&registry("corp", "idp.example.com", "")).is_err());
+        assert!(parse_identity_providers("{}").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Deserialize;
+
+use crate::domain::{email::Email, role::Role, tenant::TenantId};
+
+// Finds the entry of the user logging in, with `{email}` replaced by their email
+const DEFAULT_USER_FILTER: &str = "(mail={email})";
+
+// Lists the DNs of the groups an entry is a member of
+const DEFAULT_GROUP_ATTRIBUTE: &str = "memberOf";
+
+// An LDAP directory that holds the passwords of the users of some email
+// domains. Logins for those domains bind to the directory instead of checking
+// the password in the user store.
+#[derive(Debug, Clone)]
+pub struct LdapDirectory {
+    id: String,
+    tenant: TenantId,
+    url: String,
+    domains: Vec<String>,
+    base_dn: String,
+    // Searches are made anonymously without one
+    bind_dn: Option<String>,
+    bind_password: Option<String>,
+    user_filter: String,
+    group_attribute: String,
+    // Keyed by lowercased group DN
+    group_roles: BTreeMap<String, Role>,
+}
+
+impl LdapDirectory {
+    // Names the directory in logs and the audit log
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // Users of other tenants keep logging in with a password
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    // Normalized like `Email::domain`
+    pub fn domains(&self) -> &[String] {
+        &self.domains
+    }
+
+    pub fn base_dn(&self) -> &str {
+        &self.base_dn
+    }
+
+    pub fn bind_dn(&self) -> Option<&str> {
+        self.bind_dn.as_deref()
+    }
+
+    pub fn bind_password(&self) -> Option<&str> {
+        self.bind_password.as_deref()
+    }
+
+    pub fn user_filter(&self) -> &str {
+        &self.user_filter
+    }
+
+    pub fn group_attribute(&self) -> &str {
+        &self.group_attribute
+    }
+
+    // Without a mapping, roles are managed in auth-service as for other users
+    pub fn maps_groups(&self) -> bool {
+        !self.group_roles.is_empty()
+    }
+
+    // The roles of a member of `groups`; everyone has the `user` role
+    pub fn roles_for<'a>(&self, groups: impl IntoIterator<Item = &'a str>) -> BTreeSet<Role> {
+        let mut roles = BTreeSet::from([Role::user()]);
+        for group in groups {
+            roles.extend(self.group_roles.get(&group.to_lowercase()).cloned());
+        }
+        roles
+    }
+}
+
+// Shape of one entry of the `LDAP_DIRECTORIES` environment variable, e.g.
+// [{"id": "corp", "url": "ldaps://ldap.example.com", "domains": ["example.com"],
+//   "baseDn": "ou=people,dc=example,dc=com", "bindDn": "cn=auth-service,dc=example,dc=com",
+//   "bindPassword": "<secret>", "groupRoles": {"cn=admins,ou=groups,dc=example,dc=com": "admin"}}]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LdapDirectoryConfig {
+    id: String,
+    #[serde(default)]
+    tenant: Option<String>,
+    url: String,
+    domains: Vec<String>,
+    base_dn: String,
+    #[serde(default)]
+    bind_dn: Option<String>,
+    #[serde(default)]
+    bind_password: Option<String>,
+    #[serde(default)]
+    user_filter: Option<String>,
+    #[serde(default)]
+    group_attribute: Option<String>,
+    #[serde(default)]
+    group_roles: BTreeMap<String, String>,
+}
+
+pub fn parse_ldap_directories(json: &str) -> Result<Vec<LdapDirectory>, String> {
+    let configs: Vec<LdapDirectoryConfig> = serde_json::from_str(json)
+        .map_err(|e| format!("Invalid LDAP directory registry: {}", e))?;
+
+    configs
+        .into_iter()
+        .map(|config| {
+            let id = config.id;
+            if id.is_empty() {
+                return Err("LDAP directory id must not be empty".to_owned());
+            }
+            let tenant = match config.tenant {
+                Some(tenant) => TenantId::parse(tenant)?,
+                None => TenantId::default(),
+            };
+            if !config.url.starts_with("ldap://") && !config.url.starts_with("ldaps://") {
+                return Err(format!("URL of LDAP directory {} must be ldap(s): {}", id, config.url));
+            }
+            if config.domains.is_empty() {
+                return Err(format!("LDAP directory {} must list its email domains", id));
+            }
+            if config.bind_dn.is_some() != config.bind_password.is_some() {
+                return Err(format!(
+                    "LDAP directory {} needs both bindDn and bindPassword, or neither",
+                    id
+                ));
+            }
+
+            // Normalized like the emails they are compared with
+            let domains = config
+                .domains
+                .iter()
+                .map(|domain| {
+                    Email::parse(format!("user@{}", domain))
+                        .map(|email| email.domain().to_owned())
+                        .map_err(|_| format!("Invalid domain of LDAP directory {}: {}", id, domain))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let user_filter = config.user_filter.unwrap_or(DEFAULT_USER_FILTER.to_owned());
+            if !user_filter.contains("{email}") {
+                return Err(format!("User filter of LDAP directory {} must contain {{email}}", id));
+            }
+
+            let group_roles = config
+                .group_roles
+                .into_iter()
+                .map(|(group, role)| Ok((group.to_lowercase(), Role::parse(role)?)))
+                .collect::<Result<BTreeMap<_, _>, String>>()?;
+
+            Ok(LdapDirectory {
+                id,
+                tenant,
+                url: config.url,
+                domains,
+                base_dn: config.base_dn,
+                bind_dn: config.bind_dn,
+                bind_password: config.bind_password,
+                user_filter,
+                group_attribute: config
+                    .group_attribute
+                    .unwrap_or(DEFAULT_GROUP_ATTRIBUTE.to_owned()),
+                group_roles,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry(url: &str, extra: &str) -> String {
+        format!(
+            r#"[{{"id": "corp", "url": "{url}", "domains": ["Corp.Example.com"],
+                "baseDn": "ou=people,dc=example,dc=com"{extra}}}]"#
+        )
+    }
+
+    #[test]
+    fn test_parse_ldap_directories() {
+        let directories = parse_ldap_directories(&registry("ldap://localhost:389", "")).unwrap();
+        assert_eq!(directories.len(), 1);
+        let directory = &directories[0];
+        assert!(directory.tenant().is_default());
+        assert_eq!(directory.domains(), ["corp.example.com"]);
+        assert_eq!(directory.user_filter(), "(mail={email})");
+        assert_eq!(directory.group_attribute(), "memberOf");
+        assert!(directory.bind_dn().is_none());
+        assert!(!directory.maps_groups());
+
+        let extra = r#", "tenant": "acme", "bindDn": "cn=svc", "bindPassword": "secret",
+            "groupRoles": {"CN=Admins,DC=example,DC=com": "admin"}"#;
+        let directories = parse_ldap_directories(&registry("ldaps://ldap.example.com", extra)).unwrap();
+        assert_eq!(directories[0].tenant().as_ref(), "acme");
+        assert_eq!(directories[0].bind_dn(), Some("cn=svc"));
+        assert!(directories[0].maps_groups());
+    }
+
+    #[test]
+    fn test_parse_invalid_ldap_directories() {
+        assert!(parse_ldap_directories(&registry("http://localhost", "")).is_err());
+        assert!(parse_ldap_directories(&registry("ldap://localhost", r#", "bindDn": "cn=svc""#)).is_err());
+        assert!(parse_ldap_directories(&registry("ldap://localhost", r#", "userFilter": "(uid=x)""#)).is_err());
+        assert!(parse_ldap_directories(&registry("ldap://localhost", r#", "groupRoles": {"cn=x": "a b"}"#)).is_err());
+        assert!(parse_ldap_directories("{}").is_err());
+    }
+
+    #[test]
+    fn test_roles_for() {
+        let extra = r#", "groupRoles": {"cn=admins,dc=example,dc=com": "admin", "cn=ops,dc=example,dc=com": "admin"}"#;
+        let directory = &parse_ldap_directories(&registry("ldap://localhost", extra)).unwrap()[0];
+
+        assert_eq!(directory.roles_for([]), BTreeSet::from([Role::user()]));
+        assert_eq!(
+            directory.roles_for(["CN=Admins,DC=example,DC=com", "cn=ops,dc=example,dc=com", "cn=other"]),
+            BTreeSet::from([Role::user(), Role::admin()])
+        );
+    }
+}
@@ -1,5 +1,15 @@
 pub mod user;
 pub mod email;
 pub mod password;
+pub mod password_policy;
+pub mod role;
+pub mod api_key;
+pub mod client;
 pub mod error;
-pub mod data_stores;
\ No newline at end of file
+pub mod data_stores;
+pub mod email_client;
+pub mod audit;
+pub mod tenant;
+pub mod signing_key;
+pub mod identity_provider;
+pub mod ldap_directory;
@@ -1,10 +1,19 @@
+// Upper bound on what we accept at all, so that no request can make us hash
+// or compare an arbitrarily large input. `PasswordPolicy` sets the real limits
+// for new passwords.
+const MAX_PASSWORD_BYTES: usize = 1024;
+
+// A password exactly as the user typed it, whitespace included
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Password(String);
 
 impl Password {
     pub fn parse(password: String) ->  Result<Self, String> {
-        if password.trim().len() < 8 {
-            return Err("Password must be at least 8 characters long".to_string());
+        if password.is_empty() {
+            return Err("Password must not be empty".to_string());
+        }
+        if password.len() > MAX_PASSWORD_BYTES {
+            return Err("Password is too long".to_string());
         }
         Ok(Password(password))
     }
@@ -28,7 +37,13 @@ mod tests {
 
     #[test]
     fn test_invalid_password() {
-        let password = Password::parse("short".to_string());
-        assert!(password.is_err());
+        assert!(Password::parse("".to_string()).is_err());
+        assert!(Password::parse("x".repeat(MAX_PASSWORD_BYTES + 1)).is_err());
+    }
+
+    #[test]
+    fn test_whitespace_is_kept() {
+        let password = Password::parse("  spaced out  ".to_string()).unwrap();
+        assert_eq!(password.as_ref(), "  spaced out  ");
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use sha1::{Digest, Sha1};
+
+use crate::domain::{email::Email, password::Password};
+
+pub use auth_sdk::PasswordRejection;
+
+// Rules new passwords must satisfy. Existing passwords are not re-checked at login.
+#[derive(Debug, Clone)]
+pub struct PasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    min_score: u8,
+    // Shared by the policies of every tenant, see `with_limits`
+    breached_passwords: Arc<BreachedPasswords>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: 128,
+            min_score: 3,
+            breached_passwords: Arc::new(BreachedPasswords::default()),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn new(
+        min_length: usize,
+        max_length: usize,
+        min_score: u8,
+        breached_passwords: BreachedPasswords,
+    ) -> Result<Self, String> {
+        check_lengths(min_length, max_length)?;
+        Ok(Self {
+            min_length,
+            max_length,
+            min_score,
+            breached_passwords: Arc::new(breached_passwords),
+        })
+    }
+
+    // The same policy with other length and strength requirements, and the same
+    // breached passwords
+    pub fn with_limits(
+        &self,
+        min_length: usize,
+        max_length: usize,
+        min_score: u8,
+    ) -> Result<Self, String> {
+        check_lengths(min_length, max_length)?;
+        Ok(Self {
+            min_length,
+            max_length,
+            min_score,
+            breached_passwords: self.breached_passwords.clone(),
+        })
+    }
+
+    pub fn min_length(&self) -> usize {
+        self.min_length
+    }
+
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    pub fn min_score(&self) -> u8 {
+        self.min_score
+    }
+
+    // Returns every rule `password` breaks, not just the first one
+    pub fn check(&self, password: &Password, email: &Email) -> Result<(), Vec<PasswordRejection>> {
+        let password = password.as_ref();
+        let length = password.chars().count();
+
+        // Strength estimation is expensive on long inputs, so stop right here
+        if length > self.max_length {
+            return Err(vec![PasswordRejection::TooLong {
+                max_length: self.max_length,
+            }]);
+        }
+
+        let mut rejections = Vec::new();
+
+        if length < self.min_length {
+            rejections.push(PasswordRejection::TooShort {
+                min_length: self.min_length,
+            });
+        }
+
+        let (local_part, domain) = email.as_ref().rsplit_once('@').unwrap_or((email.as_ref(), ""));
+        // Emails keep the case of their local part, so compare both lowercased
+        let local_part = local_part.to_lowercase();
+        if local_part.chars().count() >= 3 && password.to_lowercase().contains(&local_part) {
+            rejections.push(PasswordRejection::ContainsEmail);
+        }
+
+        let entropy = zxcvbn::zxcvbn(password, &[email.as_ref(), &local_part, domain]);
+        let score = u8::from(entropy.score());
+        if score < self.min_score {
+            let feedback = entropy.feedback();
+            rejections.push(PasswordRejection::TooWeak {
+                score,
+                min_score: self.min_score,
+                warning: feedback
+                    .and_then(|feedback| feedback.warning())
+                    .map(|warning| warning.to_string()),
+                suggestions: feedback
+                    .map(|feedback| feedback.suggestions().iter().map(ToString::to_string).collect())
+                    .unwrap_or_default(),
+            });
+        }
+
+        if self.breached_passwords.contains(password) {
+            rejections.push(PasswordRejection::Breached);
+        }
+
+        if rejections.is_empty() {
+            Ok(())
+        } else {
+            Err(rejections)
+        }
+    }
+}
+
+// No password could satisfy a minimum length above the maximum
+fn check_lengths(min_length: usize, max_length: usize) -> Result<(), String> {
+    if min_length > max_length {
+        return Err(format!(
+            "Minimum password length {} exceeds the maximum {}",
+            min_length, max_length
+        ));
+    }
+    Ok(())
+}
+
+// Known-breached passwords, kept as the first 8 bytes of their SHA-1 digest in a
+// sorted vector: 8 bytes per entry, and collisions are rare enough not to matter.
+#[derive(Debug, Clone, Default)]
+pub struct BreachedPasswords(Vec<u64>);
+
+impl BreachedPasswords {
+    // One entry per line, either the password itself or its SHA-1 digest in hex,
+    // optionally followed by `:count` as in the Have I Been Pwned downloads.
+    // Empty lines and lines starting with `#` are skipped.
+    pub fn parse(contents: &str) -> Self {
+        let mut digests: Vec<u64> = contents
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let hash = line.split_once(':').map_or(line, |(hash, _)| hash);
+                match hex::decode(hash) {
+                    Ok(digest) if digest.len() == 20 => prefix(&digest),
+                    _ => prefix(&Sha1::digest(line.as_bytes())),
+                }
+            })
+            .collect();
+
+        digests.sort_unstable();
+        digests.dedup();
+        Self(digests)
+    }
+
+    pub fn contains(&self, password: &str) -> bool {
+        self.0
+            .binary_search(&prefix(&Sha1::digest(password.as_bytes())))
+            .is_ok()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn prefix(digest: &[u8]) -> u64 {
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRONG_PASSWORD: &str = "quiet-Lantern-orbit-57";
+
+    fn email() -> Email {
+        Email::parse("jane.doe@example.com".to_owned()).unwrap()
+    }
+
+    fn password(password: &str) -> Password {
+        Password::parse(password.to_owned()).unwrap()
+    }
+
+    #[test]
+    fn test_accepts_strong_password() {
+        let policy = PasswordPolicy::default();
+        assert_eq!(policy.check(&password(STRONG_PASSWORD), &email()), Ok(()));
+    }
+
+    #[test]
+    fn test_rejects_common_password_as_too_weak() {
+        let policy = PasswordPolicy::default();
+
+        let rejections = policy.check(&password("password123"), &email()).unwrap_err();
+
+        assert!(matches!(
+            rejections.as_slice(),
+            [PasswordRejection::TooWeak { score: 0, min_score: 3, .. }]
+        ));
+    }
+
+    #[test]
+    fn test_reports_every_problem() {
+        let policy = PasswordPolicy::default();
+
+        let rejections = policy.check(&password("abc"), &email()).unwrap_err();
+
+        assert_eq!(rejections[0], PasswordRejection::TooShort { min_length: 8 });
+        assert!(matches!(rejections[1], PasswordRejection::TooWeak { .. }));
+    }
+
+    #[test]
+    fn test_rejects_too_long_password() {
+        let policy = PasswordPolicy::default();
+
+        let rejections = policy.check(&password(&"x".repeat(129)), &email()).unwrap_err();
+
+        assert_eq!(rejections, vec![PasswordRejection::TooLong { max_length: 128 }]);
+    }
+
+    #[test]
+    fn test_rejects_password_containing_email() {
+        let policy = PasswordPolicy::new(8, 128, 0, BreachedPasswords::default()).unwrap();
+
+        let rejections = policy
+            .check(&password("Jane.Doe-quiet-Lantern-57"), &email())
+            .unwrap_err();
+
+        assert_eq!(rejections, vec![PasswordRejection::ContainsEmail]);
+
+        // With case-sensitive local parts, the email keeps its case
+        let email = Email::parse_with("Jane.Doe@example.com".to_owned(), true).unwrap();
+        let rejections = policy
+            .check(&password("quiet-jane.doe-Lantern-57"), &email)
+            .unwrap_err();
+
+        assert_eq!(rejections, vec![PasswordRejection::ContainsEmail]);
+    }
+
+    #[test]
+    fn test_rejects_breached_password() {
+        let breached = BreachedPasswords::parse(&format!("# known leaks\n{}\n", STRONG_PASSWORD));
+        let policy = PasswordPolicy::new(8, 128, 3, breached).unwrap();
+
+        let rejections = policy.check(&password(STRONG_PASSWORD), &email()).unwrap_err();
+
+        assert_eq!(rejections, vec![PasswordRejection::Breached]);
+    }
+
+    #[test]
+    fn test_rejects_min_length_above_max_length() {
+        assert!(PasswordPolicy::new(20, 10, 3, BreachedPasswords::default()).is_err());
+        assert!(PasswordPolicy::default().with_limits(200, 128, 3).is_err());
+        assert!(PasswordPolicy::default().with_limits(12, 12, 3).is_ok());
+    }
+
+    #[test]
+    fn test_breached_passwords_accepts_sha1_lines() {
+        // SHA-1 of "password123", in the Have I Been Pwned format
+        let breached =
+            BreachedPasswords::parse("CBFDAC6008F9CAB4083784CBD1874F76618D2A97:2472\r\n\n");
+
+        assert_eq!(breached.len(), 1);
+        assert!(breached.contains("password123"));
+        assert!(!breached.contains("password124"));
+    }
+
+    #[test]
+    fn test_breached_passwords_deduplicates() {
+        let breached = BreachedPasswords::parse("hunter2\nhunter2\n");
+
+        assert_eq!(breached.len(), 1);
+        assert!(breached.contains("hunter2"));
+    }
+
+    #[test]
+    fn test_rejections_are_tagged_with_a_code() {
+        let json = serde_json::to_value(PasswordRejection::TooShort { min_length: 8 }).unwrap();
+        assert_eq!(json, serde_json::json!({ "code": "too_short", "min_length": 8 }));
+    }
+}
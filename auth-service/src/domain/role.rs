@@ -0,0 +1,68 @@
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Role(String);
+
+impl Role {
+    pub const USER: &'static str = "user";
+    pub const ADMIN: &'static str = "admin";
+    // Client scope of the services trusted to revoke the tokens of any user
+    pub const REVOKE: &'static str = "revoke";
+
+    // Role names end up in JWT claims and are compared verbatim by downstream
+    // services, so only allow a small, case-normalized alphabet.
+    pub fn parse(role: String) -> Result<Self, String> {
+        let role = role.to_ascii_lowercase();
+        if role.is_empty() || role.len() > 64 {
+            return Err("Role must be between 1 and 64 characters long".to_string());
+        }
+        if !role
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+        {
+            return Err("Role contains invalid characters".to_string());
+        }
+        Ok(Role(role))
+    }
+
+    pub fn user() -> Self {
+        Role(Self::USER.to_owned())
+    }
+
+    pub fn admin() -> Self {
+        Role(Self::ADMIN.to_owned())
+    }
+
+    pub fn revoke() -> Self {
+        Role(Self::REVOKE.to_owned())
+    }
+}
+
+impl AsRef<str> for Role {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_role() {
+        let role = Role::parse("reports:read".to_string());
+        assert!(role.is_ok());
+        assert_eq!(role.unwrap().as_ref(), "reports:read");
+    }
+
+    #[test]
+    fn test_role_is_lowercased() {
+        let role = Role::parse("Admin".to_string()).unwrap();
+        assert_eq!(role, Role::admin());
+    }
+
+    #[test]
+    fn test_invalid_role() {
+        assert!(Role::parse("".to_string()).is_err());
+        assert!(Role::parse("has space".to_string()).is_err());
+        assert!(Role::parse("a".repeat(65)).is_err());
+    }
+}
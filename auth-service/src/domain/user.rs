@@ -1,31 +1,104 @@
+use std::collections::BTreeSet;
+
 use crate::domain::email::Email;
+use crate::domain::identity_provider::ExternalIdentity;
 use crate::domain::password::Password;
+use crate::domain::role::Role;
+use crate::domain::tenant::TenantId;
 
 #[derive(Debug, Clone)]
 pub struct User {
+    tenant: TenantId,
     email: Email,
-    password: Password,
+    // Accounts created at an identity provider have none
+    password: Option<Password>,
     requires_2fa: bool,
+    roles: BTreeSet<Role>,
+    identities: BTreeSet<ExternalIdentity>,
 }
 
 impl User {
+    // Every new user starts with the `user` role, in the default tenant.
     pub fn new(email: Email, password: Password, requires_2fa: bool) -> Self {
         Self {
+            tenant: TenantId::default(),
             email,
-            password,
+            password: Some(password),
             requires_2fa,
+            roles: BTreeSet::from([Role::user()]),
+            identities: BTreeSet::new(),
+        }
+    }
+
+    // A user signing up through an identity provider, who logs in there
+    // rather than with a password
+    pub fn from_identity(email: Email, identity: ExternalIdentity) -> Self {
+        Self {
+            tenant: TenantId::default(),
+            email,
+            password: None,
+            requires_2fa: false,
+            roles: BTreeSet::from([Role::user()]),
+            identities: BTreeSet::from([identity]),
+        }
+    }
+
+    // A user whose password is kept in an LDAP directory, see `domain::ldap_directory`
+    pub fn from_directory(email: Email) -> Self {
+        Self {
+            tenant: TenantId::default(),
+            email,
+            password: None,
+            requires_2fa: false,
+            roles: BTreeSet::from([Role::user()]),
+            identities: BTreeSet::new(),
         }
     }
 
+    pub fn with_roles(mut self, roles: BTreeSet<Role>) -> Self {
+        self.roles = roles;
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    pub fn tenant(&self) -> &TenantId {
+        &self.tenant
+    }
+
     pub fn email(&self) -> &Email {
         &self.email
     }
 
-    pub fn password(&self) -> &Password {
-        &self.password
+    pub fn password(&self) -> Option<&Password> {
+        self.password.as_ref()
     }
 
     pub fn requires_2fa(&self) -> bool {
         self.requires_2fa
     }
-}
\ No newline at end of file
+
+    pub fn roles(&self) -> &BTreeSet<Role> {
+        &self.roles
+    }
+
+    pub fn has_role(&self, role: &Role) -> bool {
+        self.roles.contains(role)
+    }
+
+    pub fn set_roles(&mut self, roles: BTreeSet<Role>) {
+        self.roles = roles;
+    }
+
+    // Identity provider accounts the user can log in with
+    pub fn identities(&self) -> &BTreeSet<ExternalIdentity> {
+        &self.identities
+    }
+
+    pub fn add_identity(&mut self, identity: ExternalIdentity) {
+        self.identities.insert(identity);
+    }
+}
@@ -0,0 +1,246 @@
+use std::error::Error;
+
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{
+        server::{Router, TcpIncoming},
+        Server,
+    },
+    Code, Request, Response, Status,
+};
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEventType, AuditOutcome},
+        email::Email,
+        error::AuthAPIError,
+        tenant::TenantId,
+    },
+    routes::{
+        may_revoke_user_tokens, record_revocation, record_verification, revoke_user_token,
+        Revocation,
+    },
+    utils::{
+        audit::AuditContext,
+        auth::{require_service_token, validate_bearer_token, Claims},
+    },
+};
+
+use proto::{
+    auth_service_server::{AuthService, AuthServiceServer},
+    GetUserRequest, GetUserResponse, RevokeTokenRequest, RevokeTokenResponse, User,
+    VerifyTokenRequest, VerifyTokenResponse,
+};
+
+// Types and client generated from `proto/auth.proto`
+pub mod proto {
+    tonic::include_proto!("auth.v1");
+
+    // Lets tools like grpcurl discover the API through the reflection service
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("auth_descriptor");
+}
+
+// gRPC interface to what other services need on every request, backed by the
+// same stores as the HTTP API. Like `/verify-token`, every call requires a
+// service token.
+pub struct AuthGrpcService {
+    state: AppState,
+}
+
+impl AuthGrpcService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[tonic::async_trait]
+impl AuthService for AuthGrpcService {
+    async fn verify_token(
+        &self,
+        request: Request<VerifyTokenRequest>,
+    ) -> Result<Response<VerifyTokenResponse>, Status> {
+        let audit = AuditContext::from_grpc_request(&request);
+        let result = verify(&self.state, &request).await;
+        record_verification(&self.state, &audit, &result).await;
+
+        let claims = result?;
+        Ok(Response::new(VerifyTokenResponse {
+            claims: Some(claims.into()),
+        }))
+    }
+
+    async fn get_user(
+        &self,
+        request: Request<GetUserRequest>,
+    ) -> Result<Response<GetUserResponse>, Status> {
+        require_service(&request)?;
+
+        let request = request.into_inner();
+        let email =
+            Email::parse(request.email).map_err(|_| Status::invalid_argument("Invalid email"))?;
+        let tenant = if request.tenant.is_empty() {
+            TenantId::default()
+        } else {
+            TenantId::parse(request.tenant).map_err(|_| Status::from(AuthAPIError::TenantNotFound))?
+        };
+        if self.state.tenants.get(&tenant).is_none() {
+            return Err(AuthAPIError::TenantNotFound.into());
+        }
+
+        let user_store = self.state.user_store.read().await;
+        let user = user_store
+            .get_user(&tenant, &email)
+            .await
+            .map_err(|_| Status::not_found("User not found"))?;
+
+        Ok(Response::new(GetUserResponse {
+            user: Some(User {
+                email: user.email().as_ref().to_owned(),
+                roles: user.roles().iter().map(|role| role.as_ref().to_owned()).collect(),
+                requires_2fa: user.requires_2fa(),
+                tenant: user.tenant().as_ref().to_owned(),
+            }),
+        }))
+    }
+
+    async fn revoke_token(
+        &self,
+        request: Request<RevokeTokenRequest>,
+    ) -> Result<Response<RevokeTokenResponse>, Status> {
+        let audit = AuditContext::from_grpc_request(&request);
+        match revoke(&self.state, &request).await {
+            Ok((client_id, revocation)) => {
+                record_revocation(&self.state, &audit, &client_id, &revocation).await
+            }
+            Err(e) => {
+                audit
+                    .record(
+                        &self.state,
+                        AuditEventType::TokenRevoked,
+                        None,
+                        AuditOutcome::Failure,
+                        Some(e.message()),
+                    )
+                    .await;
+                return Err(e.into());
+            }
+        }
+
+        Ok(Response::new(RevokeTokenResponse {}))
+    }
+}
+
+async fn verify(
+    state: &AppState,
+    request: &Request<VerifyTokenRequest>,
+) -> Result<Claims, AuthAPIError> {
+    require_service(request)?;
+
+    let token = request.get_ref().token.as_str();
+    if token.is_empty() {
+        return Err(AuthAPIError::MissingToken);
+    }
+
+    let (claims, _) = validate_bearer_token(token, state).await?;
+
+    Ok(claims)
+}
+
+// Answered as `/revoke` is, so a caller cannot tell which tokens are valid
+async fn revoke(
+    state: &AppState,
+    request: &Request<RevokeTokenRequest>,
+) -> Result<(String, Revocation), AuthAPIError> {
+    let claims = require_service_token(&request.metadata().clone().into_headers())?;
+
+    let token = request.get_ref().token.as_str();
+    if token.is_empty() {
+        return Err(AuthAPIError::MissingToken);
+    }
+
+    let may_revoke = may_revoke_user_tokens(claims.scope.split_whitespace());
+    let revocation = revoke_user_token(state, may_revoke, token, None).await?;
+
+    Ok((claims.sub, revocation))
+}
+
+fn require_service<T>(request: &Request<T>) -> Result<(), AuthAPIError> {
+    require_service_token(&request.metadata().clone().into_headers())?;
+    Ok(())
+}
+
+impl From<Claims> for proto::Claims {
+    fn from(claims: Claims) -> Self {
+        Self {
+            sub: claims.sub,
+            exp: claims.exp as u64,
+            iat: claims.iat as u64,
+            roles: claims.roles,
+            iss: claims.iss.unwrap_or_default(),
+            tenant: claims.tenant.unwrap_or_default(),
+        }
+    }
+}
+
+// Status codes mirror the HTTP statuses; the message is the same as over HTTP
+impl From<AuthAPIError> for Status {
+    fn from(error: AuthAPIError) -> Self {
+        let code = match error {
+            AuthAPIError::UserAlreadyExists => Code::AlreadyExists,
+            AuthAPIError::InvalidCredentials
+            | AuthAPIError::WeakPassword(_)
+            | AuthAPIError::MissingToken
+            | AuthAPIError::InvalidApiKeyRequest => Code::InvalidArgument,
+            AuthAPIError::IncorrectCredentials | AuthAPIError::InvalidToken => {
+                Code::Unauthenticated
+            }
+            AuthAPIError::ApiKeyNotFound
+            | AuthAPIError::TenantNotFound
+            | AuthAPIError::IdentityProviderNotFound => Code::NotFound,
+            AuthAPIError::ServiceAuthRequired
+            | AuthAPIError::Forbidden
+            | AuthAPIError::CsrfCheckFailed
+            | AuthAPIError::UnverifiedEmail
+            | AuthAPIError::ManagedByDirectory => Code::PermissionDenied,
+            AuthAPIError::IdentityProviderFailed | AuthAPIError::DirectoryUnavailable => {
+                Code::Unavailable
+            }
+            AuthAPIError::UnexpectedError => Code::Internal,
+        };
+        Status::new(code, error.message())
+    }
+}
+
+// The gRPC server, run next to the HTTP `Application` on its own port
+pub struct GrpcApplication {
+    router: Router,
+    incoming: TcpIncoming,
+    // Exposed so tests can connect to it
+    pub address: String,
+}
+
+impl GrpcApplication {
+    pub async fn build(app_state: AppState, address: &str) -> Result<Self, Box<dyn Error>> {
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(proto::FILE_DESCRIPTOR_SET)
+            .build_v1()?;
+        let router = Server::builder()
+            .add_service(AuthServiceServer::new(AuthGrpcService::new(app_state)))
+            .add_service(reflection);
+
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?.to_string();
+
+        Ok(GrpcApplication {
+            router,
+            incoming: TcpIncoming::from(listener),
+            address,
+        })
+    }
+
+    pub async fn run(self) -> Result<(), tonic::transport::Error> {
+        println!("gRPC listening on {}", &self.address);
+        self.router.serve_with_incoming(self.incoming).await
+    }
+}
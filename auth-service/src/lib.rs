@@ -1,22 +1,106 @@
-use std::error::Error;
+use std::{error::Error, net::SocketAddr};
 
-use axum::{Json, Router, http::StatusCode, response::{IntoResponse, Response}, routing::post, serve::Serve};
-use serde::{Deserialize, Serialize};
+use axum::{Json, Router, extract::{ConnectInfo, connect_info::IntoMakeServiceWithConnectInfo}, http::{HeaderName, HeaderValue, StatusCode, header}, middleware::{self, AddExtension}, response::{IntoResponse, Response}, serve::Serve};
 use tokio::net::TcpListener;
-use tower_http::services::ServeDir;
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    services::ServeDir,
+};
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
+use utoipa_swagger_ui::SwaggerUi;
 
 use app_state::AppState;
 
-use domain::error::AuthAPIError;
+use domain::error::{AuthAPIError, OAuthError};
+use utils::{audit::REQUEST_ID_HEADER, constants::JWT_COOKIE_NAME};
+
+pub use auth_sdk::ErrorResponse;
 
 pub mod routes;
+pub mod grpc;
 pub mod domain;
 pub mod services;
 pub mod app_state;
+pub mod utils;
+
+// The server hands every request the peer address, which ends up in audit events
+type Server = Serve<
+    TcpListener,
+    IntoMakeServiceWithConnectInfo<Router, SocketAddr>,
+    AddExtension<Router, ConnectInfo<SocketAddr>>,
+>;
+
+// Top-level OpenAPI document. Paths are added from the `#[utoipa::path]`
+// annotations of the handlers as they are registered in `api_router`.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "auth-service", description = "Authentication service"),
+    components(schemas(ErrorResponse)),
+    modifiers(&SecuritySchemes)
+)]
+pub struct ApiDoc;
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "jwt_cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(JWT_COOKIE_NAME))),
+        );
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "client_basic",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Basic).build()),
+        );
+    }
+}
+
+// Every API route, together with the OpenAPI description of it. Registering
+// a route here is the only way to add it, so the served document cannot drift.
+pub fn api_router() -> OpenApiRouter<AppState> {
+    OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .merge(tenant_routes())
+        .nest("/t/{tenant}", tenant_routes())
+        .routes(routes!(routes::logout))
+        .routes(routes!(routes::verify_token))
+        .routes(routes!(routes::introspect_token))
+        .routes(routes!(routes::token))
+        .routes(routes!(routes::revoke))
+        .routes(routes!(routes::list_audit_events))
+        .routes(routes!(routes::metrics))
+        .routes(routes!(routes::health_live))
+        .routes(routes!(routes::health_ready))
+}
+
+// Routes that act on the accounts of one tenant. They are served as they are
+// for the default tenant and under `/t/{tenant}` for every tenant.
+fn tenant_routes() -> OpenApiRouter<AppState> {
+    OpenApiRouter::new()
+        .routes(routes!(routes::signup))
+        .routes(routes!(routes::login))
+        .routes(routes!(routes::request_magic_link))
+        .routes(routes!(routes::verify_magic_link))
+        .routes(routes!(routes::list_identity_providers))
+        .routes(routes!(routes::start_oidc_login))
+        .routes(routes!(routes::complete_oidc_login))
+        .routes(routes!(routes::verify_2fa))
+        .routes(routes!(routes::create_api_key, routes::list_api_keys))
+        .routes(routes!(routes::revoke_api_key))
+        .routes(routes!(routes::jwks))
+}
 
 // This struct encapsulates our application-related logic.
 pub struct Application {
-    server: Serve<TcpListener, Router, Router>,
+    server: Server,
     // address is exposed as a public field
     // so we have access to it in tests.
     pub address: String,
@@ -25,18 +109,36 @@ pub struct Application {
 impl Application {
     pub async fn build(app_state: AppState, address: &str) -> Result<Self, Box<dyn Error>> {
         let assets_dir = ServeDir::new("assets");
-        let router = Router::new()
+        let cors = app_state.cors_config.layer();
+        let (api, openapi) = api_router()
+            .route_layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                utils::metrics::track_metrics,
+            ))
+            .split_for_parts();
+        let router = api
+            .merge(SwaggerUi::new("/docs").url("/openapi.json", openapi))
             .fallback_service(assets_dir)
-            .route("/signup", post(routes::signup))
-            .route("/login", post(routes::login))
-            .route("/logout", post(routes::logout))
-            .route("/verify-2fa", post(routes::verify_2fa))
-            .route("/verify-token", post(routes::verify_token))
-            .with_state(app_state);
+            .layer(middleware::from_fn_with_state(
+                app_state.clone(),
+                utils::csrf::csrf_protect,
+            ))
+            .with_state(app_state)
+            // Tag every request with an id (keeping one set by the caller) and echo it back
+            .layer(PropagateRequestIdLayer::new(HeaderName::from_static(REQUEST_ID_HEADER)))
+            .layer(SetRequestIdLayer::new(
+                HeaderName::from_static(REQUEST_ID_HEADER),
+                MakeRequestUuid,
+            ))
+            // Outermost, so preflight requests are answered before anything else runs
+            .layer(cors);
 
         let listener = tokio::net::TcpListener::bind(address).await?;
         let address = listener.local_addr()?.to_string();
-        let server = axum::serve(listener, router);
+        let server = axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        );
 
         Ok(Application {
             server,
@@ -50,23 +152,59 @@ impl Application {
     }
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct ErrorResponse {
-    pub error: String,
+impl IntoResponse for AuthAPIError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            AuthAPIError::UserAlreadyExists => StatusCode::CONFLICT,
+            AuthAPIError::InvalidCredentials => StatusCode::BAD_REQUEST,
+            AuthAPIError::WeakPassword(_) => StatusCode::BAD_REQUEST,
+            AuthAPIError::IncorrectCredentials => StatusCode::UNAUTHORIZED,
+            AuthAPIError::MissingToken => StatusCode::BAD_REQUEST,
+            AuthAPIError::InvalidToken => StatusCode::UNAUTHORIZED,
+            AuthAPIError::InvalidApiKeyRequest => StatusCode::BAD_REQUEST,
+            AuthAPIError::ApiKeyNotFound => StatusCode::NOT_FOUND,
+            // Deliberately not a 401, so callers can tell a rejected service token
+            // apart from the user token they asked us to verify being invalid.
+            AuthAPIError::ServiceAuthRequired => StatusCode::FORBIDDEN,
+            AuthAPIError::Forbidden => StatusCode::FORBIDDEN,
+            AuthAPIError::CsrfCheckFailed => StatusCode::FORBIDDEN,
+            AuthAPIError::TenantNotFound => StatusCode::NOT_FOUND,
+            AuthAPIError::IdentityProviderNotFound => StatusCode::NOT_FOUND,
+            // The upstream provider failed or answered something we cannot trust
+            AuthAPIError::IdentityProviderFailed => StatusCode::BAD_GATEWAY,
+            AuthAPIError::UnverifiedEmail => StatusCode::FORBIDDEN,
+            AuthAPIError::DirectoryUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            AuthAPIError::ManagedByDirectory => StatusCode::FORBIDDEN,
+            AuthAPIError::UnexpectedError => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        let error = self.message().to_string();
+        let reasons = match self {
+            AuthAPIError::WeakPassword(reasons) => reasons,
+            _ => Vec::new(),
+        };
+        let body = Json(ErrorResponse { error, reasons });
+        (status, body).into_response()
+    }
 }
 
-impl IntoResponse for AuthAPIError {
+impl IntoResponse for OAuthError {
     fn into_response(self) -> Response {
-        let (status, error_message) = match self {
-            AuthAPIError::UserAlreadyExists => (StatusCode::CONFLICT, "User already exists"),
-            AuthAPIError::InvalidCredentials => (StatusCode::BAD_REQUEST, "Invalid credentials"),
-            AuthAPIError::UnexpectedError => {
-                (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error")
-            }
+        let status = match self {
+            OAuthError::InvalidClient => StatusCode::UNAUTHORIZED,
+            OAuthError::ServerError => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST,
         };
         let body = Json(ErrorResponse {
-            error: error_message.to_string(),
+            error: self.code().to_string(),
+            reasons: Vec::new(),
         });
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        if status == StatusCode::UNAUTHORIZED {
+            response.headers_mut().insert(
+                header::WWW_AUTHENTICATE,
+                HeaderValue::from_static("Basic realm=\"auth-service\""),
+            );
+        }
+        response
     }
-}
\ No newline at end of file
+}
@@ -1,15 +1,261 @@
-use std::sync::Arc;
+use std::{env, sync::Arc};
+use axum_extra::extract::cookie::SameSite;
 use tokio::sync::RwLock;
-use auth_service::{Application, services::hashmap_user_store::HashmapUserStore, app_state::AppState};
+use auth_service::{
+    Application,
+    app_state::AppState,
+    grpc::GrpcApplication,
+    domain::{
+        client::parse_client_registry,
+        data_stores::ClientStore,
+        identity_provider::parse_identity_providers,
+        ldap_directory::parse_ldap_directories,
+        password_policy::{BreachedPasswords, PasswordPolicy},
+        signing_key::SigningKey,
+        tenant::{parse_tenant_registry, Tenant, TenantId, TenantRegistry},
+    },
+    services::{
+        hashmap_api_key_store::HashmapApiKeyStore, hashmap_client_store::HashmapClientStore,
+        hashmap_two_fa_code_store::HashmapTwoFACodeStore, hashmap_user_store::HashmapUserStore,
+        hashset_banned_token_store::HashsetBannedTokenStore,
+        json_lines_audit_sink::JsonLinesAuditSink, mock_email_client::MockEmailClient,
+    },
+    utils::constants::{
+        env::{
+            AUDIT_LOG_MAX_BYTES_ENV_VAR, AUDIT_LOG_MAX_FILES_ENV_VAR, AUDIT_LOG_PATH_ENV_VAR,
+            AUTH_COOKIE_DOMAIN_ENV_VAR, AUTH_COOKIE_MAX_AGE_ENV_VAR, AUTH_COOKIE_NAME_ENV_VAR,
+            AUTH_COOKIE_PATH_ENV_VAR, AUTH_COOKIE_SAME_SITE_ENV_VAR, AUTH_COOKIE_SECURE_ENV_VAR,
+            BREACHED_PASSWORDS_PATH_ENV_VAR, CORS_ALLOWED_HEADERS_ENV_VAR,
+            CORS_ALLOWED_METHODS_ENV_VAR, CORS_ALLOWED_ORIGINS_ENV_VAR,
+            CSRF_TRUSTED_ORIGINS_ENV_VAR, MAGIC_LINK_BASE_URL_ENV_VAR, PASSWORD_MAX_LENGTH_ENV_VAR,
+            PASSWORD_MIN_LENGTH_ENV_VAR, PASSWORD_MIN_SCORE_ENV_VAR, SERVICE_CLIENTS_ENV_VAR,
+            OIDC_PROVIDERS_ENV_VAR, OIDC_REDIRECT_BASE_URL_ENV_VAR, TENANTS_ENV_VAR,
+            LDAP_DIRECTORIES_ENV_VAR, JWT_SIGNING_KEY_ENV_VAR, REVOCATION_WEBHOOK_URLS_ENV_VAR,
+        },
+        prod, JWT_SECRET,
+    },
+    utils::{
+        cookies::{parse_same_site, CookieSettings},
+        cors::CorsConfig,
+        csrf::CsrfConfig,
+        ldap::LdapConfig,
+        magic_link::MagicLinkConfig,
+        oidc::OidcConfig,
+        revocations::RevocationNotifier,
+    },
+};
 
 #[tokio::main]
 async fn main() {
     let user_store = Arc::new(RwLock::new(HashmapUserStore::default()));
-    let app_state = AppState::new(user_store);
+    let banned_token_store = Arc::new(RwLock::new(HashsetBannedTokenStore::default()));
+    let two_fa_code_store = Arc::new(RwLock::new(HashmapTwoFACodeStore::default()));
+    let api_key_store = Arc::new(RwLock::new(HashmapApiKeyStore::default()));
+    let client_store = Arc::new(RwLock::new(load_client_registry().await));
+    let email_client = Arc::new(MockEmailClient);
+    let audit_sink = Arc::new(open_audit_sink().await);
+    let tenants = load_tenant_registry().await;
+    let ldap_config = load_ldap_config(&tenants);
+    let app_state = AppState::new(
+        user_store,
+        banned_token_store,
+        two_fa_code_store,
+        api_key_store,
+        client_store,
+        email_client,
+        audit_sink,
+    )
+    .with_tenants(tenants)
+    .with_csrf_config(load_csrf_config())
+    .with_cors_config(load_cors_config())
+    .with_cookie_settings(load_cookie_settings())
+    .with_magic_link_config(load_magic_link_config())
+    .with_oidc_config(load_oidc_config())
+    .with_ldap_config(ldap_config)
+    .with_revocation_notifier(load_revocation_notifier());
     
-    let app = Application::build(app_state, "0.0.0.0:3000")
+    let app = Application::build(app_state.clone(), prod::APP_ADDRESS)
         .await
         .expect("Failed to build app");
+    let grpc = GrpcApplication::build(app_state, prod::GRPC_ADDRESS)
+        .await
+        .expect("Failed to build gRPC server");
+
+    tokio::select! {
+        result = app.run() => result.expect("Failed to run app"),
+        result = grpc.run() => result.expect("Failed to run gRPC server"),
+    }
+}
+
+// Register the confidential clients (other services) listed in `SERVICE_CLIENTS`
+async fn load_client_registry() -> HashmapClientStore {
+    let mut client_store = HashmapClientStore::default();
+
+    let registry = env::var(SERVICE_CLIENTS_ENV_VAR).unwrap_or("[]".to_owned());
+    let clients = parse_client_registry(&registry).expect("Invalid SERVICE_CLIENTS");
+
+    for client in clients {
+        client_store
+            .add_client(client)
+            .await
+            .expect("Duplicate client in SERVICE_CLIENTS");
+    }
+
+    client_store
+}
+
+// Open the audit log configured by `AUDIT_LOG_PATH`, `AUDIT_LOG_MAX_BYTES` and `AUDIT_LOG_MAX_FILES`
+async fn open_audit_sink() -> JsonLinesAuditSink {
+    let path = env::var(AUDIT_LOG_PATH_ENV_VAR).unwrap_or(prod::AUDIT_LOG_PATH.to_owned());
+    let max_bytes = match env::var(AUDIT_LOG_MAX_BYTES_ENV_VAR) {
+        Ok(value) => value.parse().expect("Invalid AUDIT_LOG_MAX_BYTES"),
+        Err(_) => prod::AUDIT_LOG_MAX_BYTES,
+    };
+    let max_files = match env::var(AUDIT_LOG_MAX_FILES_ENV_VAR) {
+        Ok(value) => value.parse().expect("Invalid AUDIT_LOG_MAX_FILES"),
+        Err(_) => prod::AUDIT_LOG_MAX_FILES,
+    };
+
+    JsonLinesAuditSink::new(path, max_bytes, max_files)
+        .await
+        .expect("Failed to open audit log")
+}
+
+// Build the password policy from `PASSWORD_MIN_LENGTH`, `PASSWORD_MAX_LENGTH`,
+// `PASSWORD_MIN_SCORE` and the optional `BREACHED_PASSWORDS_PATH` file
+async fn load_password_policy() -> PasswordPolicy {
+    let default = PasswordPolicy::default();
+
+    let min_length = match env::var(PASSWORD_MIN_LENGTH_ENV_VAR) {
+        Ok(value) => value.parse().expect("Invalid PASSWORD_MIN_LENGTH"),
+        Err(_) => default.min_length(),
+    };
+    let max_length = match env::var(PASSWORD_MAX_LENGTH_ENV_VAR) {
+        Ok(value) => value.parse().expect("Invalid PASSWORD_MAX_LENGTH"),
+        Err(_) => default.max_length(),
+    };
+    let min_score = match env::var(PASSWORD_MIN_SCORE_ENV_VAR) {
+        Ok(value) => value.parse().expect("Invalid PASSWORD_MIN_SCORE"),
+        Err(_) => default.min_score(),
+    };
+
+    let breached_passwords = match env::var(BREACHED_PASSWORDS_PATH_ENV_VAR) {
+        Ok(path) => {
+            let contents = tokio::fs::read_to_string(&path)
+                .await
+                .expect("Failed to read BREACHED_PASSWORDS_PATH");
+            let breached_passwords = BreachedPasswords::parse(&contents);
+            println!("loaded {} breached passwords", breached_passwords.len());
+            breached_passwords
+        }
+        Err(_) => BreachedPasswords::default(),
+    };
+
+    PasswordPolicy::new(min_length, max_length, min_score, breached_passwords)
+        .expect("Invalid password policy")
+}
+
+// The default tenant, signing with `JWT_SECRET` (or the Ed25519 private key PEM
+// in `JWT_SIGNING_KEY`) under the deployment-wide password policy, and the
+// tenants listed in `TENANTS`
+async fn load_tenant_registry() -> TenantRegistry {
+    let mut default_tenant = Tenant::new(TenantId::default(), JWT_SECRET.clone())
+        .with_password_policy(load_password_policy().await);
+    if let Ok(pem) = env::var(JWT_SIGNING_KEY_ENV_VAR) {
+        default_tenant = default_tenant
+            .with_signing_key(SigningKey::from_pem(&pem).expect("Invalid JWT_SIGNING_KEY"));
+    }
+
+    let registry = env::var(TENANTS_ENV_VAR).unwrap_or("[]".to_owned());
+    parse_tenant_registry(&registry, default_tenant).expect("Invalid TENANTS")
+}
+
+// Origins listed in `CSRF_TRUSTED_ORIGINS` (comma-separated) may send
+// state-changing requests, e.g. `http://localhost:8000` for app-service
+fn load_csrf_config() -> CsrfConfig {
+    CsrfConfig::new(env_list(CSRF_TRUSTED_ORIGINS_ENV_VAR))
+}
+
+// Cross-origin frontends listed in `CORS_ALLOWED_ORIGINS`, optionally restricted
+// to `CORS_ALLOWED_METHODS` and `CORS_ALLOWED_HEADERS` (all comma-separated)
+fn load_cors_config() -> CorsConfig {
+    CorsConfig::new(
+        env_list(CORS_ALLOWED_ORIGINS_ENV_VAR),
+        env_list(CORS_ALLOWED_METHODS_ENV_VAR),
+        env_list(CORS_ALLOWED_HEADERS_ENV_VAR),
+    )
+    .expect("Invalid CORS configuration")
+}
+
+// Attributes of the auth cookie, from the `AUTH_COOKIE_*` variables
+fn load_cookie_settings() -> CookieSettings {
+    let default = CookieSettings::default();
+
+    let name = env::var(AUTH_COOKIE_NAME_ENV_VAR).unwrap_or(default.name().to_owned());
+    let domain = env::var(AUTH_COOKIE_DOMAIN_ENV_VAR).ok();
+    let path = env::var(AUTH_COOKIE_PATH_ENV_VAR).unwrap_or("/".to_owned());
+    let secure = env::var(AUTH_COOKIE_SECURE_ENV_VAR)
+        .map(|value| value.eq_ignore_ascii_case("true") || value == "1")
+        .unwrap_or(false);
+    let same_site = match env::var(AUTH_COOKIE_SAME_SITE_ENV_VAR) {
+        Ok(value) => parse_same_site(&value).expect("Invalid AUTH_COOKIE_SAME_SITE"),
+        Err(_) => SameSite::Lax,
+    };
+    let max_age = env::var(AUTH_COOKIE_MAX_AGE_ENV_VAR)
+        .ok()
+        .map(|value| value.parse().expect("Invalid AUTH_COOKIE_MAX_AGE"));
+
+    CookieSettings::new(name, domain, path, secure, same_site, max_age)
+        .expect("Invalid auth cookie settings")
+}
+
+// Emailed login links point at `MAGIC_LINK_BASE_URL`, where users reach auth-service
+fn load_magic_link_config() -> MagicLinkConfig {
+    match env::var(MAGIC_LINK_BASE_URL_ENV_VAR) {
+        Ok(base_url) => MagicLinkConfig::new(&base_url).expect("Invalid MAGIC_LINK_BASE_URL"),
+        Err(_) => MagicLinkConfig::default(),
+    }
+}
+
+// The identity providers listed in `OIDC_PROVIDERS`, which send users back to
+// `OIDC_REDIRECT_BASE_URL`, where they reach auth-service
+fn load_oidc_config() -> OidcConfig {
+    let registry = env::var(OIDC_PROVIDERS_ENV_VAR).unwrap_or("[]".to_owned());
+    let providers = parse_identity_providers(&registry).expect("Invalid OIDC_PROVIDERS");
+    let base_url =
+        env::var(OIDC_REDIRECT_BASE_URL_ENV_VAR).unwrap_or("http://localhost:3000".to_owned());
+
+    OidcConfig::new(&base_url, providers).expect("Invalid OIDC configuration")
+}
+
+// The LDAP directories listed in `LDAP_DIRECTORIES`, each holding the passwords
+// of some email domains of a tenant
+fn load_ldap_config(tenants: &TenantRegistry) -> LdapConfig {
+    let registry = env::var(LDAP_DIRECTORIES_ENV_VAR).unwrap_or("[]".to_owned());
+    let directories = parse_ldap_directories(&registry).expect("Invalid LDAP_DIRECTORIES");
+    for directory in &directories {
+        if tenants.get(directory.tenant()).is_none() {
+            panic!("LDAP directory {} names unknown tenant {}", directory.id(), directory.tenant());
+        }
+    }
+
+    LdapConfig::new(directories).expect("Invalid LDAP configuration")
+}
+
+// The services `REVOCATION_WEBHOOK_URLS` points at are told about logouts and
+// revoked tokens
+fn load_revocation_notifier() -> RevocationNotifier {
+    RevocationNotifier::new(env_list(REVOCATION_WEBHOOK_URLS_ENV_VAR))
+        .expect("Invalid REVOCATION_WEBHOOK_URLS")
+}
 
-    app.run().await.expect("Failed to run app");
+// A comma-separated list from the environment, empty when the variable is unset
+fn env_list(name: &str) -> Vec<String> {
+    env::var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+        .collect()
 }
@@ -0,0 +1,269 @@
+use std::collections::BTreeSet;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        api_key::{ApiKey, ApiKeyId, ApiKeySecret},
+        audit::{AuditEventType, AuditOutcome},
+        data_stores::ApiKeyStoreError,
+        email::Email,
+        error::AuthAPIError,
+        role::Role,
+        tenant::Tenant,
+    },
+    utils::{
+        audit::AuditContext,
+        auth::{authenticate_session, AuthToken},
+        constants::MAX_API_KEY_TTL_DAYS,
+    },
+    ErrorResponse,
+};
+
+const MAX_API_KEY_NAME_LENGTH: usize = 64;
+
+pub use auth_sdk::{ApiKeyResponse, CreateApiKeyRequest, CreateApiKeyResponse};
+
+impl From<&ApiKey> for ApiKeyResponse {
+    fn from(key: &ApiKey) -> Self {
+        Self {
+            id: key.id().to_string(),
+            name: key.name().to_owned(),
+            scopes: key.scopes().iter().map(|scope| scope.as_ref().to_owned()).collect(),
+            created_at: key.created_at(),
+            expires_at: key.expires_at(),
+            last_used_at: key.last_used_at(),
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api-keys",
+    request_body = CreateApiKeyRequest,
+    security(("jwt_cookie" = []), ("bearer" = [])),
+    responses(
+        (status = 201, description = "API key created; the secret is only returned once", body = CreateApiKeyResponse),
+        (status = 400, description = "Missing auth token or invalid request", body = ErrorResponse),
+        (status = 401, description = "Invalid auth token", body = ErrorResponse),
+        (status = 422, description = "Malformed request body"),
+    )
+)]
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
+    token: Result<AuthToken, AuthAPIError>,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let session = match &token {
+        Ok(token) => authenticate_session(token, &tenant, &state).await,
+        Err(_) => Err(AuthAPIError::MissingToken),
+    };
+    let (email, result) = match session {
+        Ok((email, _)) => (
+            Some(email.clone()),
+            issue_key(&state, &tenant, email, request).await,
+        ),
+        Err(e) => (None, Err(e)),
+    };
+
+    match &result {
+        Ok(response) => {
+            let detail = format!("key {}", response.key.id);
+            audit
+                .record(
+                    &state,
+                    AuditEventType::ApiKeyCreated,
+                    email.as_ref().map(AsRef::as_ref),
+                    AuditOutcome::Success,
+                    Some(&detail),
+                )
+                .await
+        }
+        Err(_) => {
+            audit
+                .record_result(
+                    &state,
+                    AuditEventType::ApiKeyCreated,
+                    email.as_ref().map(AsRef::as_ref),
+                    &result,
+                )
+                .await
+        }
+    }
+
+    result.map(|response| (StatusCode::CREATED, Json(response)))
+}
+
+async fn issue_key(
+    state: &AppState,
+    tenant: &Tenant,
+    email: Email,
+    request: CreateApiKeyRequest,
+) -> Result<CreateApiKeyResponse, AuthAPIError> {
+    let name = request.name.trim().to_owned();
+    if name.is_empty() || name.chars().count() > MAX_API_KEY_NAME_LENGTH {
+        return Err(AuthAPIError::InvalidApiKeyRequest);
+    }
+
+    if request.expires_in_days == 0 || request.expires_in_days > MAX_API_KEY_TTL_DAYS {
+        return Err(AuthAPIError::InvalidApiKeyRequest);
+    }
+
+    let scopes = request
+        .scopes
+        .into_iter()
+        .map(Role::parse)
+        .collect::<Result<BTreeSet<Role>, String>>()
+        .map_err(|_| AuthAPIError::InvalidApiKeyRequest)?;
+
+    // Users cannot hand out roles they do not hold themselves
+    let owner_roles = match state.user_store.read().await.get_user(tenant.id(), &email).await {
+        Ok(user) => user.roles().clone(),
+        Err(_) => return Err(AuthAPIError::InvalidToken),
+    };
+    if !scopes.is_subset(&owner_roles) {
+        return Err(AuthAPIError::InvalidApiKeyRequest);
+    }
+
+    let id = ApiKeyId::default();
+    let secret = ApiKeySecret::generate(&id);
+    let created_at = Utc::now();
+    let expires_at = created_at + chrono::Duration::days(request.expires_in_days.into());
+    let key = ApiKey::new(id, email, name, scopes, &secret, created_at, expires_at)
+        .with_tenant(tenant.id().clone());
+
+    if state.api_key_store.write().await.add_key(key.clone()).await.is_err() {
+        return Err(AuthAPIError::UnexpectedError);
+    }
+
+    Ok(CreateApiKeyResponse {
+        key: ApiKeyResponse::from(&key),
+        secret: secret.as_ref().to_owned(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/api-keys",
+    security(("jwt_cookie" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "The caller's API keys", body = Vec<ApiKeyResponse>),
+        (status = 400, description = "Missing auth token", body = ErrorResponse),
+        (status = 401, description = "Invalid auth token", body = ErrorResponse),
+    )
+)]
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
+    token: Result<AuthToken, AuthAPIError>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let session = match &token {
+        Ok(token) => authenticate_session(token, &tenant, &state).await,
+        Err(_) => Err(AuthAPIError::MissingToken),
+    };
+    let (email, result) = match session {
+        Ok((email, _)) => {
+            let result = match state.api_key_store.read().await.list_keys(tenant.id(), &email).await {
+                Ok(keys) => Ok(keys.iter().map(ApiKeyResponse::from).collect::<Vec<_>>()),
+                Err(_) => Err(AuthAPIError::UnexpectedError),
+            };
+            (Some(email), result)
+        }
+        Err(e) => (None, Err(e)),
+    };
+
+    audit
+        .record_result(
+            &state,
+            AuditEventType::ApiKeysListed,
+            email.as_ref().map(AsRef::as_ref),
+            &result,
+        )
+        .await;
+
+    result.map(Json)
+}
+
+// Taken by name, since the route may also have a `{tenant}` segment
+#[derive(Deserialize)]
+pub struct ApiKeyPath {
+    id: String,
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api-keys/{id}",
+    params(("id" = String, Path, description = "API key id")),
+    security(("jwt_cookie" = []), ("bearer" = [])),
+    responses(
+        (status = 204, description = "API key revoked"),
+        (status = 400, description = "Missing auth token", body = ErrorResponse),
+        (status = 401, description = "Invalid auth token", body = ErrorResponse),
+        (status = 404, description = "API key not found", body = ErrorResponse),
+    )
+)]
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
+    token: Result<AuthToken, AuthAPIError>,
+    Path(ApiKeyPath { id }): Path<ApiKeyPath>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let session = match &token {
+        Ok(token) => authenticate_session(token, &tenant, &state).await,
+        Err(_) => Err(AuthAPIError::MissingToken),
+    };
+    let (email, result) = match session {
+        Ok((email, _)) => {
+            let result = revoke_key(&state, &tenant, &email, &id).await;
+            (Some(email), result)
+        }
+        Err(e) => (None, Err(e)),
+    };
+
+    let outcome = match &result {
+        Ok(_) => AuditOutcome::Success,
+        Err(_) => AuditOutcome::Failure,
+    };
+    let detail = match &result {
+        Ok(_) => format!("key {}", id),
+        Err(e) => format!("key {}: {}", id, e.message()),
+    };
+    audit
+        .record(
+            &state,
+            AuditEventType::ApiKeyRevoked,
+            email.as_ref().map(AsRef::as_ref),
+            outcome,
+            Some(&detail),
+        )
+        .await;
+
+    result
+}
+
+async fn revoke_key(
+    state: &AppState,
+    tenant: &Tenant,
+    email: &Email,
+    id: &str,
+) -> Result<StatusCode, AuthAPIError> {
+    let id = ApiKeyId::parse(id.to_owned()).map_err(|_| AuthAPIError::ApiKeyNotFound)?;
+
+    match state.api_key_store.write().await.revoke_key(tenant.id(), email, &id).await {
+        Ok(()) => Ok(StatusCode::NO_CONTENT),
+        Err(ApiKeyStoreError::KeyNotFound) => Err(AuthAPIError::ApiKeyNotFound),
+        Err(_) => Err(AuthAPIError::UnexpectedError),
+    }
+}
@@ -0,0 +1,109 @@
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEvent, AuditEventType, AuditQuery},
+        error::AuthAPIError,
+        role::Role,
+        tenant::DEFAULT_TENANT,
+    },
+    utils::{
+        audit::{audit_email, AuditContext},
+        auth::{authenticate_user, AuthToken},
+    },
+    ErrorResponse,
+};
+
+const DEFAULT_AUDIT_QUERY_LIMIT: usize = 100;
+const MAX_AUDIT_QUERY_LIMIT: usize = 1000;
+
+// `from` and `to` are RFC 3339 timestamps; `from` is inclusive, `to` exclusive
+#[derive(Deserialize, IntoParams)]
+pub struct AuditEventsParams {
+    pub email: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+}
+
+// Lets administrators search the audit log. Queries are audited themselves.
+#[utoipa::path(
+    get,
+    path = "/admin/audit-events",
+    params(AuditEventsParams),
+    security(("jwt_cookie" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "Matching events, newest first", body = Vec<AuditEvent>),
+        (status = 400, description = "Missing auth token", body = ErrorResponse),
+        (status = 401, description = "Invalid auth token", body = ErrorResponse),
+        (status = 403, description = "Caller is not an admin of the default tenant", body = ErrorResponse),
+    )
+)]
+pub async fn list_audit_events(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    token: Result<AuthToken, AuthAPIError>,
+    Query(params): Query<AuditEventsParams>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let user = match &token {
+        Ok(token) => authenticate_user(token, &state).await,
+        Err(_) => Err(AuthAPIError::MissingToken),
+    };
+    let (email, result) = match user {
+        Ok(claims) => {
+            // The log covers every tenant, so only the deployment's own admins
+            // may read it, not those of a hosted product
+            let deployment_admin = claims.has_role(Role::ADMIN)
+                && claims.tenant.as_deref().is_none_or(|t| t == DEFAULT_TENANT);
+            let result = if deployment_admin {
+                query_events(&state, params).await
+            } else {
+                Err(AuthAPIError::Forbidden)
+            };
+            (Some(claims.sub), result)
+        }
+        Err(e) => (None, Err(e)),
+    };
+
+    audit
+        .record_result(
+            &state,
+            AuditEventType::AuditEventsQueried,
+            email.as_deref(),
+            &result,
+        )
+        .await;
+
+    result.map(Json)
+}
+
+async fn query_events(
+    state: &AppState,
+    params: AuditEventsParams,
+) -> Result<Vec<AuditEvent>, AuthAPIError> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_QUERY_LIMIT)
+        .clamp(1, MAX_AUDIT_QUERY_LIMIT);
+
+    let query = AuditQuery {
+        email: params.email.as_deref().map(audit_email),
+        from: params.from,
+        to: params.to,
+        limit,
+    };
+
+    state
+        .audit_sink
+        .query(&query)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)
+}
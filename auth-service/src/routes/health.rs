@@ -0,0 +1,113 @@
+use std::{collections::BTreeMap, future::Future, time::Duration};
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::app_state::AppState;
+
+// A dependency that does not answer within this time counts as down
+const DEPENDENCY_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct HealthResponse {
+    pub status: HealthStatus,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub checks: BTreeMap<String, DependencyHealth>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct DependencyHealth {
+    pub status: HealthStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// The process is running and able to answer requests
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    responses((status = 200, description = "The process is up", body = HealthResponse)),
+)]
+pub async fn health_live() -> impl IntoResponse {
+    Json(HealthResponse {
+        status: HealthStatus::Up,
+        checks: BTreeMap::new(),
+    })
+}
+
+// The service can do useful work: every dependency answered in time.
+// Answers 503 with the same breakdown if any of them did not.
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    responses(
+        (status = 200, description = "All dependencies answered", body = HealthResponse),
+        (status = 503, description = "At least one dependency is down or timed out", body = HealthResponse),
+    ),
+)]
+pub async fn health_ready(State(state): State<AppState>) -> impl IntoResponse {
+    let mut checks = BTreeMap::new();
+
+    checks.insert(
+        "user_store".to_owned(),
+        check(async {
+            state
+                .user_store
+                .read()
+                .await
+                .count_users()
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("{:?}", e))
+        })
+        .await,
+    );
+    checks.insert(
+        "banned_token_store".to_owned(),
+        check(async {
+            state
+                .banned_token_store
+                .read()
+                .await
+                .count_tokens()
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("{:?}", e))
+        })
+        .await,
+    );
+    checks.insert(
+        "email_client".to_owned(),
+        check(state.email_client.health_check()).await,
+    );
+
+    let healthy = checks.values().all(|check| check.status == HealthStatus::Up);
+    let (status_code, status) = if healthy {
+        (StatusCode::OK, HealthStatus::Up)
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, HealthStatus::Down)
+    };
+
+    (status_code, Json(HealthResponse { status, checks }))
+}
+
+async fn check(probe: impl Future<Output = Result<(), String>>) -> DependencyHealth {
+    let error = match tokio::time::timeout(DEPENDENCY_CHECK_TIMEOUT, probe).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e),
+        Err(_) => Some("timed out".to_owned()),
+    };
+
+    DependencyHealth {
+        status: if error.is_none() { HealthStatus::Up } else { HealthStatus::Down },
+        error,
+    }
+}
@@ -0,0 +1,154 @@
+use axum::{extract::State, http::HeaderMap, response::IntoResponse, Form, Json};
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEventType, AuditOutcome},
+        error::{AuthAPIError, OAuthError},
+    },
+    utils::{
+        audit::AuditContext,
+        auth::{authenticate_client, inspect_bearer_token, validate_service_token},
+    },
+    ErrorResponse,
+};
+
+pub use auth_sdk::{IntrospectRequest, IntrospectResponse};
+
+// `token_type` of service tokens, which belong to clients rather than users
+pub const SERVICE_TOKEN_TYPE: &str = "service_token";
+
+// Where a token is looked for
+#[derive(Clone, Copy)]
+enum Lookup {
+    // Session tokens and API keys, told apart by the API key prefix
+    UserToken,
+    ServiceToken,
+}
+
+// RFC 7662 token introspection: a registered client describes somebody else's
+// token. Session tokens, API keys and service tokens are recognized, whatever
+// `token_type_hint` says; the hint only picks where to look first.
+#[utoipa::path(
+    post,
+    path = "/introspect",
+    request_body(content = IntrospectRequest, content_type = "application/x-www-form-urlencoded"),
+    security(("client_basic" = [])),
+    responses(
+        (status = 200, description = "Description of the token; `active` is false if it is not valid", body = IntrospectResponse),
+        (status = 400, description = "invalid_request", body = ErrorResponse),
+        (status = 401, description = "invalid_client", body = ErrorResponse),
+    )
+)]
+pub async fn introspect_token(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    headers: HeaderMap,
+    Form(request): Form<IntrospectRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let result = describe_for_client(&state, &headers, request).await;
+
+    // The caller is a client, so it goes into `detail`; the email is the token's owner
+    let (email, outcome, detail) = match &result {
+        Ok((client_id, response)) if response.active => (
+            response.sub.as_deref(),
+            AuditOutcome::Success,
+            format!("client {}", client_id),
+        ),
+        Ok((client_id, _)) => (
+            None,
+            AuditOutcome::Failure,
+            format!("client {}: inactive token", client_id),
+        ),
+        Err(e) => (None, AuditOutcome::Failure, e.code().to_owned()),
+    };
+    audit
+        .record(&state, AuditEventType::Introspect, email, outcome, Some(&detail))
+        .await;
+
+    result.map(|(_, response)| Json(response))
+}
+
+async fn describe_for_client(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: IntrospectRequest,
+) -> Result<(String, IntrospectResponse), OAuthError> {
+    let client = authenticate_client(
+        headers,
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        state.client_store.clone(),
+    )
+    .await?;
+
+    let token = request
+        .token
+        .filter(|token| !token.is_empty())
+        .ok_or(OAuthError::InvalidRequest)?;
+    let response = describe(state, &token, request.token_type_hint.as_deref())
+        .await
+        .map_err(|_| OAuthError::ServerError)?;
+
+    Ok((client.id().as_ref().to_owned(), response))
+}
+
+// Only an unexpected failure is an error; whatever does not validate, e.g. a
+// banned or expired token, is merely inactive
+async fn describe(
+    state: &AppState,
+    token: &str,
+    token_type_hint: Option<&str>,
+) -> Result<IntrospectResponse, AuthAPIError> {
+    let lookups = match token_type_hint {
+        Some(SERVICE_TOKEN_TYPE) => [Lookup::ServiceToken, Lookup::UserToken],
+        _ => [Lookup::UserToken, Lookup::ServiceToken],
+    };
+
+    for lookup in lookups {
+        let response = match lookup {
+            Lookup::UserToken => describe_user_token(state, token).await?,
+            Lookup::ServiceToken => describe_service_token(token),
+        };
+        if let Some(response) = response {
+            return Ok(response);
+        }
+    }
+
+    Ok(IntrospectResponse::default())
+}
+
+async fn describe_user_token(
+    state: &AppState,
+    token: &str,
+) -> Result<Option<IntrospectResponse>, AuthAPIError> {
+    match inspect_bearer_token(token, state).await {
+        Ok((claims, token_type)) => Ok(Some(IntrospectResponse {
+            active: true,
+            sub: Some(claims.sub),
+            exp: Some(claims.exp),
+            iat: Some(claims.iat),
+            scope: Some(claims.roles.join(" ")),
+            client_id: None,
+            token_type: Some(token_type.as_str().to_owned()),
+            iss: claims.iss,
+        })),
+        Err(AuthAPIError::UnexpectedError) => Err(AuthAPIError::UnexpectedError),
+        Err(_) => Ok(None),
+    }
+}
+
+fn describe_service_token(token: &str) -> Option<IntrospectResponse> {
+    let claims = validate_service_token(token).ok()?;
+
+    Some(IntrospectResponse {
+        active: true,
+        client_id: Some(claims.sub.clone()),
+        sub: Some(claims.sub),
+        exp: Some(claims.exp),
+        iat: Some(claims.iat),
+        scope: Some(claims.scope),
+        token_type: Some(SERVICE_TOKEN_TYPE.to_owned()),
+        iss: None,
+    })
+}
@@ -0,0 +1,24 @@
+use axum::{response::IntoResponse, Json};
+
+use crate::domain::tenant::Tenant;
+
+pub use auth_sdk::{JsonWebKey, JsonWebKeySet};
+
+// The public keys that verify the tenant's auth tokens, for services that
+// verify them locally. Empty while the tenant signs its tokens with a secret.
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses(
+        (status = 200, description = "Key set of the tenant", body = JsonWebKeySet),
+    )
+)]
+pub async fn jwks(tenant: Tenant) -> impl IntoResponse {
+    let keys = tenant
+        .signing_key()
+        .map(|signing_key| signing_key.jwk(tenant.id().as_ref()))
+        .into_iter()
+        .collect();
+
+    Json(JsonWebKeySet { keys })
+}
@@ -1,5 +1,288 @@
-use axum::{http, response::IntoResponse};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
 
-pub async fn login() -> impl IntoResponse {
-    http::StatusCode::OK.into_response()
-}
\ No newline at end of file
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEventType, AuditOutcome},
+        data_stores::{LoginAttemptId, TwoFACode, UserStoreError},
+        email::Email,
+        error::AuthAPIError,
+        ldap_directory::LdapDirectory,
+        password::Password,
+        tenant::Tenant,
+        user::User,
+    },
+    utils::{
+        audit::{audit_email, AuditContext},
+        auth::generate_auth_cookie,
+        ldap::LdapLoginError,
+    },
+    ErrorResponse,
+};
+
+pub use auth_sdk::{LoginRequest, LoginResponse, TwoFactorAuthResponse};
+
+#[utoipa::path(
+    post,
+    path = "/login",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Logged in; the `jwt` cookie is set"),
+        (status = 206, description = "2FA required; a code was emailed", body = TwoFactorAuthResponse),
+        (status = 400, description = "Invalid email or password", body = ErrorResponse),
+        (status = 401, description = "Incorrect credentials", body = ErrorResponse),
+        (status = 422, description = "Malformed request body"),
+        (status = 503, description = "The LDAP directory of the email's domain is unavailable", body = ErrorResponse),
+    )
+)]
+pub async fn login(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
+    jar: CookieJar,
+    Json(request): Json<LoginRequest>,
+) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+    let email = audit_email(&request.email);
+    let (jar, directory, result) = authenticate(&state, &audit, &tenant, jar, request).await;
+
+    match &result {
+        Ok((StatusCode::PARTIAL_CONTENT, _)) => state.metrics.record_login("2fa_required"),
+        Ok(_) => state.metrics.record_login("success"),
+        Err(_) => state.metrics.record_login("failure"),
+    }
+
+    let outcome = match &result {
+        Ok(_) => AuditOutcome::Success,
+        Err(_) => AuditOutcome::Failure,
+    };
+    let detail = match (&result, directory) {
+        (Ok((StatusCode::PARTIAL_CONTENT, _)), Some(directory)) => {
+            Some(format!("{}, 2FA required", directory))
+        }
+        (Ok((StatusCode::PARTIAL_CONTENT, _)), None) => Some("2FA required".to_owned()),
+        (Err(e), None) => Some(e.message().to_owned()),
+        (_, directory) => directory,
+    };
+    audit
+        .record(&state, AuditEventType::Login, Some(&email), outcome, detail.as_deref())
+        .await;
+
+    (jar, result)
+}
+
+// Also returns what to put in the audit log about the LDAP directory the
+// password was checked against, if any
+async fn authenticate(
+    state: &AppState,
+    audit: &AuditContext,
+    tenant: &Tenant,
+    jar: CookieJar,
+    request: LoginRequest,
+) -> (
+    CookieJar,
+    Option<String>,
+    Result<(StatusCode, Json<LoginResponse>), AuthAPIError>,
+) {
+    let (email, password) = match (
+        Email::parse(request.email),
+        Password::parse(request.password),
+    ) {
+        (Ok(email), Ok(password)) => (email, password),
+        _ => return (jar, None, Err(AuthAPIError::InvalidCredentials)),
+    };
+
+    // The directory alone decides on the passwords of its domains
+    if let Some(directory) = state.ldap_config.directory_for(tenant.id(), &email) {
+        let (jar, result) =
+            match directory_user(state, audit, tenant, directory, &email, &password).await {
+                Ok(user) => continue_login(&user, tenant, state, jar).await,
+                Err((reason, e)) => {
+                    let detail = format!("Directory {}: {}", directory.id(), reason);
+                    return (jar, Some(detail), Err(e));
+                }
+            };
+        let detail = match &result {
+            Ok(_) => format!("Directory {}", directory.id()),
+            Err(e) => format!("Directory {}: {}", directory.id(), e.message()),
+        };
+        return (jar, Some(detail), result);
+    }
+
+    let user_store = state.user_store.read().await;
+
+    if user_store
+        .validate_user(tenant.id(), &email, &password)
+        .await
+        .is_err()
+    {
+        return (jar, None, Err(AuthAPIError::IncorrectCredentials));
+    }
+
+    let user = match user_store.get_user(tenant.id(), &email).await {
+        Ok(user) => user.clone(),
+        Err(_) => return (jar, None, Err(AuthAPIError::IncorrectCredentials)),
+    };
+
+    // Release the user store before touching any other store
+    drop(user_store);
+
+    let (jar, result) = continue_login(&user, tenant, state, jar).await;
+    (jar, None, result)
+}
+
+// Binds to the directory as the user, then returns the local user, created on
+// their first login. If the directory maps groups to roles, the user's roles
+// follow their groups on every login. On failure, also returns why.
+async fn directory_user(
+    state: &AppState,
+    audit: &AuditContext,
+    tenant: &Tenant,
+    directory: &LdapDirectory,
+    email: &Email,
+    password: &Password,
+) -> Result<User, (String, AuthAPIError)> {
+    let entry = state
+        .ldap_config
+        .authenticate(directory, email, password)
+        .await
+        .map_err(|e| match e {
+            LdapLoginError::IncorrectCredentials => {
+                let e = AuthAPIError::IncorrectCredentials;
+                (e.message().to_owned(), e)
+            }
+            LdapLoginError::Unavailable(reason) => (reason, AuthAPIError::DirectoryUnavailable),
+        })?;
+    let roles = directory.roles_for(entry.groups.iter().map(String::as_str));
+    let unexpected = || {
+        let e = AuthAPIError::UnexpectedError;
+        (e.message().to_owned(), e)
+    };
+
+    let mut user_store = state.user_store.write().await;
+    match user_store.get_user(tenant.id(), email).await {
+        Ok(user) if !directory.maps_groups() || user.roles() == &roles => Ok(user.clone()),
+        Ok(user) => {
+            let user = user.clone().with_roles(roles.clone());
+            let result = user_store.set_roles(tenant.id(), email, roles).await;
+            let roles = user
+                .roles()
+                .iter()
+                .map(|role| role.as_ref())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let (outcome, detail) = match &result {
+                Ok(()) => (
+                    AuditOutcome::Success,
+                    format!("Directory {}: {}", directory.id(), roles),
+                ),
+                Err(_) => (
+                    AuditOutcome::Failure,
+                    AuthAPIError::UnexpectedError.message().to_owned(),
+                ),
+            };
+            audit
+                .record(state, AuditEventType::RolesChanged, Some(email.as_ref()), outcome, Some(&detail))
+                .await;
+            result.map(|()| user).map_err(|_| unexpected())
+        }
+        Err(UserStoreError::UserNotFound) => {
+            let user = User::from_directory(email.clone())
+                .with_tenant(tenant.id().clone())
+                .with_roles(roles);
+            let result = user_store.add_user(user.clone()).await;
+            let (outcome, detail) = match &result {
+                Ok(()) => (AuditOutcome::Success, format!("Directory {}", directory.id())),
+                Err(_) => (
+                    AuditOutcome::Failure,
+                    AuthAPIError::UnexpectedError.message().to_owned(),
+                ),
+            };
+            audit
+                .record(state, AuditEventType::Signup, Some(email.as_ref()), outcome, Some(&detail))
+                .await;
+            result.map(|()| user).map_err(|_| unexpected())
+        }
+        Err(_) => Err(unexpected()),
+    }
+}
+
+// Once the user proved who they are, either log them in or, if they or their
+// tenant require 2FA, email them a code
+pub(crate) async fn continue_login(
+    user: &User,
+    tenant: &Tenant,
+    state: &AppState,
+    jar: CookieJar,
+) -> (
+    CookieJar,
+    Result<(StatusCode, Json<LoginResponse>), AuthAPIError>,
+) {
+    match user.requires_2fa() || tenant.requires_2fa() {
+        true => handle_2fa(user, state, jar).await,
+        false => handle_no_2fa(user, tenant, state, jar).await,
+    }
+}
+
+async fn handle_2fa(
+    user: &User,
+    state: &AppState,
+    jar: CookieJar,
+) -> (
+    CookieJar,
+    Result<(StatusCode, Json<LoginResponse>), AuthAPIError>,
+) {
+    let login_attempt_id = LoginAttemptId::default();
+    let two_fa_code = TwoFACode::default();
+
+    if state
+        .two_fa_code_store
+        .write()
+        .await
+        .add_code(
+            user.tenant().clone(),
+            user.email().clone(),
+            login_attempt_id.clone(),
+            two_fa_code.clone(),
+        )
+        .await
+        .is_err()
+    {
+        return (jar, Err(AuthAPIError::UnexpectedError));
+    }
+
+    if state
+        .email_client
+        .send_email(user.email(), "2FA Code", two_fa_code.as_ref())
+        .await
+        .is_err()
+    {
+        return (jar, Err(AuthAPIError::UnexpectedError));
+    }
+
+    let response = Json(LoginResponse::TwoFactorAuth(TwoFactorAuthResponse {
+        message: "2FA required".to_owned(),
+        login_attempt_id: login_attempt_id.as_ref().to_owned(),
+    }));
+
+    (jar, Ok((StatusCode::PARTIAL_CONTENT, response)))
+}
+
+async fn handle_no_2fa(
+    user: &User,
+    tenant: &Tenant,
+    state: &AppState,
+    jar: CookieJar,
+) -> (
+    CookieJar,
+    Result<(StatusCode, Json<LoginResponse>), AuthAPIError>,
+) {
+    let auth_cookie = match generate_auth_cookie(user, tenant, &state.cookie_settings) {
+        Ok(cookie) => cookie,
+        Err(_) => return (jar, Err(AuthAPIError::UnexpectedError)),
+    };
+
+    let updated_jar = jar.add(auth_cookie);
+
+    (updated_jar, Ok((StatusCode::OK, Json(LoginResponse::RegularAuth))))
+}
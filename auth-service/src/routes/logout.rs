@@ -1,5 +1,71 @@
-use axum::{http, response::IntoResponse};
+use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use axum_extra::extract::CookieJar;
 
-pub async fn logout() -> impl IntoResponse {
-    http::StatusCode::OK.into_response()
-}
\ No newline at end of file
+use crate::{
+    app_state::AppState,
+    domain::{audit::AuditEventType, error::AuthAPIError},
+    utils::{
+        audit::AuditContext,
+        auth::{validate_token, AuthToken},
+    },
+    ErrorResponse,
+};
+
+#[utoipa::path(
+    post,
+    path = "/logout",
+    security(("jwt_cookie" = []), ("bearer" = [])),
+    responses(
+        (status = 200, description = "Logged out; the token is banned and the cookie removed"),
+        (status = 400, description = "Missing auth token", body = ErrorResponse),
+        (status = 401, description = "Invalid auth token", body = ErrorResponse),
+    )
+)]
+pub async fn logout(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    jar: CookieJar,
+    token: Result<AuthToken, AuthAPIError>,
+) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+    let (jar, email, result) = end_session(&state, jar, token).await;
+    audit
+        .record_result(&state, AuditEventType::Logout, email.as_deref(), &result)
+        .await;
+    (jar, result)
+}
+
+// Returns the email of the logged out user, if the token was valid
+async fn end_session(
+    state: &AppState,
+    jar: CookieJar,
+    token: Result<AuthToken, AuthAPIError>,
+) -> (CookieJar, Option<String>, Result<StatusCode, AuthAPIError>) {
+    // The token comes from the auth cookie or an `Authorization: Bearer` header
+    let token = match token {
+        Ok(token) => token.value,
+        Err(e) => return (jar, None, Err(e)),
+    };
+
+    let claims = match validate_token(&token, &state.tenants, state.banned_token_store.clone())
+        .await
+    {
+        Ok(claims) => claims,
+        Err(_) => return (jar, None, Err(AuthAPIError::InvalidToken)),
+    };
+
+    if state
+        .banned_token_store
+        .write()
+        .await
+        .add_token(token.clone())
+        .await
+        .is_err()
+    {
+        return (jar, Some(claims.sub), Err(AuthAPIError::UnexpectedError));
+    }
+    state.revocation_notifier.notify(&token);
+
+    let jar = jar.remove(state.cookie_settings.removal_cookie());
+
+    (jar, Some(claims.sub), Ok(StatusCode::OK))
+}
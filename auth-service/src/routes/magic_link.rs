@@ -0,0 +1,216 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEventType, AuditOutcome},
+        email::Email,
+        error::AuthAPIError,
+        tenant::Tenant,
+    },
+    utils::{
+        audit::{audit_email, AuditContext},
+        magic_link::{generate_magic_link_token, validate_magic_link_token},
+    },
+    ErrorResponse,
+};
+
+use super::login::continue_login;
+
+pub use auth_sdk::{MagicLinkRequest, MagicLinkResponse, VerifyMagicLinkRequest};
+
+use auth_sdk::{LoginResponse, TwoFactorAuthResponse};
+
+const MAGIC_LINK_SENT_MESSAGE: &str = "If an account exists for this email, a login link was sent to it";
+
+// Emails a one-time login link. The response is the same, and as fast, whether
+// or not the account exists, and even if the email could not be sent, so it
+// cannot be used to find out who has an account; only the audit log tells.
+#[utoipa::path(
+    post,
+    path = "/login/magic-link",
+    request_body = MagicLinkRequest,
+    responses(
+        (status = 202, description = "A link was emailed if the account exists", body = MagicLinkResponse),
+        (status = 400, description = "Invalid email", body = ErrorResponse),
+        (status = 422, description = "Malformed request body"),
+    )
+)]
+pub async fn request_magic_link(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
+    Json(request): Json<MagicLinkRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = audit_email(&request.email);
+    let result = match Email::parse(request.email) {
+        Ok(parsed) => Ok(check_account(&state, &tenant, &parsed).await.map(|()| parsed)),
+        Err(_) => Err(AuthAPIError::InvalidCredentials),
+    };
+
+    match &result {
+        // Sending takes a while, so it is done in the background, which records
+        // how it went. Accounts that get no link are recorded there too, so that
+        // no account is answered more slowly than another.
+        Ok(account) => {
+            tokio::spawn(send_link(state.clone(), audit, tenant, email, account.clone()));
+        }
+        Err(_) => {
+            audit
+                .record_result(&state, AuditEventType::MagicLinkRequested, Some(&email), &result)
+                .await
+        }
+    }
+
+    result.map(|_| {
+        let response = Json(MagicLinkResponse {
+            message: MAGIC_LINK_SENT_MESSAGE.to_owned(),
+        });
+        (StatusCode::ACCEPTED, response)
+    })
+}
+
+// On failure, says why no link is sent, for the audit log only
+async fn check_account(state: &AppState, tenant: &Tenant, email: &Email) -> Result<(), &'static str> {
+    // Directory users log in with their directory password, which a link would bypass
+    if state.ldap_config.directory_for(tenant.id(), email).is_some() {
+        return Err("Managed by a directory");
+    }
+    if state.user_store.read().await.get_user(tenant.id(), email).await.is_err() {
+        return Err("Unknown account");
+    }
+
+    Ok(())
+}
+
+// `account` is the checked account, or why it gets no link
+async fn send_link(
+    state: AppState,
+    audit: AuditContext,
+    tenant: Tenant,
+    email: String,
+    account: Result<Email, &'static str>,
+) {
+    let result = match &account {
+        Ok(account) => email_link(&state, &tenant, account).await,
+        Err(reason) => Err(*reason),
+    };
+
+    let (outcome, detail) = match result {
+        Ok(()) => (AuditOutcome::Success, None),
+        Err(reason) => (AuditOutcome::Failure, Some(reason)),
+    };
+    audit
+        .record(&state, AuditEventType::MagicLinkRequested, Some(&email), outcome, detail)
+        .await;
+}
+
+async fn email_link(state: &AppState, tenant: &Tenant, email: &Email) -> Result<(), &'static str> {
+    let token = generate_magic_link_token(email, tenant).map_err(|_| "Failed to generate link")?;
+    let link = state.magic_link_config.link(tenant.id(), &token);
+
+    state
+        .email_client
+        .send_email(email, "Your login link", &link)
+        .await
+        .map_err(|_| "Failed to send email")
+}
+
+// Completes a login started with an emailed link, like `/login` would with the
+// password: the auth cookie is set, unless the account requires 2FA, in which
+// case a code is emailed and the login continues at `/verify-2fa`.
+#[utoipa::path(
+    post,
+    path = "/login/magic-link/verify",
+    request_body = VerifyMagicLinkRequest,
+    responses(
+        (status = 200, description = "Logged in; the `jwt` cookie is set"),
+        (status = 206, description = "2FA required; a code was emailed", body = TwoFactorAuthResponse),
+        (status = 401, description = "Invalid, expired or already used link", body = ErrorResponse),
+        (status = 403, description = "The email is managed by a directory", body = ErrorResponse),
+        (status = 422, description = "Malformed request body"),
+    )
+)]
+pub async fn verify_magic_link(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
+    jar: CookieJar,
+    Json(request): Json<VerifyMagicLinkRequest>,
+) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+    let (jar, email, result) = complete_login(&state, &tenant, jar, request.token).await;
+
+    let (metric, outcome, detail) = match &result {
+        Ok((StatusCode::PARTIAL_CONTENT, _)) => {
+            ("2fa_required", AuditOutcome::Success, "Magic link, 2FA required")
+        }
+        Ok(_) => ("success", AuditOutcome::Success, "Magic link"),
+        Err(e) => ("failure", AuditOutcome::Failure, e.message()),
+    };
+    state.metrics.record_login(metric);
+    audit
+        .record(
+            &state,
+            AuditEventType::Login,
+            email.as_deref(),
+            outcome,
+            Some(detail),
+        )
+        .await;
+
+    (jar, result)
+}
+
+// Returns the email the link was sent to, if the link was valid
+async fn complete_login(
+    state: &AppState,
+    tenant: &Tenant,
+    jar: CookieJar,
+    token: String,
+) -> (
+    CookieJar,
+    Option<String>,
+    Result<(StatusCode, Json<LoginResponse>), AuthAPIError>,
+) {
+    let claims = match validate_magic_link_token(&token, tenant) {
+        Ok(claims) => claims,
+        Err(_) => return (jar, None, Err(AuthAPIError::InvalidToken)),
+    };
+
+    // A link can only be used once. Checking and banning it under one lock
+    // keeps two concurrent requests from both using it.
+    {
+        let mut banned_token_store = state.banned_token_store.write().await;
+        match banned_token_store.contains_token(&token).await {
+            Ok(false) => {}
+            _ => return (jar, Some(claims.sub), Err(AuthAPIError::InvalidToken)),
+        }
+        if banned_token_store.add_token(token).await.is_err() {
+            return (jar, Some(claims.sub), Err(AuthAPIError::UnexpectedError));
+        }
+    }
+
+    let email = match Email::parse(claims.sub.clone()) {
+        Ok(email) => email,
+        Err(_) => return (jar, Some(claims.sub), Err(AuthAPIError::InvalidToken)),
+    };
+    // The domain may have moved to a directory since the link was sent
+    if state.ldap_config.directory_for(tenant.id(), &email).is_some() {
+        return (jar, Some(claims.sub), Err(AuthAPIError::ManagedByDirectory));
+    }
+    let user = state
+        .user_store
+        .read()
+        .await
+        .get_user(tenant.id(), &email)
+        .await
+        .cloned();
+    let user = match user {
+        Ok(user) => user,
+        Err(_) => return (jar, Some(claims.sub), Err(AuthAPIError::InvalidToken)),
+    };
+
+    let (jar, result) = continue_login(&user, tenant, state, jar).await;
+    (jar, Some(claims.sub), result)
+}
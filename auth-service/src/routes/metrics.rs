@@ -0,0 +1,29 @@
+use axum::{extract::State, http::header, response::IntoResponse};
+
+use crate::app_state::AppState;
+
+// Prometheus scrape endpoint. Store sizes are sampled at scrape time.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    responses((status = 200, description = "Metrics in the Prometheus text format", body = String, content_type = "text/plain")),
+)]
+pub async fn metrics(State(state): State<AppState>) -> impl IntoResponse {
+    if let Ok(count) = state.user_store.read().await.count_users().await {
+        state.metrics.set_store_size("users", count);
+    }
+    if let Ok(count) = state.banned_token_store.read().await.count_tokens().await {
+        state.metrics.set_store_size("banned_tokens", count);
+    }
+    if let Ok(count) = state.two_fa_code_store.read().await.count_codes().await {
+        state.metrics.set_store_size("two_fa_codes", count);
+    }
+    if let Ok(count) = state.api_key_store.read().await.count_keys().await {
+        state.metrics.set_store_size("api_keys", count);
+    }
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(),
+    )
+}
@@ -1,12 +1,32 @@
+mod api_keys;
+mod audit_events;
+mod health;
+mod introspect;
+mod jwks;
 mod login;
 mod logout;
+mod magic_link;
+mod metrics;
+mod oidc;
+mod revoke;
 mod signup;
+mod token;
 mod verify_2fa;
 mod verify_token;
 
 // re-export items from sub-modules
+pub use api_keys::*;
+pub use audit_events::*;
+pub use health::*;
+pub use introspect::*;
+pub use jwks::*;
 pub use login::*;
 pub use logout::*;
+pub use magic_link::*;
+pub use metrics::*;
+pub use oidc::*;
+pub use revoke::*;
 pub use signup::*;
+pub use token::*;
 pub use verify_2fa::*;
-pub use verify_token::*;
\ No newline at end of file
+pub use verify_token::*;
@@ -0,0 +1,281 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect},
+    Json,
+};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEventType, AuditOutcome},
+        data_stores::UserStoreError,
+        email::Email,
+        error::AuthAPIError,
+        identity_provider::{ExternalIdentity, IdentityProvider, ProviderId},
+        tenant::Tenant,
+        user::User,
+    },
+    utils::{
+        audit::AuditContext,
+        constants::{OIDC_LOGIN_COOKIE_NAME, OIDC_LOGIN_TTL_SECONDS},
+        oidc::{validate_login_state, UpstreamIdentity},
+    },
+    ErrorResponse,
+};
+
+use super::login::continue_login;
+
+pub use auth_sdk::{IdentityProviderResponse, OidcCallbackRequest};
+
+use auth_sdk::{LoginResponse, TwoFactorAuthResponse};
+
+// The identity providers the login page offers
+#[utoipa::path(
+    get,
+    path = "/login/oidc",
+    responses(
+        (status = 200, description = "Configured identity providers", body = Vec<IdentityProviderResponse>),
+    )
+)]
+pub async fn list_identity_providers(State(state): State<AppState>) -> impl IntoResponse {
+    let providers: Vec<IdentityProviderResponse> = state
+        .oidc_config
+        .providers()
+        .map(|provider| IdentityProviderResponse {
+            id: provider.id().as_ref().to_owned(),
+            name: provider.name().to_owned(),
+        })
+        .collect();
+
+    Json(providers)
+}
+
+// Taken by name, since the route may also have a `{tenant}` segment
+#[derive(Deserialize)]
+pub struct ProviderPath {
+    provider: String,
+}
+
+// Starts a login at an upstream identity provider: the browser is sent there
+// and comes back to the login page, which completes the login at
+// `/login/oidc/callback`.
+#[utoipa::path(
+    get,
+    path = "/login/oidc/{provider}",
+    params(("provider" = String, Path, description = "Identity provider id")),
+    responses(
+        (status = 303, description = "Redirect to the identity provider; the `oidc_login` cookie is set"),
+        (status = 404, description = "Unknown identity provider", body = ErrorResponse),
+    )
+)]
+pub async fn start_oidc_login(
+    State(state): State<AppState>,
+    tenant: Tenant,
+    jar: CookieJar,
+    Path(ProviderPath { provider }): Path<ProviderPath>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let provider = ProviderId::parse(provider)
+        .ok()
+        .and_then(|id| state.oidc_config.provider(&id))
+        .ok_or(AuthAPIError::IdentityProviderNotFound)?;
+
+    let (url, login_state) = state
+        .oidc_config
+        .start_login(provider, &tenant)
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+    let cookie = state.cookie_settings.companion_cookie(
+        OIDC_LOGIN_COOKIE_NAME,
+        login_state,
+        OIDC_LOGIN_TTL_SECONDS,
+    );
+
+    Ok((jar.add(cookie), Redirect::to(&url)))
+}
+
+// Completes a login at an identity provider like `/login` would with the
+// password. The upstream account logs in to the user it was linked to before;
+// otherwise it is linked to the user with the same email, or a user is created
+// for it, provided the provider verified the email.
+#[utoipa::path(
+    post,
+    path = "/login/oidc/callback",
+    request_body = OidcCallbackRequest,
+    responses(
+        (status = 200, description = "Logged in; the `jwt` cookie is set"),
+        (status = 206, description = "2FA required; a code was emailed", body = TwoFactorAuthResponse),
+        (status = 401, description = "Missing, expired or mismatched login state", body = ErrorResponse),
+        (status = 403, description = "The identity provider did not verify the email, or it is managed by a directory", body = ErrorResponse),
+        (status = 404, description = "Unknown identity provider", body = ErrorResponse),
+        (status = 502, description = "The identity provider failed or could not be trusted", body = ErrorResponse),
+        (status = 422, description = "Malformed request body"),
+    )
+)]
+pub async fn complete_oidc_login(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
+    jar: CookieJar,
+    Json(request): Json<OidcCallbackRequest>,
+) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+    // The login state is good for one attempt, whatever its outcome
+    let login_state = jar.get(OIDC_LOGIN_COOKIE_NAME).map(|cookie| cookie.value().to_owned());
+    let jar = jar.remove(state.cookie_settings.companion_removal_cookie(OIDC_LOGIN_COOKIE_NAME));
+
+    let (jar, email, detail, result) =
+        complete_login(&state, &audit, &tenant, jar, login_state, request).await;
+
+    let (metric, outcome) = match &result {
+        Ok((StatusCode::PARTIAL_CONTENT, _)) => ("2fa_required", AuditOutcome::Success),
+        Ok(_) => ("success", AuditOutcome::Success),
+        Err(_) => ("failure", AuditOutcome::Failure),
+    };
+    state.metrics.record_login(metric);
+    audit
+        .record(
+            &state,
+            AuditEventType::Login,
+            email.as_deref(),
+            outcome,
+            Some(&detail),
+        )
+        .await;
+
+    (jar, result)
+}
+
+// Returns the email of the user, once known, and what to put in the audit log
+async fn complete_login(
+    state: &AppState,
+    audit: &AuditContext,
+    tenant: &Tenant,
+    jar: CookieJar,
+    login_state: Option<String>,
+    request: OidcCallbackRequest,
+) -> (
+    CookieJar,
+    Option<String>,
+    String,
+    Result<(StatusCode, Json<LoginResponse>), AuthAPIError>,
+) {
+    let login = match login_state.map(|token| validate_login_state(&token, tenant)) {
+        Some(Ok(login)) if login.state == request.state => login,
+        _ => {
+            let detail = "Missing or mismatched login state".to_owned();
+            return (jar, None, detail, Err(AuthAPIError::InvalidToken));
+        }
+    };
+
+    let provider = match ProviderId::parse(login.provider.clone())
+        .ok()
+        .and_then(|id| state.oidc_config.provider(&id))
+    {
+        Some(provider) => provider,
+        None => {
+            let detail = format!("Identity provider {} is gone", login.provider);
+            return (jar, None, detail, Err(AuthAPIError::IdentityProviderNotFound));
+        }
+    };
+
+    let upstream = match state
+        .oidc_config
+        .fetch_identity(provider, tenant.id(), &login, &request.code)
+        .await
+    {
+        Ok(upstream) => upstream,
+        Err(reason) => {
+            let detail = format!("Identity provider {}: {}", provider.id(), reason);
+            return (jar, None, detail, Err(AuthAPIError::IdentityProviderFailed));
+        }
+    };
+
+    let user = match find_or_create_user(state, audit, tenant, provider, upstream).await {
+        Ok(user) => user,
+        Err((email, e)) => {
+            let detail = format!("Identity provider {}: {}", provider.id(), e.message());
+            return (jar, email, detail, Err(e));
+        }
+    };
+
+    let (jar, result) = continue_login(&user, tenant, state, jar).await;
+    let detail = match &result {
+        Ok((StatusCode::PARTIAL_CONTENT, _)) => {
+            format!("Identity provider {}, 2FA required", provider.id())
+        }
+        Ok(_) => format!("Identity provider {}", provider.id()),
+        Err(e) => format!("Identity provider {}: {}", provider.id(), e.message()),
+    };
+    (jar, Some(user.email().as_ref().to_owned()), detail, result)
+}
+
+// On failure, also returns the email the provider gave, if any
+async fn find_or_create_user(
+    state: &AppState,
+    audit: &AuditContext,
+    tenant: &Tenant,
+    provider: &IdentityProvider,
+    upstream: UpstreamIdentity,
+) -> Result<User, (Option<String>, AuthAPIError)> {
+    let identity = ExternalIdentity::new(provider.id().clone(), upstream.subject);
+
+    // Checking and changing the store under one lock keeps two concurrent
+    // first logins from creating two users
+    let mut user_store = state.user_store.write().await;
+    if let Ok(user) = user_store.get_user_by_identity(tenant.id(), &identity).await {
+        // Linked before the domain moved to a directory, which now has the say
+        if state.ldap_config.directory_for(tenant.id(), user.email()).is_some() {
+            let email = user.email().as_ref().to_owned();
+            return Err((Some(email), AuthAPIError::ManagedByDirectory));
+        }
+        return Ok(user.clone());
+    }
+
+    // An unverified email could be anyone's, so it neither creates an account
+    // nor takes over an existing one
+    let email = match (upstream.email, upstream.email_verified) {
+        (Some(email), true) => match Email::parse(email.clone()) {
+            Ok(email) => email,
+            Err(_) => return Err((Some(email), AuthAPIError::IdentityProviderFailed)),
+        },
+        (email, _) => return Err((email, AuthAPIError::UnverifiedEmail)),
+    };
+    let audit_email = email.as_ref().to_owned();
+
+    // Directory users log in with their directory password; a provider vouching
+    // for the email must not create or take over their account
+    if state.ldap_config.directory_for(tenant.id(), &email).is_some() {
+        return Err((Some(audit_email), AuthAPIError::ManagedByDirectory));
+    }
+
+    let (event_type, result) = match user_store.get_user(tenant.id(), &email).await {
+        Ok(_) => (
+            AuditEventType::IdentityLinked,
+            user_store.link_identity(tenant.id(), &email, identity).await,
+        ),
+        Err(UserStoreError::UserNotFound) => {
+            let user = User::from_identity(email.clone(), identity).with_tenant(tenant.id().clone());
+            (AuditEventType::Signup, user_store.add_user(user).await)
+        }
+        Err(_) => return Err((Some(audit_email), AuthAPIError::UnexpectedError)),
+    };
+    let result = result.map_err(|_| AuthAPIError::UnexpectedError);
+
+    let (outcome, detail) = match &result {
+        Ok(()) => (AuditOutcome::Success, format!("Identity provider {}", provider.id())),
+        Err(e) => (AuditOutcome::Failure, e.message().to_owned()),
+    };
+    audit
+        .record(state, event_type, Some(&audit_email), outcome, Some(&detail))
+        .await;
+
+    match result {
+        Ok(()) => user_store
+            .get_user(tenant.id(), &email)
+            .await
+            .cloned()
+            .map_err(|_| (Some(audit_email), AuthAPIError::UnexpectedError)),
+        Err(e) => Err((Some(audit_email), e)),
+    }
+}
@@ -0,0 +1,159 @@
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Form,
+};
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEventType, AuditOutcome},
+        error::{AuthAPIError, OAuthError},
+        role::Role,
+    },
+    utils::{
+        audit::AuditContext,
+        auth::{authenticate_client, revoke_token, validate_service_token},
+    },
+    ErrorResponse,
+};
+
+pub use auth_sdk::RevokeRequest;
+
+// RFC 7009 token revocation, for OAuth clients and scripts rather than browsers.
+// Session tokens and API keys are revoked by value. An invalid token is answered
+// like a revoked one, so the endpoint does not tell which tokens exist.
+#[utoipa::path(
+    post,
+    path = "/revoke",
+    request_body(content = RevokeRequest, content_type = "application/x-www-form-urlencoded"),
+    security(("client_basic" = [])),
+    responses(
+        (status = 200, description = "The token is revoked, or was not valid or not the client's to revoke"),
+        (status = 400, description = "invalid_request or unsupported_token_type", body = ErrorResponse),
+        (status = 401, description = "invalid_client", body = ErrorResponse),
+    )
+)]
+pub async fn revoke(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    headers: HeaderMap,
+    Form(request): Form<RevokeRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let result = revoke_for_client(&state, &headers, request).await;
+
+    match &result {
+        Ok((client_id, revocation)) => {
+            record_revocation(&state, &audit, client_id, revocation).await
+        }
+        Err(e) => {
+            audit
+                .record(
+                    &state,
+                    AuditEventType::TokenRevoked,
+                    None,
+                    AuditOutcome::Failure,
+                    Some(e.code()),
+                )
+                .await
+        }
+    }
+
+    result.map(|_| StatusCode::OK)
+}
+
+pub(crate) enum Revocation {
+    // Holds the email of the token's owner
+    Revoked(String),
+    InvalidToken,
+    NotOwned,
+}
+
+// The caller is a client, so it goes into `detail`; the email is the token's owner
+pub(crate) async fn record_revocation(
+    state: &AppState,
+    audit: &AuditContext,
+    client_id: &str,
+    revocation: &Revocation,
+) {
+    let (email, outcome, detail) = match revocation {
+        Revocation::Revoked(email) => (
+            Some(email.as_str()),
+            AuditOutcome::Success,
+            format!("client {}", client_id),
+        ),
+        Revocation::InvalidToken => (
+            None,
+            AuditOutcome::Failure,
+            format!("client {}: invalid token", client_id),
+        ),
+        Revocation::NotOwned => (
+            None,
+            AuditOutcome::Failure,
+            format!("client {}: not the token's client", client_id),
+        ),
+    };
+    audit
+        .record(state, AuditEventType::TokenRevoked, email, outcome, Some(&detail))
+        .await;
+}
+
+// RFC 7009 section 2.1: clients only revoke the tokens issued to them. Sessions
+// and API keys are issued to users rather than to a client, so only clients
+// trusted with every user's tokens, through the `revoke` scope, may revoke them.
+pub(crate) fn may_revoke_user_tokens<'a>(mut scopes: impl Iterator<Item = &'a str>) -> bool {
+    scopes.any(|scope| scope == Role::REVOKE)
+}
+
+// Tokens the client may not revoke, or that are not valid, are left alone
+pub(crate) async fn revoke_user_token(
+    state: &AppState,
+    may_revoke: bool,
+    token: &str,
+    token_type_hint: Option<&str>,
+) -> Result<Revocation, AuthAPIError> {
+    if !may_revoke {
+        return Ok(Revocation::NotOwned);
+    }
+
+    Ok(match revoke_token(state, token, token_type_hint).await? {
+        Some(email) => Revocation::Revoked(email),
+        None => Revocation::InvalidToken,
+    })
+}
+
+// Returns the client id and what became of the token
+async fn revoke_for_client(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: RevokeRequest,
+) -> Result<(String, Revocation), OAuthError> {
+    let client = authenticate_client(
+        headers,
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        state.client_store.clone(),
+    )
+    .await?;
+
+    let token = request
+        .token
+        .filter(|token| !token.is_empty())
+        .ok_or(OAuthError::InvalidRequest)?;
+
+    // Service tokens are short-lived and never checked against the banned tokens
+    if validate_service_token(&token).is_ok() {
+        return Err(OAuthError::UnsupportedTokenType);
+    }
+
+    let client_id = client.id().as_ref().to_owned();
+
+    // Others are answered as if the token was revoked
+    let may_revoke = may_revoke_user_tokens(client.scopes().iter().map(|scope| scope.as_ref()));
+    let revocation = revoke_user_token(state, may_revoke, &token, request.token_type_hint.as_deref())
+        .await
+        .map_err(|_| OAuthError::ServerError)?;
+
+    Ok((client_id, revocation))
+}
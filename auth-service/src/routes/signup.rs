@@ -1,25 +1,39 @@
 use axum::{Json, extract::State, http::StatusCode, response::IntoResponse};
-use serde::{Deserialize, Serialize};
 
-use crate::{app_state::AppState, domain::{email::Email, error::AuthAPIError, password::Password, user::User}};
+use crate::{app_state::AppState, ErrorResponse, domain::{audit::AuditEventType, email::Email, error::AuthAPIError, password::Password, tenant::Tenant, user::User}, utils::audit::{audit_email, AuditContext}};
 
-#[derive(Deserialize)]
-pub struct SignupRequest {
-    pub email: String,
-    pub password: String,
-    #[serde(rename = "requires2FA")]
-    pub requires_2fa: bool,
-}
-
-#[derive(Deserialize, Serialize, PartialEq, Debug)]
-pub struct SignupResponse {
-    pub message: String,
-}
+pub use auth_sdk::{SignupRequest, SignupResponse};
 
+#[utoipa::path(
+    post,
+    path = "/signup",
+    request_body = SignupRequest,
+    responses(
+        (status = 201, description = "User created", body = SignupResponse),
+        (status = 400, description = "Invalid email or password", body = ErrorResponse),
+        (status = 403, description = "Accounts with this email are managed by an LDAP directory", body = ErrorResponse),
+        (status = 409, description = "User already exists", body = ErrorResponse),
+        (status = 422, description = "Malformed request body"),
+    )
+)]
 pub async fn signup(
     State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
     Json(request): Json<SignupRequest>,
 ) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = audit_email(&request.email);
+    let result = create_user(&state, &tenant, request).await;
+    state.metrics.record_signup(if result.is_ok() { "success" } else { "failure" });
+    audit.record_result(&state, AuditEventType::Signup, Some(&email), &result).await;
+    result
+}
+
+async fn create_user(
+    state: &AppState,
+    tenant: &Tenant,
+    request: SignupRequest,
+) -> Result<(StatusCode, Json<SignupResponse>), AuthAPIError> {
     // let email = request.email.trim();
     // let password = request.password.trim();
 
@@ -30,20 +44,32 @@ pub async fn signup(
         return Err(AuthAPIError::InvalidCredentials);
     }
 
-    let mut user_store = state.user_store.write().await;
-
     let email = email.unwrap();
     let password = password.unwrap();
 
-    if let Ok(_) = user_store.get_user(&email).await {
+    // The directory holds the passwords of its domains; its users are created
+    // at their first login
+    if state.ldap_config.directory_for(tenant.id(), &email).is_some() {
+        return Err(AuthAPIError::ManagedByDirectory);
+    }
+
+    // Strength estimation takes a while, so do it before locking the store
+    tenant
+        .password_policy()
+        .check(&password, &email)
+        .map_err(AuthAPIError::WeakPassword)?;
+
+    let mut user_store = state.user_store.write().await;
+
+    if user_store.get_user(tenant.id(), &email).await.is_ok() {
         return Err(AuthAPIError::UserAlreadyExists);
     }
 
     // Create a new `User` instance using data in the `request`
-    let user = User::new(email, password, request.requires_2fa);
+    let user = User::new(email, password, request.requires_2fa).with_tenant(tenant.id().clone());
 
     // Add `user` to the `user_store`. Simply unwrap the returned `Result` enum type for now.
-    if let Err(_) = user_store.add_user(user).await {
+    if user_store.add_user(user).await.is_err() {
         return Err(AuthAPIError::UnexpectedError);
     }
 
@@ -0,0 +1,106 @@
+use std::collections::BTreeSet;
+
+use axum::{
+    extract::State,
+    http::{header, HeaderMap},
+    response::IntoResponse,
+    Form, Json,
+};
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEventType, AuditOutcome},
+        error::OAuthError,
+        role::Role,
+    },
+    utils::{
+        audit::AuditContext,
+        auth::{authenticate_client, generate_service_token},
+    },
+    ErrorResponse,
+};
+
+pub use auth_sdk::{TokenRequest, TokenResponse};
+
+// OAuth 2.0 token endpoint. Only the client credentials grant (RFC 6749
+// section 4.4) is supported; it issues service tokens to registered clients.
+#[utoipa::path(
+    post,
+    path = "/token",
+    request_body(content = TokenRequest, content_type = "application/x-www-form-urlencoded"),
+    security((), ("client_basic" = [])),
+    responses(
+        (status = 200, description = "Service token issued", body = TokenResponse),
+        (status = 400, description = "invalid_request, unsupported_grant_type or invalid_scope", body = ErrorResponse),
+        (status = 401, description = "invalid_client", body = ErrorResponse),
+    )
+)]
+pub async fn token(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    headers: HeaderMap,
+    Form(request): Form<TokenRequest>,
+) -> Result<impl IntoResponse, OAuthError> {
+    let result = issue_token(&state, &headers, request).await;
+
+    // Service tokens belong to clients, not users, so the client id goes into `detail`
+    let (outcome, detail) = match &result {
+        Ok((client_id, _)) => (AuditOutcome::Success, format!("client {}", client_id)),
+        Err(e) => (AuditOutcome::Failure, e.code().to_owned()),
+    };
+    audit
+        .record(&state, AuditEventType::ServiceToken, None, outcome, Some(&detail))
+        .await;
+
+    let (_, response) = result?;
+
+    // RFC 6749 section 5.1: responses carrying tokens must not be cached
+    Ok((
+        [(header::CACHE_CONTROL, "no-store"), (header::PRAGMA, "no-cache")],
+        Json(response),
+    ))
+}
+
+async fn issue_token(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: TokenRequest,
+) -> Result<(String, TokenResponse), OAuthError> {
+    if request.grant_type != "client_credentials" {
+        return Err(OAuthError::UnsupportedGrantType);
+    }
+
+    let client = authenticate_client(
+        headers,
+        request.client_id.as_deref(),
+        request.client_secret.as_deref(),
+        state.client_store.clone(),
+    )
+    .await?;
+
+    // Without an explicit scope the client gets everything it is allowed
+    let scopes = match request.scope {
+        Some(scope) => scope
+            .split_whitespace()
+            .map(|scope| Role::parse(scope.to_owned()))
+            .collect::<Result<BTreeSet<Role>, String>>()
+            .map_err(|_| OAuthError::InvalidScope)?,
+        None => client.scopes().clone(),
+    };
+    if !scopes.is_subset(client.scopes()) {
+        return Err(OAuthError::InvalidScope);
+    }
+
+    let (access_token, claims) =
+        generate_service_token(&client, &scopes).map_err(|_| OAuthError::ServerError)?;
+
+    let response = TokenResponse {
+        access_token,
+        token_type: "Bearer".to_owned(),
+        expires_in: claims.exp - claims.iat,
+        scope: claims.scope,
+    };
+
+    Ok((client.id().as_ref().to_owned(), response))
+}
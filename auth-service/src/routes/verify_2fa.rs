@@ -1,5 +1,96 @@
-use axum::{http, response::IntoResponse};
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
 
-pub async fn verify_2fa() -> impl IntoResponse {
-    http::StatusCode::OK.into_response()
-}
\ No newline at end of file
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::AuditEventType,
+        data_stores::{LoginAttemptId, TwoFACode},
+        email::Email,
+        error::AuthAPIError,
+        tenant::Tenant,
+    },
+    utils::{
+        audit::{audit_email, AuditContext},
+        auth::generate_auth_cookie,
+    },
+    ErrorResponse,
+};
+
+pub use auth_sdk::Verify2FARequest;
+
+#[utoipa::path(
+    post,
+    path = "/verify-2fa",
+    request_body = Verify2FARequest,
+    responses(
+        (status = 200, description = "Code accepted; the `jwt` cookie is set"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+        (status = 401, description = "Wrong login attempt id or code", body = ErrorResponse),
+        (status = 422, description = "Malformed request body"),
+    )
+)]
+pub async fn verify_2fa(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    tenant: Tenant,
+    jar: CookieJar,
+    Json(request): Json<Verify2FARequest>,
+) -> (CookieJar, Result<impl IntoResponse, AuthAPIError>) {
+    let email = audit_email(&request.email);
+    let (jar, result) = check_code(&state, &tenant, jar, request).await;
+    audit
+        .record_result(&state, AuditEventType::Verify2fa, Some(&email), &result)
+        .await;
+    (jar, result)
+}
+
+async fn check_code(
+    state: &AppState,
+    tenant: &Tenant,
+    jar: CookieJar,
+    request: Verify2FARequest,
+) -> (CookieJar, Result<StatusCode, AuthAPIError>) {
+    let (email, login_attempt_id, two_fa_code) = match (
+        Email::parse(request.email),
+        LoginAttemptId::parse(request.login_attempt_id),
+        TwoFACode::parse(request.two_fa_code),
+    ) {
+        (Ok(email), Ok(login_attempt_id), Ok(two_fa_code)) => {
+            (email, login_attempt_id, two_fa_code)
+        }
+        _ => return (jar, Err(AuthAPIError::InvalidCredentials)),
+    };
+
+    let mut two_fa_code_store = state.two_fa_code_store.write().await;
+
+    let code_tuple = match two_fa_code_store.get_code(tenant.id(), &email).await {
+        Ok(code_tuple) => code_tuple,
+        Err(_) => return (jar, Err(AuthAPIError::IncorrectCredentials)),
+    };
+
+    if code_tuple != (login_attempt_id, two_fa_code) {
+        return (jar, Err(AuthAPIError::IncorrectCredentials));
+    }
+
+    // A 2FA code can only be used once
+    if two_fa_code_store.remove_code(tenant.id(), &email).await.is_err() {
+        return (jar, Err(AuthAPIError::UnexpectedError));
+    }
+
+    drop(two_fa_code_store);
+
+    let user = match state.user_store.read().await.get_user(tenant.id(), &email).await {
+        Ok(user) => user.clone(),
+        Err(_) => return (jar, Err(AuthAPIError::IncorrectCredentials)),
+    };
+
+    let auth_cookie = match generate_auth_cookie(&user, tenant, &state.cookie_settings) {
+        Ok(cookie) => cookie,
+        Err(_) => return (jar, Err(AuthAPIError::UnexpectedError)),
+    };
+
+    let updated_jar = jar.add(auth_cookie);
+
+    (updated_jar, Ok(StatusCode::OK))
+}
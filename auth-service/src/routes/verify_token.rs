@@ -1,5 +1,84 @@
-use axum::{http, response::IntoResponse};
+use axum::{extract::State, http::HeaderMap, response::IntoResponse, Json};
 
-pub async fn verify_token() -> impl IntoResponse {
-    http::StatusCode::OK.into_response()
-}
\ No newline at end of file
+use crate::{
+    app_state::AppState,
+    domain::{audit::AuditEventType, error::AuthAPIError},
+    utils::{
+        audit::AuditContext,
+        auth::{require_service_token, validate_bearer_token, Claims},
+    },
+    ErrorResponse,
+};
+
+pub use auth_sdk::VerifyTokenRequest;
+
+// A valid token is answered with its claims (subject, expiry and roles),
+// so that downstream services can make authorization decisions.
+// Verifying a token, passed in the JSON body, is an internal operation and
+// requires a service token in `Authorization: Bearer`. Both JWTs and API keys
+// are accepted.
+#[utoipa::path(
+    post,
+    path = "/verify-token",
+    request_body(content = VerifyTokenRequest, description = "Token to verify; requires a service token as bearer"),
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Token is valid", body = Claims),
+        (status = 400, description = "Missing auth token", body = ErrorResponse),
+        (status = 401, description = "Invalid auth token, or no service token and no body", body = ErrorResponse),
+        (status = 403, description = "Service authentication required", body = ErrorResponse),
+    )
+)]
+pub async fn verify_token(
+    State(state): State<AppState>,
+    audit: AuditContext,
+    headers: HeaderMap,
+    request: Option<Json<VerifyTokenRequest>>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let result = verify(&state, &headers, request.as_ref().map(|Json(r)| r)).await;
+    record_verification(&state, &audit, &result).await;
+    result.map(Json)
+}
+
+// Count and audit a verification; shared with the gRPC `VerifyToken`
+pub(crate) async fn record_verification(
+    state: &AppState,
+    audit: &AuditContext,
+    result: &Result<Claims, AuthAPIError>,
+) {
+    state.metrics.record_token_verification(match result {
+        Ok(_) => "valid",
+        Err(AuthAPIError::MissingToken) => "missing",
+        Err(AuthAPIError::InvalidToken) => "invalid",
+        Err(AuthAPIError::ServiceAuthRequired) => "unauthorized_service",
+        Err(_) => "error",
+    });
+
+    let email = result.as_ref().ok().map(|claims| claims.sub.as_str());
+    audit
+        .record_result(state, AuditEventType::VerifyToken, email, result)
+        .await;
+}
+
+async fn verify(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: Option<&VerifyTokenRequest>,
+) -> Result<Claims, AuthAPIError> {
+    let token = match request {
+        Some(request) => {
+            require_service_token(headers)?;
+            request.token.as_str()
+        }
+        // A bearer credential is never verified on its own behalf, or anyone
+        // could probe tokens here; without a service token there are no credentials
+        None => {
+            require_service_token(headers).map_err(|_| AuthAPIError::InvalidToken)?;
+            return Err(AuthAPIError::MissingToken);
+        }
+    };
+
+    let (claims, _) = validate_bearer_token(token, state).await?;
+
+    Ok(claims)
+}
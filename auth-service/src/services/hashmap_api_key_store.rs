@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{
+    api_key::{ApiKey, ApiKeyId},
+    data_stores::{ApiKeyStore, ApiKeyStoreError},
+    email::Email,
+    tenant::TenantId,
+};
+
+#[derive(Default)]
+pub struct HashmapApiKeyStore {
+    keys: HashMap<ApiKeyId, ApiKey>,
+}
+
+#[async_trait::async_trait]
+impl ApiKeyStore for HashmapApiKeyStore {
+    async fn add_key(&mut self, key: ApiKey) -> Result<(), ApiKeyStoreError> {
+        if self.keys.contains_key(key.id()) {
+            return Err(ApiKeyStoreError::KeyAlreadyExists);
+        }
+        self.keys.insert(*key.id(), key);
+        Ok(())
+    }
+
+    async fn get_key(&self, id: &ApiKeyId) -> Result<ApiKey, ApiKeyStoreError> {
+        self.keys.get(id).cloned().ok_or(ApiKeyStoreError::KeyNotFound)
+    }
+
+    async fn list_keys(&self, tenant: &TenantId, owner: &Email) -> Result<Vec<ApiKey>, ApiKeyStoreError> {
+        let mut keys: Vec<ApiKey> = self
+            .keys
+            .values()
+            .filter(|key| key.tenant() == tenant && key.owner() == owner)
+            .cloned()
+            .collect();
+        keys.sort_by_key(|key| key.created_at());
+        Ok(keys)
+    }
+
+    async fn revoke_key(
+        &mut self,
+        tenant: &TenantId,
+        owner: &Email,
+        id: &ApiKeyId,
+    ) -> Result<(), ApiKeyStoreError> {
+        // Users can only revoke their own keys; anything else looks like a missing key
+        match self.keys.get(id) {
+            Some(key) if key.tenant() == tenant && key.owner() == owner => {
+                self.keys.remove(id);
+                Ok(())
+            }
+            _ => Err(ApiKeyStoreError::KeyNotFound),
+        }
+    }
+
+    async fn record_use(&mut self, id: &ApiKeyId, at: DateTime<Utc>) -> Result<(), ApiKeyStoreError> {
+        match self.keys.get_mut(id) {
+            Some(key) => {
+                key.set_last_used_at(at);
+                Ok(())
+            }
+            None => Err(ApiKeyStoreError::KeyNotFound),
+        }
+    }
+
+    async fn count_keys(&self) -> Result<usize, ApiKeyStoreError> {
+        Ok(self.keys.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::domain::{api_key::ApiKeySecret, role::Role};
+
+    fn test_key(owner: &str) -> ApiKey {
+        let id = ApiKeyId::default();
+        let now = Utc::now();
+        ApiKey::new(
+            id,
+            Email::parse(owner.to_owned()).unwrap(),
+            "ci".to_owned(),
+            BTreeSet::from([Role::user()]),
+            &ApiKeySecret::generate(&id),
+            now,
+            now + chrono::Duration::days(30),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_add_and_get_key() {
+        let mut store = HashmapApiKeyStore::default();
+        let key = test_key("test@example.com");
+
+        assert!(store.add_key(key.clone()).await.is_ok());
+        assert_eq!(
+            store.add_key(key.clone()).await.err().unwrap(),
+            ApiKeyStoreError::KeyAlreadyExists
+        );
+        assert_eq!(store.get_key(key.id()).await.unwrap().name(), "ci");
+        assert_eq!(
+            store.get_key(&ApiKeyId::default()).await.err().unwrap(),
+            ApiKeyStoreError::KeyNotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_keys_only_returns_owned_keys() {
+        let mut store = HashmapApiKeyStore::default();
+        let key = test_key("test@example.com");
+        store.add_key(key.clone()).await.unwrap();
+        store.add_key(test_key("other@example.com")).await.unwrap();
+
+        let keys = store.list_keys(key.tenant(), key.owner()).await.unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].id(), key.id());
+
+        // The same email in another tenant is another user
+        let acme = TenantId::parse("acme".to_owned()).unwrap();
+        assert!(store.list_keys(&acme, key.owner()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_key() {
+        let mut store = HashmapApiKeyStore::default();
+        let key = test_key("test@example.com");
+        store.add_key(key.clone()).await.unwrap();
+
+        let other_owner = Email::parse("other@example.com".to_owned()).unwrap();
+        assert_eq!(
+            store.revoke_key(key.tenant(), &other_owner, key.id()).await.err().unwrap(),
+            ApiKeyStoreError::KeyNotFound
+        );
+
+        assert!(store.revoke_key(key.tenant(), key.owner(), key.id()).await.is_ok());
+        assert!(store.get_key(key.id()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_record_use() {
+        let mut store = HashmapApiKeyStore::default();
+        let key = test_key("test@example.com");
+        store.add_key(key.clone()).await.unwrap();
+
+        let now = Utc::now();
+        assert!(store.record_use(key.id(), now).await.is_ok());
+        assert_eq!(store.get_key(key.id()).await.unwrap().last_used_at(), Some(now));
+    }
+}
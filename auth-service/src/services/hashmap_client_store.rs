@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+
+use crate::domain::{
+    client::{Client, ClientId},
+    data_stores::{ClientStore, ClientStoreError},
+};
+
+#[derive(Default)]
+pub struct HashmapClientStore {
+    clients: HashMap<ClientId, Client>,
+}
+
+#[async_trait::async_trait]
+impl ClientStore for HashmapClientStore {
+    async fn add_client(&mut self, client: Client) -> Result<(), ClientStoreError> {
+        if self.clients.contains_key(client.id()) {
+            return Err(ClientStoreError::ClientAlreadyExists);
+        }
+        self.clients.insert(client.id().clone(), client);
+        Ok(())
+    }
+
+    async fn get_client(&self, id: &ClientId) -> Result<Client, ClientStoreError> {
+        self.clients
+            .get(id)
+            .cloned()
+            .ok_or(ClientStoreError::ClientNotFound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use crate::domain::client::ClientSecret;
+
+    fn test_client() -> Client {
+        Client::new(
+            ClientId::parse("app-service".to_owned()).unwrap(),
+            ClientSecret::parse("secret".to_owned()).unwrap().hash(),
+            BTreeSet::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_add_client() {
+        let mut store = HashmapClientStore::default();
+
+        assert!(store.add_client(test_client()).await.is_ok());
+        assert_eq!(
+            store.add_client(test_client()).await.err().unwrap(),
+            ClientStoreError::ClientAlreadyExists
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_client() {
+        let mut store = HashmapClientStore::default();
+        let client = test_client();
+        store.add_client(client.clone()).await.unwrap();
+
+        assert_eq!(store.get_client(client.id()).await.unwrap().id(), client.id());
+        assert_eq!(
+            store
+                .get_client(&ClientId::parse("unknown".to_owned()).unwrap())
+                .await
+                .err()
+                .unwrap(),
+            ClientStoreError::ClientNotFound
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use crate::domain::{
+    data_stores::{LoginAttemptId, TwoFACode, TwoFACodeStore, TwoFACodeStoreError},
+    email::Email,
+    tenant::TenantId,
+};
+
+#[derive(Default)]
+pub struct HashmapTwoFACodeStore {
+    codes: HashMap<(TenantId, Email), (LoginAttemptId, TwoFACode)>,
+}
+
+#[async_trait::async_trait]
+impl TwoFACodeStore for HashmapTwoFACodeStore {
+    async fn add_code(
+        &mut self,
+        tenant: TenantId,
+        email: Email,
+        login_attempt_id: LoginAttemptId,
+        code: TwoFACode,
+    ) -> Result<(), TwoFACodeStoreError> {
+        self.codes.insert((tenant, email), (login_attempt_id, code));
+        Ok(())
+    }
+
+    async fn remove_code(&mut self, tenant: &TenantId, email: &Email) -> Result<(), TwoFACodeStoreError> {
+        self.codes.remove(&(tenant.clone(), email.clone()));
+        Ok(())
+    }
+
+    async fn get_code(
+        &self,
+        tenant: &TenantId,
+        email: &Email,
+    ) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError> {
+        match self.codes.get(&(tenant.clone(), email.clone())) {
+            Some(value) => Ok(value.clone()),
+            None => Err(TwoFACodeStoreError::LoginAttemptIdNotFound),
+        }
+    }
+
+    async fn count_codes(&self) -> Result<usize, TwoFACodeStoreError> {
+        Ok(self.codes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_and_get_code() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let email = Email::parse("test@example.com".to_owned()).unwrap();
+        let login_attempt_id = LoginAttemptId::default();
+        let code = TwoFACode::default();
+
+        let result = store
+            .add_code(TenantId::default(), email.clone(), login_attempt_id.clone(), code.clone())
+            .await;
+        assert!(result.is_ok());
+
+        let result = store.get_code(&TenantId::default(), &email).await;
+        assert_eq!(result.unwrap(), (login_attempt_id, code));
+    }
+
+    #[tokio::test]
+    async fn test_remove_code() {
+        let mut store = HashmapTwoFACodeStore::default();
+        let email = Email::parse("test@example.com".to_owned()).unwrap();
+        store
+            .add_code(TenantId::default(), email.clone(), LoginAttemptId::default(), TwoFACode::default())
+            .await
+            .unwrap();
+
+        let result = store.remove_code(&TenantId::default(), &email).await;
+        assert!(result.is_ok());
+
+        let result = store.get_code(&TenantId::default(), &email).await;
+        assert_eq!(result.err().unwrap(), TwoFACodeStoreError::LoginAttemptIdNotFound);
+    }
+}
@@ -1,10 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
-use crate::domain::{data_stores::{UserStore, UserStoreError}, email::Email, password::Password, user::User};
+use crate::domain::{data_stores::{UserStore, UserStoreError}, email::Email, identity_provider::ExternalIdentity, password::Password, role::Role, tenant::TenantId, user::User};
 
 #[derive(Default)]
 pub struct HashmapUserStore {
-    users: HashMap<Email, User>,
+    users: HashMap<(TenantId, Email), User>,
 }
 
 #[async_trait::async_trait]
@@ -12,13 +12,15 @@ impl UserStore for HashmapUserStore {
     async fn add_user(&mut self, user: User) -> Result<(), UserStoreError> {
         // Return `UserStoreError::UserAlreadyExists` if the user already exists,
         // otherwise insert the user into the hashmap and return `Ok(())`.
-        match self.users.get(user.email()) {
-            Some(_) => Err(UserStoreError::UserAlreadyExists),
-            None => {
-                self.users.insert(user.email().clone(), user);
-                Ok(())
-            }
+        let key = (user.tenant().clone(), user.email().clone());
+        if self.users.contains_key(&key) {
+            return Err(UserStoreError::UserAlreadyExists);
         }
+        if user.identities().iter().any(|identity| self.is_linked(user.tenant(), identity)) {
+            return Err(UserStoreError::IdentityAlreadyLinked);
+        }
+        self.users.insert(key, user);
+        Ok(())
     }
 
     // Implement a public method called `get_user`, which takes an
@@ -26,8 +28,8 @@ impl UserStore for HashmapUserStore {
     // This function should return a `Result` type containing either a
     // `User` object or a `UserStoreError`.
     // Return `UserStoreError::UserNotFound` if the user can not be found.
-    async fn get_user(&self, email: &Email) -> Result<&User, UserStoreError> {
-        match self.users.get(email) {
+    async fn get_user(&self, tenant: &TenantId, email: &Email) -> Result<&User, UserStoreError> {
+        match self.users.get(&(tenant.clone(), email.clone())) {
             Some(user) => Ok(user),
             None => Err(UserStoreError::UserNotFound),
         }
@@ -39,10 +41,16 @@ impl UserStore for HashmapUserStore {
     // unit type `()` if the email/password passed in match an existing user, or a `UserStoreError`.
     // Return `UserStoreError::UserNotFound` if the user can not be found.
     // Return `UserStoreError::InvalidCredentials` if the password is incorrect.
-    async fn validate_user(&self, email: &Email, password: &Password) -> Result<(), UserStoreError> {
-        match self.users.get(email) {
+    async fn validate_user(
+        &self,
+        tenant: &TenantId,
+        email: &Email,
+        password: &Password,
+    ) -> Result<(), UserStoreError> {
+        match self.users.get(&(tenant.clone(), email.clone())) {
             Some(user) => {
-                if user.password() == password {
+                // Users without a password never match one
+                if user.password() == Some(password) {
                     Ok(())
                 } else {
                     Err(UserStoreError::InvalidCredentials)
@@ -51,12 +59,69 @@ impl UserStore for HashmapUserStore {
             None => Err(UserStoreError::UserNotFound),
         }
     }
+
+    async fn set_roles(
+        &mut self,
+        tenant: &TenantId,
+        email: &Email,
+        roles: BTreeSet<Role>,
+    ) -> Result<(), UserStoreError> {
+        match self.users.get_mut(&(tenant.clone(), email.clone())) {
+            Some(user) => {
+                user.set_roles(roles);
+                Ok(())
+            }
+            None => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    async fn get_user_by_identity(
+        &self,
+        tenant: &TenantId,
+        identity: &ExternalIdentity,
+    ) -> Result<&User, UserStoreError> {
+        self.users
+            .values()
+            .find(|user| user.tenant() == tenant && user.identities().contains(identity))
+            .ok_or(UserStoreError::UserNotFound)
+    }
+
+    async fn link_identity(
+        &mut self,
+        tenant: &TenantId,
+        email: &Email,
+        identity: ExternalIdentity,
+    ) -> Result<(), UserStoreError> {
+        if self.is_linked(tenant, &identity) {
+            return Err(UserStoreError::IdentityAlreadyLinked);
+        }
+        match self.users.get_mut(&(tenant.clone(), email.clone())) {
+            Some(user) => {
+                user.add_identity(identity);
+                Ok(())
+            }
+            None => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    async fn count_users(&self) -> Result<usize, UserStoreError> {
+        Ok(self.users.len())
+    }
+}
+
+impl HashmapUserStore {
+    fn is_linked(&self, tenant: &TenantId, identity: &ExternalIdentity) -> bool {
+        self.users
+            .values()
+            .any(|user| user.tenant() == tenant && user.identities().contains(identity))
+    }
 }
 
 #[cfg(test)]
 mod tests {
 
     use super::*;
+    use crate::domain::identity_provider::ProviderId;
 
     #[tokio::test]
     async fn test_add_user() {
@@ -75,10 +140,10 @@ mod tests {
         let mut store = HashmapUserStore::default();
         let result = store.add_user(user.clone()).await;
         assert!(result.is_ok());
-        let result = store.get_user(&Email::parse("test@example.com".to_string()).unwrap()).await;
+        let result = store.get_user(&TenantId::default(), &Email::parse("test@example.com".to_string()).unwrap()).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().email(), &Email::parse("test@example.com".to_string()).unwrap());
-        let result = store.get_user(&Email::parse("nonexistent@example.com".to_string()).unwrap()).await;
+        let result = store.get_user(&TenantId::default(), &Email::parse("nonexistent@example.com".to_string()).unwrap()).await;
         assert!(result.is_err());
         assert_eq!(result.err().unwrap(), UserStoreError::UserNotFound);
     }
@@ -89,13 +154,75 @@ mod tests {
         let mut store = HashmapUserStore::default();
         let result = store.add_user(user.clone()).await;
         assert!(result.is_ok());
-        let result = store.validate_user(&Email::parse("test@example.com".to_string()).unwrap(), &Password::parse("password".to_string()).unwrap()).await;
+        let result = store.validate_user(&TenantId::default(), &Email::parse("test@example.com".to_string()).unwrap(), &Password::parse("password".to_string()).unwrap()).await;
         assert!(result.is_ok());
-        let result = store.validate_user(&Email::parse("test@example.com".to_string()).unwrap(), &Password::parse("wrong_password".to_string()).unwrap()).await;
+        let result = store.validate_user(&TenantId::default(), &Email::parse("test@example.com".to_string()).unwrap(), &Password::parse("wrong_password".to_string()).unwrap()).await;
         assert!(result.is_err());
         assert_eq!(result.err().unwrap(), UserStoreError::InvalidCredentials);
-        let result = store.validate_user(&Email::parse("nonexistent@example.com".to_string()).unwrap(), &Password::parse("password".to_string()).unwrap()).await;
+        let result = store.validate_user(&TenantId::default(), &Email::parse("nonexistent@example.com".to_string()).unwrap(), &Password::parse("password".to_string()).unwrap()).await;
         assert!(result.is_err());
         assert_eq!(result.err().unwrap(), UserStoreError::UserNotFound);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_set_roles() {
+        let email = Email::parse("test@example.com".to_string()).unwrap();
+        let user = User::new(email.clone(), Password::parse("password".to_string()).unwrap(), false);
+        let mut store = HashmapUserStore::default();
+        store.add_user(user).await.unwrap();
+        assert!(store.get_user(&TenantId::default(), &email).await.unwrap().has_role(&Role::user()));
+
+        let roles = BTreeSet::from([Role::user(), Role::admin()]);
+        let result = store.set_roles(&TenantId::default(), &email, roles.clone()).await;
+        assert!(result.is_ok());
+        assert_eq!(store.get_user(&TenantId::default(), &email).await.unwrap().roles(), &roles);
+
+        let result = store.set_roles(&TenantId::default(), &Email::parse("nonexistent@example.com".to_string()).unwrap(), roles).await;
+        assert_eq!(result.err().unwrap(), UserStoreError::UserNotFound);
+    }
+
+    #[tokio::test]
+    async fn test_tenants_are_isolated() {
+        let email = Email::parse("test@example.com".to_string()).unwrap();
+        let acme = TenantId::parse("acme".to_owned()).unwrap();
+        let mut store = HashmapUserStore::default();
+        store.add_user(User::new(email.clone(), Password::parse("password".to_string()).unwrap(), false)).await.unwrap();
+        assert_eq!(store.get_user(&acme, &email).await.err().unwrap(), UserStoreError::UserNotFound);
+
+        let user = User::new(email.clone(), Password::parse("other_password".to_string()).unwrap(), false).with_tenant(acme.clone());
+        assert!(store.add_user(user).await.is_ok());
+        assert!(store.validate_user(&acme, &email, &Password::parse("other_password".to_string()).unwrap()).await.is_ok());
+        assert_eq!(
+            store.validate_user(&TenantId::default(), &email, &Password::parse("other_password".to_string()).unwrap()).await.err().unwrap(),
+            UserStoreError::InvalidCredentials
+        );
+    }
+
+    #[tokio::test]
+    async fn test_identities() {
+        let email = Email::parse("test@example.com".to_string()).unwrap();
+        let identity = ExternalIdentity::new(ProviderId::parse("corp".to_owned()).unwrap(), "subject-1".to_owned());
+        let mut store = HashmapUserStore::default();
+        store.add_user(User::new(email.clone(), Password::parse("password".to_string()).unwrap(), false)).await.unwrap();
+        assert_eq!(store.get_user_by_identity(&TenantId::default(), &identity).await.err().unwrap(), UserStoreError::UserNotFound);
+
+        assert!(store.link_identity(&TenantId::default(), &email, identity.clone()).await.is_ok());
+        assert_eq!(store.get_user_by_identity(&TenantId::default(), &identity).await.unwrap().email(), &email);
+        assert!(store.get_user_by_identity(&TenantId::parse("acme".to_owned()).unwrap(), &identity).await.is_err());
+
+        // An identity logs in to one account only
+        let other = User::from_identity(Email::parse("other@example.com".to_string()).unwrap(), identity.clone());
+        assert_eq!(store.add_user(other).await.err().unwrap(), UserStoreError::IdentityAlreadyLinked);
+    }
+
+    #[tokio::test]
+    async fn test_users_without_password_cannot_log_in_with_one() {
+        let email = Email::parse("test@example.com".to_string()).unwrap();
+        let identity = ExternalIdentity::new(ProviderId::parse("corp".to_owned()).unwrap(), "subject-1".to_owned());
+        let mut store = HashmapUserStore::default();
+        store.add_user(User::from_identity(email.clone(), identity)).await.unwrap();
+
+        let result = store.validate_user(&TenantId::default(), &email, &Password::parse("password".to_string()).unwrap()).await;
+        assert_eq!(result.err().unwrap(), UserStoreError::InvalidCredentials);
+    }
+}
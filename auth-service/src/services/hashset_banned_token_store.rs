@@ -0,0 +1,61 @@
+use std::collections::HashSet;
+
+use crate::domain::data_stores::{BannedTokenStore, BannedTokenStoreError};
+
+#[derive(Default)]
+pub struct HashsetBannedTokenStore {
+    tokens: HashSet<String>,
+}
+
+#[async_trait::async_trait]
+impl BannedTokenStore for HashsetBannedTokenStore {
+    async fn add_token(&mut self, token: String) -> Result<(), BannedTokenStoreError> {
+        self.tokens.insert(token);
+        Ok(())
+    }
+
+    async fn contains_token(&self, token: &str) -> Result<bool, BannedTokenStoreError> {
+        Ok(self.tokens.contains(token))
+    }
+
+    async fn count_tokens(&self) -> Result<usize, BannedTokenStoreError> {
+        Ok(self.tokens.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_token() {
+        let mut store = HashsetBannedTokenStore::default();
+        let token = "test_token".to_owned();
+
+        let result = store.add_token(token.clone()).await;
+
+        assert!(result.is_ok());
+        assert!(store.tokens.contains(&token));
+    }
+
+    #[tokio::test]
+    async fn test_contains_token() {
+        let mut store = HashsetBannedTokenStore::default();
+        let token = "test_token".to_owned();
+        store.tokens.insert(token.clone());
+
+        assert!(store.contains_token(&token).await.unwrap());
+        assert!(!store.contains_token("other_token").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_count_tokens() {
+        let mut store = HashsetBannedTokenStore::default();
+        assert_eq!(store.count_tokens().await.unwrap(), 0);
+
+        store.add_token("test_token".to_owned()).await.unwrap();
+        store.add_token("test_token".to_owned()).await.unwrap();
+
+        assert_eq!(store.count_tokens().await.unwrap(), 1);
+    }
+}
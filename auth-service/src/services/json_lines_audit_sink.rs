@@ -0,0 +1,247 @@
+use std::path::{Path, PathBuf};
+
+use tokio::{
+    fs,
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::Mutex,
+};
+
+use crate::domain::audit::{AuditEvent, AuditQuery, AuditSink, AuditSinkError};
+
+// Appends one JSON object per line to `path`. Once the file would grow past
+// `max_bytes` it is rotated to `path.1` (and `path.1` to `path.2`, ...),
+// keeping at most `max_files` rotated files.
+pub struct JsonLinesAuditSink {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+    // Serializes writers so that lines and rotations never interleave
+    lock: Mutex<()>,
+}
+
+impl JsonLinesAuditSink {
+    pub async fn new(
+        path: impl Into<PathBuf>,
+        max_bytes: u64,
+        max_files: usize,
+    ) -> Result<Self, AuditSinkError> {
+        let path = path.into();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).await.map_err(unexpected)?;
+        }
+        Ok(Self {
+            path,
+            max_bytes,
+            max_files,
+            lock: Mutex::new(()),
+        })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        path.into()
+    }
+
+    async fn rotate(&self) -> Result<(), AuditSinkError> {
+        if self.max_files == 0 {
+            return remove_if_exists(&self.path).await;
+        }
+
+        remove_if_exists(&self.rotated_path(self.max_files)).await?;
+        for index in (1..self.max_files).rev() {
+            rename_if_exists(&self.rotated_path(index), &self.rotated_path(index + 1)).await?;
+        }
+        rename_if_exists(&self.path, &self.rotated_path(1)).await
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditSink for JsonLinesAuditSink {
+    async fn record(&self, event: AuditEvent) -> Result<(), AuditSinkError> {
+        let mut line = serde_json::to_vec(&event).map_err(unexpected)?;
+        line.push(b'\n');
+
+        let _guard = self.lock.lock().await;
+
+        let current_size = match fs::metadata(&self.path).await {
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        };
+        if current_size > 0 && current_size + line.len() as u64 > self.max_bytes {
+            self.rotate().await?;
+        }
+
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(unexpected)?;
+        file.write_all(&line).await.map_err(unexpected)?;
+        file.flush().await.map_err(unexpected)
+    }
+
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditEvent>, AuditSinkError> {
+        // The files are opened under the lock, so that no rotation moves lines
+        // between them, but read after it is released so writers are not held up
+        let files = {
+            let _guard = self.lock.lock().await;
+
+            // Newest file first
+            let paths = std::iter::once(self.path.clone())
+                .chain((1..=self.max_files).map(|index| self.rotated_path(index)));
+            let mut files = Vec::new();
+            for path in paths {
+                let file = match fs::File::open(&path).await {
+                    Ok(file) => file,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(unexpected(e)),
+                };
+                let len = file.metadata().await.map_err(unexpected)?.len();
+                files.push((file, len));
+            }
+            files
+        };
+
+        let mut events = Vec::new();
+        for (file, len) in files {
+            // Lines appended since are left for the next query
+            let mut contents = String::new();
+            file.take(len)
+                .read_to_string(&mut contents)
+                .await
+                .map_err(unexpected)?;
+
+            for line in contents.lines().rev().filter(|line| !line.trim().is_empty()) {
+                let event: AuditEvent = serde_json::from_str(line).map_err(unexpected)?;
+                if query.matches(&event) {
+                    events.push(event);
+                    if events.len() >= query.limit {
+                        return Ok(events);
+                    }
+                }
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+fn unexpected(e: impl std::fmt::Display) -> AuditSinkError {
+    AuditSinkError::UnexpectedError(e.to_string())
+}
+
+async fn remove_if_exists(path: &Path) -> Result<(), AuditSinkError> {
+    match fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(unexpected(e)),
+        _ => Ok(()),
+    }
+}
+
+async fn rename_if_exists(from: &Path, to: &Path) -> Result<(), AuditSinkError> {
+    match fs::rename(from, to).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(unexpected(e)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::domain::audit::{AuditEventType, AuditOutcome};
+
+    fn test_path() -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("audit-test-{}", uuid::Uuid::new_v4()))
+            .join("audit.jsonl")
+    }
+
+    fn test_event(email: &str) -> AuditEvent {
+        AuditEvent {
+            timestamp: Utc::now(),
+            event_type: AuditEventType::Signup,
+            outcome: AuditOutcome::Success,
+            detail: None,
+            email: Some(email.to_owned()),
+            ip: Some("127.0.0.1".to_owned()),
+            user_agent: None,
+            request_id: None,
+            tenant: None,
+        }
+    }
+
+    fn all() -> AuditQuery {
+        AuditQuery {
+            limit: usize::MAX,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_appends_json_lines() {
+        let path = test_path();
+        let sink = JsonLinesAuditSink::new(&path, 1024 * 1024, 3).await.unwrap();
+
+        sink.record(test_event("a@example.com")).await.unwrap();
+        sink.record(test_event("b@example.com")).await.unwrap();
+
+        let contents = fs::read_to_string(&path).await.unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            serde_json::from_str::<AuditEvent>(lines[1]).unwrap().email,
+            Some("b@example.com".to_owned())
+        );
+
+        fs::remove_dir_all(path.parent().unwrap()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rotates_by_size_and_keeps_max_files() {
+        let path = test_path();
+        let line_len = serde_json::to_vec(&test_event("a@example.com")).unwrap().len() as u64 + 1;
+        // Room for two events per file
+        let sink = JsonLinesAuditSink::new(&path, line_len * 2, 2).await.unwrap();
+
+        for _ in 0..7 {
+            sink.record(test_event("a@example.com")).await.unwrap();
+        }
+
+        assert!(fs::metadata(&path).await.is_ok());
+        assert!(fs::metadata(sink.rotated_path(1)).await.is_ok());
+        assert!(fs::metadata(sink.rotated_path(2)).await.is_ok());
+        assert!(fs::metadata(sink.rotated_path(3)).await.is_err());
+
+        // 1 event in the current file, 2 in each of the rotated ones
+        assert_eq!(sink.query(&all()).await.unwrap().len(), 5);
+
+        fs::remove_dir_all(path.parent().unwrap()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_query_filters_newest_first_and_limits() {
+        let path = test_path();
+        let sink = JsonLinesAuditSink::new(&path, 300, 5).await.unwrap();
+
+        for email in ["a@example.com", "b@example.com", "a@example.com", "a@example.com"] {
+            sink.record(test_event(email)).await.unwrap();
+        }
+
+        let query = AuditQuery {
+            email: Some("a@example.com".to_owned()),
+            ..all()
+        };
+        let events = sink.query(&query).await.unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(events.windows(2).all(|w| w[0].timestamp >= w[1].timestamp));
+
+        // The limit keeps the newest events
+        let limited = sink.query(&AuditQuery { limit: 2, ..query }).await.unwrap();
+        assert_eq!(limited, events[..2]);
+
+        fs::remove_dir_all(path.parent().unwrap()).await.unwrap();
+    }
+}
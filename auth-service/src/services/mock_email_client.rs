@@ -0,0 +1,27 @@
+use crate::domain::{email::Email, email_client::EmailClient};
+
+pub struct MockEmailClient;
+
+#[async_trait::async_trait]
+impl EmailClient for MockEmailClient {
+    async fn send_email(
+        &self,
+        recipient: &Email,
+        subject: &str,
+        content: &str,
+    ) -> Result<(), String> {
+        // Our mock email client will simply log the recipient, subject, and content to standard output
+        println!(
+            "Sending email to {} with subject: {} and content: {}",
+            recipient.display(),
+            subject,
+            content
+        );
+
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
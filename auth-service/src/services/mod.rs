@@ -1 +1,7 @@
-pub mod hashmap_user_store;
\ No newline at end of file
+pub mod hashmap_user_store;
+pub mod hashmap_api_key_store;
+pub mod hashmap_client_store;
+pub mod hashmap_two_fa_code_store;
+pub mod hashset_banned_token_store;
+pub mod mock_email_client;
+pub mod json_lines_audit_sink;
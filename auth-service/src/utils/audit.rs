@@ -0,0 +1,145 @@
+use std::net::SocketAddr;
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header::USER_AGENT, request::Parts},
+};
+use chrono::Utc;
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        audit::{AuditEvent, AuditEventType, AuditOutcome},
+        email::Email,
+        error::AuthAPIError,
+    },
+    utils::tenant::requested_tenant,
+};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+// Emails longer than this cannot be valid anyway; don't let callers bloat the log
+const MAX_LOGGED_FIELD_LENGTH: usize = 320;
+
+// Who made the request, captured once per request so handlers can record audit events
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    // Set for requests to a tenant's routes, e.g. `/t/acme/login`
+    pub tenant: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for AuditContext
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+
+        let header = |name| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(truncate)
+        };
+
+        let user_agent = header(USER_AGENT.as_str());
+        let request_id = header(REQUEST_ID_HEADER);
+        let tenant = requested_tenant(parts, state).await.as_deref().map(truncate);
+
+        Ok(Self {
+            ip,
+            user_agent,
+            request_id,
+            tenant,
+        })
+    }
+}
+
+impl AuditContext {
+    // The same details for a gRPC call, taken from its metadata
+    pub fn from_grpc_request<T>(request: &tonic::Request<T>) -> Self {
+        let metadata = |name| {
+            request
+                .metadata()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(truncate)
+        };
+
+        Self {
+            ip: request.remote_addr().map(|addr| addr.ip().to_string()),
+            user_agent: metadata(USER_AGENT.as_str()),
+            request_id: metadata(REQUEST_ID_HEADER),
+            tenant: None,
+        }
+    }
+
+    // Append an event to the audit sink. A failing sink is reported on stderr
+    // but never turns a request into an error.
+    pub async fn record(
+        &self,
+        state: &AppState,
+        event_type: AuditEventType,
+        email: Option<&str>,
+        outcome: AuditOutcome,
+        detail: Option<&str>,
+    ) {
+        let event = AuditEvent {
+            timestamp: Utc::now(),
+            event_type,
+            outcome,
+            detail: detail.map(truncate),
+            email: email.map(truncate),
+            ip: self.ip.clone(),
+            user_agent: self.user_agent.clone(),
+            request_id: self.request_id.clone(),
+            tenant: self.tenant.clone(),
+        };
+
+        if let Err(e) = state.audit_sink.record(event).await {
+            eprintln!("Failed to record audit event: {:?}", e);
+        }
+    }
+
+    // Record the outcome of a handler that reports errors as `AuthAPIError`
+    pub async fn record_result<T>(
+        &self,
+        state: &AppState,
+        event_type: AuditEventType,
+        email: Option<&str>,
+        result: &Result<T, AuthAPIError>,
+    ) {
+        match result {
+            Ok(_) => {
+                self.record(state, event_type, email, AuditOutcome::Success, None)
+                    .await
+            }
+            Err(e) => {
+                self.record(state, event_type, email, AuditOutcome::Failure, Some(e.message()))
+                    .await
+            }
+        }
+    }
+}
+
+// Events are searched by normalized email, so record that form whenever the
+// address is valid, and what the client sent otherwise
+pub fn audit_email(email: &str) -> String {
+    match Email::parse(email.to_owned()) {
+        Ok(email) => email.as_ref().to_owned(),
+        Err(_) => email.to_owned(),
+    }
+}
+
+fn truncate(value: &str) -> String {
+    value.chars().take(MAX_LOGGED_FIELD_LENGTH).collect()
+}
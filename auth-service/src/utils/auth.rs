@@ -0,0 +1,741 @@
+use std::collections::BTreeSet;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header::AUTHORIZATION, request::Parts, HeaderMap},
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use axum_extra::extract::{cookie::Cookie, CookieJar};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey,
+    Validation,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::{AppState, BannedTokenStoreType, ClientStoreType},
+    domain::{
+        api_key::{ApiKey, ApiKeyId, ApiKeySecret},
+        data_stores::ApiKeyStore,
+        client::{Client, ClientId, ClientSecret},
+        email::Email,
+        error::{AuthAPIError, OAuthError},
+        role::Role,
+        tenant::{Tenant, TenantId, TenantRegistry},
+        user::User,
+    },
+};
+
+use super::{
+    constants::{
+        JWT_SECRET, SERVICE_TOKEN_AUDIENCE, SERVICE_TOKEN_TTL_SECONDS, TOKEN_ISSUER,
+        TOKEN_TTL_SECONDS,
+    },
+    cookies::CookieSettings,
+};
+
+pub use auth_sdk::Claims;
+
+use auth_sdk::AUTH_SERVICE_CLIENT_ID;
+
+// Create cookie with a new JWT auth token, as configured by `settings`
+pub fn generate_auth_cookie(
+    user: &User,
+    tenant: &Tenant,
+    settings: &CookieSettings,
+) -> Result<Cookie<'static>, GenerateTokenError> {
+    let token = generate_auth_token(user, tenant)?;
+    Ok(settings.auth_cookie(token))
+}
+
+#[derive(Debug)]
+pub enum GenerateTokenError {
+    TokenError(jsonwebtoken::errors::Error),
+    UnexpectedError,
+}
+
+// Create JWT auth token carrying the user's roles as a custom claim, signed
+// with the key of the user's tenant
+pub fn generate_auth_token(user: &User, tenant: &Tenant) -> Result<String, GenerateTokenError> {
+    let delta = chrono::Duration::try_seconds(TOKEN_TTL_SECONDS)
+        .ok_or(GenerateTokenError::UnexpectedError)?;
+
+    let now = Utc::now();
+
+    // Create JWT expiration time
+    let exp = now
+        .checked_add_signed(delta)
+        .ok_or(GenerateTokenError::UnexpectedError)?
+        .timestamp();
+
+    // Cast exp to a usize, which is what Claims expects
+    let exp: usize = exp
+        .try_into()
+        .map_err(|_| GenerateTokenError::UnexpectedError)?;
+    let iat: usize = now
+        .timestamp()
+        .try_into()
+        .map_err(|_| GenerateTokenError::UnexpectedError)?;
+
+    let sub = user.email().as_ref().to_owned();
+    let roles = user.roles().iter().map(|role| role.as_ref().to_owned()).collect();
+
+    let claims = Claims {
+        sub,
+        exp,
+        iat,
+        roles,
+        iss: Some(issuer(tenant.id())),
+        tenant: Some(tenant.id().as_ref().to_owned()),
+    };
+
+    create_token(&claims, tenant).map_err(GenerateTokenError::TokenError)
+}
+
+// The `iss` claim of the auth tokens of `tenant`
+pub fn issuer(tenant: &TenantId) -> String {
+    format!("{}/t/{}", TOKEN_ISSUER, tenant)
+}
+
+// Check if JWT auth token is valid by decoding it using the key of its tenant
+// and making sure it has not been banned (e.g. by logging out).
+pub async fn validate_token(
+    token: &str,
+    tenants: &TenantRegistry,
+    banned_token_store: BannedTokenStoreType,
+) -> Result<Claims, jsonwebtoken::errors::Error> {
+    match banned_token_store.read().await.contains_token(token).await {
+        Ok(false) => {}
+        _ => return Err(ErrorKind::InvalidToken.into()),
+    }
+
+    let tenant = token_tenant(token, tenants)?;
+
+    // A tenant with a signing key accepts nothing signed with its secret
+    let (mut validation, decoding_key) = match tenant.signing_key() {
+        Some(signing_key) => (Validation::new(Algorithm::EdDSA), signing_key.decoding_key().clone()),
+        None => (Validation::default(), tenant.decoding_key()),
+    };
+    validation.set_issuer(&[issuer(tenant.id())]);
+    validation.set_required_spec_claims(&["exp", "sub", "iss"]);
+
+    let claims = decode::<Claims>(token, &decoding_key, &validation)?.claims;
+    if claims.tenant.as_deref() != Some(tenant.id().as_ref()) {
+        return Err(ErrorKind::InvalidToken.into());
+    }
+
+    Ok(claims)
+}
+
+// The tenant named by the `kid` header of `token`. Nothing in the token can be
+// trusted before its signature was checked with this tenant's key.
+pub fn token_tenant<'a>(
+    token: &str,
+    tenants: &'a TenantRegistry,
+) -> Result<&'a Tenant, jsonwebtoken::errors::Error> {
+    let kid = decode_header(token)?.kid.ok_or(ErrorKind::InvalidToken)?;
+    TenantId::parse(kid)
+        .ok()
+        .and_then(|id| tenants.get(&id))
+        .ok_or_else(|| ErrorKind::InvalidToken.into())
+}
+
+// Where an `AuthToken` was found
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenSource {
+    Cookie,
+    Bearer,
+}
+
+// The caller's credential, taken from `Authorization: Bearer` if present and from
+// the auth cookie otherwise. Protected routes take this extractor, or
+// `Result<AuthToken, AuthAPIError>` when they need to record a missing token.
+#[derive(Debug, Clone)]
+pub struct AuthToken {
+    pub value: String,
+    pub source: TokenSource,
+}
+
+impl FromRequestParts<AppState> for AuthToken {
+    type Rejection = AuthAPIError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        if let Some(token) = bearer_token(&parts.headers) {
+            return Ok(AuthToken {
+                value: token.to_owned(),
+                source: TokenSource::Bearer,
+            });
+        }
+
+        let jar = CookieJar::from_headers(&parts.headers);
+        match jar.get(state.cookie_settings.name()) {
+            Some(cookie) if !cookie.value().is_empty() => Ok(AuthToken {
+                value: cookie.value().to_owned(),
+                source: TokenSource::Cookie,
+            }),
+            _ => Err(AuthAPIError::MissingToken),
+        }
+    }
+}
+
+// Validate the JWT of a user session of `tenant` and return the caller's email
+// and claims. API keys are deliberately not accepted here.
+pub async fn authenticate_session(
+    token: &AuthToken,
+    tenant: &Tenant,
+    state: &AppState,
+) -> Result<(Email, Claims), AuthAPIError> {
+    let claims = validate_token(&token.value, &state.tenants, state.banned_token_store.clone())
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    // Sessions of other tenants are no good here, however valid
+    if claims.tenant.as_deref() != Some(tenant.id().as_ref()) {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let email = Email::parse(claims.sub.clone()).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    Ok((email, claims))
+}
+
+// Authenticate a user by session JWT or, when sent as a bearer credential, API key
+pub async fn authenticate_user(token: &AuthToken, state: &AppState) -> Result<Claims, AuthAPIError> {
+    match token.source {
+        TokenSource::Bearer => validate_bearer_token(&token.value, state)
+            .await
+            .map(|(claims, _)| claims),
+        TokenSource::Cookie => {
+            validate_token(&token.value, &state.tenants, state.banned_token_store.clone())
+                .await
+                .map_err(|_| AuthAPIError::InvalidToken)
+        }
+    }
+}
+
+// Extract the credential from an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenType {
+    AccessToken,
+    ApiKey,
+}
+
+impl TokenType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenType::AccessToken => "access_token",
+            TokenType::ApiKey => "api_key",
+        }
+    }
+}
+
+// Validate a bearer credential, which is either a JWT access token or an API key
+pub async fn validate_bearer_token(
+    token: &str,
+    state: &AppState,
+) -> Result<(Claims, TokenType), AuthAPIError> {
+    check_bearer_token(token, state, true).await
+}
+
+// Like `validate_bearer_token`, for callers that only look at the token, such as
+// `/introspect`: an API key's last use is left as it was
+pub async fn inspect_bearer_token(
+    token: &str,
+    state: &AppState,
+) -> Result<(Claims, TokenType), AuthAPIError> {
+    check_bearer_token(token, state, false).await
+}
+
+async fn check_bearer_token(
+    token: &str,
+    state: &AppState,
+    record_use: bool,
+) -> Result<(Claims, TokenType), AuthAPIError> {
+    if ApiKeySecret::looks_like_api_key(token) {
+        let claims = validate_api_key(token, state, record_use).await?;
+        return Ok((claims, TokenType::ApiKey));
+    }
+
+    validate_token(token, &state.tenants, state.banned_token_store.clone())
+        .await
+        .map(|claims| (claims, TokenType::AccessToken))
+        .map_err(|_| AuthAPIError::InvalidToken)
+}
+
+// Stop accepting a session token or API key: session tokens are banned, as on
+// logout, and API keys revoked. The hint, e.g. `api_key`, says which kind to
+// look for first. Returns the email of the token's owner, or `None` if the token
+// is not one of ours or no longer valid anyway.
+pub async fn revoke_token(
+    state: &AppState,
+    token: &str,
+    token_type_hint: Option<&str>,
+) -> Result<Option<String>, AuthAPIError> {
+    let kinds = match token_type_hint {
+        Some("api_key") => [TokenType::ApiKey, TokenType::AccessToken],
+        _ => [TokenType::AccessToken, TokenType::ApiKey],
+    };
+
+    for kind in kinds {
+        let revoked = match kind {
+            TokenType::AccessToken => revoke_access_token(state, token).await?,
+            TokenType::ApiKey => revoke_api_key(state, token).await?,
+        };
+        if revoked.is_some() {
+            state.revocation_notifier.notify(token);
+            return Ok(revoked);
+        }
+    }
+
+    Ok(None)
+}
+
+async fn revoke_access_token(state: &AppState, token: &str) -> Result<Option<String>, AuthAPIError> {
+    let Ok(claims) = validate_token(token, &state.tenants, state.banned_token_store.clone()).await
+    else {
+        return Ok(None);
+    };
+
+    state
+        .banned_token_store
+        .write()
+        .await
+        .add_token(token.to_owned())
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok(Some(claims.sub))
+}
+
+async fn revoke_api_key(state: &AppState, token: &str) -> Result<Option<String>, AuthAPIError> {
+    let Ok((id, secret)) = ApiKeySecret::parse(token.to_owned()) else {
+        return Ok(None);
+    };
+
+    let mut api_key_store = state.api_key_store.write().await;
+    let key = match api_key_store.get_key(&id).await {
+        Ok(key) if key.matches(&secret) => key,
+        _ => return Ok(None),
+    };
+
+    api_key_store
+        .revoke_key(key.tenant(), key.owner(), &id)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok(Some(key.owner().as_ref().to_owned()))
+}
+
+// Check an API key against its stored hash and expiry, record that it was used
+// if `record_use`, and describe it with the same claims an access token would carry.
+async fn validate_api_key(
+    token: &str,
+    state: &AppState,
+    record_use: bool,
+) -> Result<Claims, AuthAPIError> {
+    let (id, secret) =
+        ApiKeySecret::parse(token.to_owned()).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let now = Utc::now();
+
+    let key = if record_use {
+        let mut api_key_store = state.api_key_store.write().await;
+        let key = usable_api_key(&*api_key_store, &id, &secret, now).await?;
+
+        api_key_store
+            .record_use(&id, now)
+            .await
+            .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+        key
+    } else {
+        usable_api_key(&*state.api_key_store.read().await, &id, &secret, now).await?
+    };
+
+    // A key never grants more than its owner currently has
+    let owner_roles: BTreeSet<Role> = match state
+        .user_store
+        .read()
+        .await
+        .get_user(key.tenant(), key.owner())
+        .await
+    {
+        Ok(user) => user.roles().clone(),
+        Err(_) => return Err(AuthAPIError::InvalidToken),
+    };
+
+    let roles = key
+        .scopes()
+        .intersection(&owner_roles)
+        .map(|role| role.as_ref().to_owned())
+        .collect();
+
+    let exp = key
+        .expires_at()
+        .timestamp()
+        .try_into()
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+    let iat = key
+        .created_at()
+        .timestamp()
+        .try_into()
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok(Claims {
+        sub: key.owner().as_ref().to_owned(),
+        exp,
+        iat,
+        roles,
+        iss: Some(issuer(key.tenant())),
+        tenant: Some(key.tenant().as_ref().to_owned()),
+    })
+}
+
+// The stored key, if `secret` is its secret and it has not expired
+async fn usable_api_key(
+    api_key_store: &(dyn ApiKeyStore + Send + Sync),
+    id: &ApiKeyId,
+    secret: &ApiKeySecret,
+    now: DateTime<Utc>,
+) -> Result<ApiKey, AuthAPIError> {
+    let key = api_key_store
+        .get_key(id)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    if !key.matches(secret) || key.is_expired(now) {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    Ok(key)
+}
+
+// Authenticate a confidential client, either with HTTP Basic credentials
+// (client_secret_basic) or with `client_id`/`client_secret` form fields (client_secret_post).
+pub async fn authenticate_client(
+    headers: &HeaderMap,
+    form_client_id: Option<&str>,
+    form_client_secret: Option<&str>,
+    client_store: ClientStoreType,
+) -> Result<Client, OAuthError> {
+    let (client_id, client_secret) = match basic_credentials(headers)? {
+        Some(credentials) => credentials,
+        None => match (form_client_id, form_client_secret) {
+            (Some(id), Some(secret)) => (id.to_owned(), secret.to_owned()),
+            _ => return Err(OAuthError::InvalidClient),
+        },
+    };
+
+    let client_id = ClientId::parse(client_id).map_err(|_| OAuthError::InvalidClient)?;
+    let client_secret =
+        ClientSecret::parse(client_secret).map_err(|_| OAuthError::InvalidClient)?;
+
+    let client = client_store
+        .read()
+        .await
+        .get_client(&client_id)
+        .await
+        .map_err(|_| OAuthError::InvalidClient)?;
+
+    if !client.matches(&client_secret) {
+        return Err(OAuthError::InvalidClient);
+    }
+
+    Ok(client)
+}
+
+fn basic_credentials(headers: &HeaderMap) -> Result<Option<(String, String)>, OAuthError> {
+    let Some(value) = headers.get(AUTHORIZATION) else {
+        return Ok(None);
+    };
+    let Some(encoded) = value.to_str().ok().and_then(|v| v.strip_prefix("Basic ")) else {
+        return Ok(None);
+    };
+
+    let decoded = BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or(OAuthError::InvalidClient)?;
+
+    let (id, secret) = decoded.split_once(':').ok_or(OAuthError::InvalidClient)?;
+    Ok(Some((id.to_owned(), secret.to_owned())))
+}
+
+// Issue a short-lived service token for an authenticated client
+pub fn generate_service_token(
+    client: &Client,
+    scopes: &BTreeSet<Role>,
+) -> Result<(String, ServiceClaims), GenerateTokenError> {
+    let scope = scopes
+        .iter()
+        .map(|scope| scope.as_ref())
+        .collect::<Vec<_>>()
+        .join(" ");
+    sign_service_token(client.id().as_ref(), scope)
+}
+
+// Issue a service token to auth-service itself, to authenticate its own calls
+// to other services
+pub fn generate_own_service_token() -> Result<String, GenerateTokenError> {
+    sign_service_token(AUTH_SERVICE_CLIENT_ID, String::new()).map(|(token, _)| token)
+}
+
+fn sign_service_token(sub: &str, scope: String) -> Result<(String, ServiceClaims), GenerateTokenError> {
+    let now = Utc::now();
+    let iat: usize = now
+        .timestamp()
+        .try_into()
+        .map_err(|_| GenerateTokenError::UnexpectedError)?;
+    let exp = iat + SERVICE_TOKEN_TTL_SECONDS as usize;
+
+    let claims = ServiceClaims {
+        sub: sub.to_owned(),
+        aud: SERVICE_TOKEN_AUDIENCE.to_owned(),
+        exp,
+        iat,
+        scope,
+    };
+
+    let token = encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .map_err(GenerateTokenError::TokenError)?;
+
+    Ok((token, claims))
+}
+
+pub fn validate_service_token(token: &str) -> Result<ServiceClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[SERVICE_TOKEN_AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "sub", "aud"]);
+
+    decode::<ServiceClaims>(
+        token,
+        &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+}
+
+// Internal endpoints are only for registered services, which prove who they
+// are with a service token in the `Authorization: Bearer` header.
+pub fn require_service_token(headers: &HeaderMap) -> Result<ServiceClaims, AuthAPIError> {
+    let token = bearer_token(headers).ok_or(AuthAPIError::ServiceAuthRequired)?;
+    validate_service_token(token).map_err(|_| AuthAPIError::ServiceAuthRequired)
+}
+
+// Create JWT auth token by encoding claims using the key of `tenant`, which
+// the `kid` header names so that validation knows which key to check against
+fn create_token(claims: &Claims, tenant: &Tenant) -> Result<String, jsonwebtoken::errors::Error> {
+    let kid = Some(tenant.id().as_ref().to_owned());
+    match tenant.signing_key() {
+        Some(signing_key) => {
+            let header = jsonwebtoken::Header {
+                kid,
+                ..jsonwebtoken::Header::new(Algorithm::EdDSA)
+            };
+            encode(&header, &claims, signing_key.encoding_key())
+        }
+        None => {
+            let header = jsonwebtoken::Header {
+                kid,
+                ..Default::default()
+            };
+            encode(&header, &claims, &tenant.encoding_key())
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ServiceClaims {
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub iat: usize,
+    pub scope: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, sync::Arc};
+
+    use tokio::sync::RwLock;
+
+    use axum_extra::extract::cookie::SameSite;
+
+    use super::*;
+    use crate::{
+        domain::{email::Email, password::Password, role::Role},
+        services::hashset_banned_token_store::HashsetBannedTokenStore,
+        utils::constants::JWT_COOKIE_NAME,
+    };
+
+    fn test_user() -> User {
+        User::new(
+            Email::parse("test@example.com".to_owned()).unwrap(),
+            Password::parse("password123".to_owned()).unwrap(),
+            false,
+        )
+    }
+
+    fn test_client() -> Client {
+        Client::new(
+            ClientId::parse("app-service".to_owned()).unwrap(),
+            ClientSecret::parse("secret".to_owned()).unwrap().hash(),
+            BTreeSet::new(),
+        )
+    }
+
+    fn banned_token_store() -> BannedTokenStoreType {
+        Arc::new(RwLock::new(HashsetBannedTokenStore::default()))
+    }
+
+    // The default tenant, and `acme` with its own key
+    fn tenants() -> TenantRegistry {
+        let mut tenants = TenantRegistry::default();
+        tenants
+            .add_tenant(Tenant::new(
+                TenantId::parse("acme".to_owned()).unwrap(),
+                "acme-secret-with-plenty-of-entropy".to_owned(),
+            ))
+            .unwrap();
+        tenants
+    }
+
+    fn acme() -> Tenant {
+        tenants()
+            .get(&TenantId::parse("acme".to_owned()).unwrap())
+            .unwrap()
+            .clone()
+    }
+
+    #[tokio::test]
+    async fn test_generate_auth_cookie() {
+        let cookie = generate_auth_cookie(
+            &test_user(),
+            tenants().default_tenant(),
+            &CookieSettings::default(),
+        )
+        .unwrap();
+        assert_eq!(cookie.name(), JWT_COOKIE_NAME);
+        assert_eq!(cookie.value().split('.').count(), 3);
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_with_valid_token() {
+        let user = test_user().with_roles(BTreeSet::from([Role::user(), Role::admin()]));
+        let token = generate_auth_token(&user, tenants().default_tenant()).unwrap();
+
+        let claims = validate_token(&token, &tenants(), banned_token_store()).await.unwrap();
+
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.tenant.as_deref(), Some("default"));
+        assert_eq!(claims.iss.as_deref(), Some("auth-service/t/default"));
+        assert_eq!(claims.roles, vec!["admin".to_owned(), "user".to_owned()]);
+        assert!(claims.has_role("admin"));
+
+        let exp = Utc::now()
+            .checked_add_signed(chrono::Duration::try_minutes(9).expect("valid duration"))
+            .expect("valid timestamp")
+            .timestamp();
+        assert!(claims.exp > exp as usize);
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_with_invalid_token() {
+        let result = validate_token("invalid_token", &tenants(), banned_token_store()).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(AUTHORIZATION, "Basic abc".parse().unwrap());
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(AUTHORIZATION, "Bearer ".parse().unwrap());
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(AUTHORIZATION, "Bearer abc".parse().unwrap());
+        assert_eq!(bearer_token(&headers), Some("abc"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_with_banned_token() {
+        let token = generate_auth_token(&test_user(), tenants().default_tenant()).unwrap();
+        let store = banned_token_store();
+        store.write().await.add_token(token.clone()).await.unwrap();
+
+        let result = validate_token(&token, &tenants(), store).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_validate_token_checks_the_key_of_its_tenant() {
+        let user = test_user().with_tenant(acme().id().clone());
+        let token = generate_auth_token(&user, &acme()).unwrap();
+        let claims = validate_token(&token, &tenants(), banned_token_store()).await.unwrap();
+        assert_eq!(claims.tenant.as_deref(), Some("acme"));
+        assert_eq!(claims.iss.as_deref(), Some("auth-service/t/acme"));
+
+        // Unknown to a deployment without the tenant
+        let result = validate_token(&token, &TenantRegistry::default(), banned_token_store()).await;
+        assert!(result.is_err());
+
+        // A token signed with one tenant's key cannot pass for another tenant's
+        let header = jsonwebtoken::Header {
+            kid: Some("default".to_owned()),
+            ..Default::default()
+        };
+        let forged = encode(&header, &claims, &acme().encoding_key()).unwrap();
+        assert!(validate_token(&forged, &tenants(), banned_token_store()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_service_and_user_tokens_are_not_interchangeable() {
+        let (service_token, claims) = generate_service_token(&test_client(), &BTreeSet::new()).unwrap();
+        assert_eq!(validate_service_token(&service_token).unwrap(), claims);
+        assert!(validate_token(&service_token, &tenants(), banned_token_store()).await.is_err());
+
+        let user_token = generate_auth_token(&test_user(), tenants().default_tenant()).unwrap();
+        assert!(validate_service_token(&user_token).is_err());
+    }
+
+    #[test]
+    fn test_basic_credentials() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(basic_credentials(&headers), Ok(None));
+
+        headers.insert(AUTHORIZATION, "Bearer abc".parse().unwrap());
+        assert_eq!(basic_credentials(&headers), Ok(None));
+
+        let encoded = BASE64.encode("app-service:secret");
+        headers.insert(AUTHORIZATION, format!("Basic {}", encoded).parse().unwrap());
+        assert_eq!(
+            basic_credentials(&headers),
+            Ok(Some(("app-service".to_owned(), "secret".to_owned())))
+        );
+
+        headers.insert(AUTHORIZATION, "Basic !!!".parse().unwrap());
+        assert_eq!(basic_credentials(&headers), Err(OAuthError::InvalidClient));
+    }
+}
@@ -0,0 +1,129 @@
+use std::env as std_env;
+
+use dotenvy::dotenv;
+use lazy_static::lazy_static;
+use rand::{distr::Alphanumeric, Rng};
+
+// Define a lazily evaluated static. lazy_static is needed because std_env::var is not a const function.
+lazy_static! {
+    pub static ref JWT_SECRET: String = set_token();
+    pub static ref EMAIL_LOCAL_PART_CASE_SENSITIVE: bool = set_email_local_part_case_sensitive();
+}
+
+fn set_token() -> String {
+    dotenv().ok(); // Load environment variables
+    match std_env::var(env::JWT_SECRET_ENV_VAR) {
+        Ok(secret) if !secret.is_empty() => secret,
+        // Without a configured secret every process signs with its own random key,
+        // so tokens stop validating after a restart or on another replica.
+        _ => {
+            eprintln!("{} is not set, using a random per-process secret", env::JWT_SECRET_ENV_VAR);
+            rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(64)
+                .map(char::from)
+                .collect()
+        }
+    }
+}
+
+// RFC 5321 leaves the case of the local part to the mail server, but in practice
+// `Bob@example.com` and `bob@example.com` are the same mailbox, so by default they
+// are the same account. Set `EMAIL_LOCAL_PART_CASE_SENSITIVE=true` to tell them apart.
+fn set_email_local_part_case_sensitive() -> bool {
+    dotenv().ok();
+    std_env::var(env::EMAIL_LOCAL_PART_CASE_SENSITIVE_ENV_VAR)
+        .map(|value| value.eq_ignore_ascii_case("true") || value == "1")
+        .unwrap_or(false)
+}
+
+pub mod env {
+    pub const JWT_SECRET_ENV_VAR: &str = "JWT_SECRET";
+    pub const SERVICE_CLIENTS_ENV_VAR: &str = "SERVICE_CLIENTS";
+    pub const AUDIT_LOG_PATH_ENV_VAR: &str = "AUDIT_LOG_PATH";
+    pub const AUDIT_LOG_MAX_BYTES_ENV_VAR: &str = "AUDIT_LOG_MAX_BYTES";
+    pub const AUDIT_LOG_MAX_FILES_ENV_VAR: &str = "AUDIT_LOG_MAX_FILES";
+    pub const EMAIL_LOCAL_PART_CASE_SENSITIVE_ENV_VAR: &str = "EMAIL_LOCAL_PART_CASE_SENSITIVE";
+    pub const PASSWORD_MIN_LENGTH_ENV_VAR: &str = "PASSWORD_MIN_LENGTH";
+    pub const PASSWORD_MAX_LENGTH_ENV_VAR: &str = "PASSWORD_MAX_LENGTH";
+    pub const PASSWORD_MIN_SCORE_ENV_VAR: &str = "PASSWORD_MIN_SCORE";
+    pub const BREACHED_PASSWORDS_PATH_ENV_VAR: &str = "BREACHED_PASSWORDS_PATH";
+    pub const CSRF_TRUSTED_ORIGINS_ENV_VAR: &str = "CSRF_TRUSTED_ORIGINS";
+    pub const CORS_ALLOWED_ORIGINS_ENV_VAR: &str = "CORS_ALLOWED_ORIGINS";
+    pub const CORS_ALLOWED_METHODS_ENV_VAR: &str = "CORS_ALLOWED_METHODS";
+    pub const CORS_ALLOWED_HEADERS_ENV_VAR: &str = "CORS_ALLOWED_HEADERS";
+    pub const AUTH_COOKIE_NAME_ENV_VAR: &str = "AUTH_COOKIE_NAME";
+    pub const AUTH_COOKIE_DOMAIN_ENV_VAR: &str = "AUTH_COOKIE_DOMAIN";
+    pub const AUTH_COOKIE_PATH_ENV_VAR: &str = "AUTH_COOKIE_PATH";
+    pub const AUTH_COOKIE_SECURE_ENV_VAR: &str = "AUTH_COOKIE_SECURE";
+    pub const AUTH_COOKIE_SAME_SITE_ENV_VAR: &str = "AUTH_COOKIE_SAME_SITE";
+    pub const AUTH_COOKIE_MAX_AGE_ENV_VAR: &str = "AUTH_COOKIE_MAX_AGE";
+    pub const MAGIC_LINK_BASE_URL_ENV_VAR: &str = "MAGIC_LINK_BASE_URL";
+    pub const TENANTS_ENV_VAR: &str = "TENANTS";
+    pub const JWT_SIGNING_KEY_ENV_VAR: &str = "JWT_SIGNING_KEY";
+    pub const OIDC_PROVIDERS_ENV_VAR: &str = "OIDC_PROVIDERS";
+    pub const OIDC_REDIRECT_BASE_URL_ENV_VAR: &str = "OIDC_REDIRECT_BASE_URL";
+    pub const LDAP_DIRECTORIES_ENV_VAR: &str = "LDAP_DIRECTORIES";
+    pub const REVOCATION_WEBHOOK_URLS_ENV_VAR: &str = "REVOCATION_WEBHOOK_URLS";
+}
+
+// Default name of the auth cookie, see `AUTH_COOKIE_NAME`
+pub const JWT_COOKIE_NAME: &str = "jwt";
+
+// Double-submit CSRF token: the cookie value must be echoed back in the header
+pub const CSRF_COOKIE_NAME: &str = "csrf_token";
+pub const CSRF_HEADER_NAME: &str = "x-csrf-token";
+
+// Issuer of auth tokens, followed by the tenant: `auth-service/t/<tenant>`
+pub const TOKEN_ISSUER: &str = "auth-service";
+
+// Lifetime of issued auth tokens, in seconds
+pub const TOKEN_TTL_SECONDS: i64 = 600;
+
+// Lifetime of emailed login links, in seconds
+pub const MAGIC_LINK_TTL_SECONDS: i64 = 600;
+
+// Audience of login link tokens, which keeps them apart from auth tokens
+pub const MAGIC_LINK_AUDIENCE: &str = "magic-link";
+
+// Keeps the state of a login at an identity provider between the redirect
+// there and the callback, which must come within the TTL (seconds)
+pub const OIDC_LOGIN_COOKIE_NAME: &str = "oidc_login";
+pub const OIDC_LOGIN_TTL_SECONDS: i64 = 600;
+
+// Audience of the login state, which keeps it apart from auth tokens
+pub const OIDC_LOGIN_AUDIENCE: &str = "oidc-login";
+
+// How long a call to an identity provider may take, in seconds
+pub const OIDC_HTTP_TIMEOUT_SECONDS: u64 = 10;
+
+// How long connecting to an LDAP directory, and each operation there, may take, in seconds
+pub const LDAP_TIMEOUT_SECONDS: u64 = 10;
+
+// How long pushing a revoked token to a service may take, in seconds
+pub const REVOCATION_WEBHOOK_TIMEOUT_SECONDS: u64 = 5;
+
+// Lifetime of service tokens issued through the client credentials grant, in seconds
+pub const SERVICE_TOKEN_TTL_SECONDS: i64 = 900;
+
+// Audience of service tokens. User tokens never carry it, which keeps the two apart.
+pub const SERVICE_TOKEN_AUDIENCE: &str = "auth-service";
+
+// Longest lifetime a user can request for an API key, in days
+pub const MAX_API_KEY_TTL_DAYS: u32 = 365;
+
+pub mod prod {
+    pub const APP_ADDRESS: &str = "0.0.0.0:3000";
+    pub const GRPC_ADDRESS: &str = "0.0.0.0:50051";
+
+    // The audit log is rotated once it reaches this size; older files beyond
+    // AUDIT_LOG_MAX_FILES are dropped
+    pub const AUDIT_LOG_PATH: &str = "audit/audit.jsonl";
+    pub const AUDIT_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+    pub const AUDIT_LOG_MAX_FILES: usize = 5;
+}
+
+pub mod test {
+    pub const APP_ADDRESS: &str = "127.0.0.1:0";
+    pub const GRPC_ADDRESS: &str = "127.0.0.1:0";
+}
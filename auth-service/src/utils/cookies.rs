@@ -0,0 +1,189 @@
+use axum_extra::extract::cookie::{Cookie, SameSite};
+
+use super::constants::JWT_COOKIE_NAME;
+
+// How the auth cookie is issued. The defaults suit local development over
+// plain HTTP; deployments behind TLS should at least turn on `secure`.
+#[derive(Debug, Clone)]
+pub struct CookieSettings {
+    name: String,
+    domain: Option<String>,
+    path: String,
+    secure: bool,
+    same_site: SameSite,
+    // `None` makes it a session cookie, dropped when the browser closes
+    max_age: Option<i64>,
+}
+
+impl Default for CookieSettings {
+    fn default() -> Self {
+        Self {
+            name: JWT_COOKIE_NAME.to_owned(),
+            domain: None,
+            path: "/".to_owned(),
+            secure: false,
+            same_site: SameSite::Lax,
+            max_age: None,
+        }
+    }
+}
+
+impl CookieSettings {
+    pub fn new(
+        name: String,
+        domain: Option<String>,
+        path: String,
+        secure: bool,
+        same_site: SameSite,
+        max_age: Option<i64>,
+    ) -> Result<Self, String> {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) {
+            return Err(format!("Invalid cookie name: {}", name));
+        }
+        if !path.starts_with('/') {
+            return Err(format!("Invalid cookie path: {}", path));
+        }
+        // Browsers refuse `SameSite=None` cookies that are not `Secure`
+        if same_site == SameSite::None && !secure {
+            return Err("SameSite=None requires a Secure cookie".to_owned());
+        }
+        if max_age.is_some_and(|max_age| max_age <= 0) {
+            return Err("Cookie Max-Age must be positive".to_owned());
+        }
+
+        Ok(Self {
+            name,
+            domain: domain.filter(|domain| !domain.is_empty()),
+            path,
+            secure,
+            same_site,
+            max_age,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn auth_cookie(&self, token: String) -> Cookie<'static> {
+        let mut cookie = Cookie::build((self.name.clone(), token))
+            .path(self.path.clone())
+            .http_only(true) // prevent JavaScript from accessing the cookie
+            .secure(self.secure)
+            .same_site(self.same_site);
+        if let Some(domain) = &self.domain {
+            cookie = cookie.domain(domain.clone());
+        }
+        if let Some(max_age) = self.max_age {
+            cookie = cookie.max_age(time::Duration::seconds(max_age));
+        }
+        cookie.build()
+    }
+
+    // A short-lived cookie set next to the auth cookie, with the same domain,
+    // path and security attributes, e.g. the state of a login in progress
+    pub fn companion_cookie(&self, name: &str, value: String, max_age: i64) -> Cookie<'static> {
+        let mut cookie = Cookie::build((name.to_owned(), value))
+            .path(self.path.clone())
+            .http_only(true)
+            .secure(self.secure)
+            .same_site(self.same_site)
+            .max_age(time::Duration::seconds(max_age));
+        if let Some(domain) = &self.domain {
+            cookie = cookie.domain(domain.clone());
+        }
+        cookie.build()
+    }
+
+    pub fn companion_removal_cookie(&self, name: &str) -> Cookie<'static> {
+        let mut cookie = Cookie::build(name.to_owned()).path(self.path.clone());
+        if let Some(domain) = &self.domain {
+            cookie = cookie.domain(domain.clone());
+        }
+        cookie.build()
+    }
+
+    // A cookie matching the auth cookie, for `CookieJar::remove`. Domain and
+    // path must be the ones it was set with, or the browser keeps it.
+    pub fn removal_cookie(&self) -> Cookie<'static> {
+        let mut cookie = Cookie::build(self.name.clone()).path(self.path.clone());
+        if let Some(domain) = &self.domain {
+            cookie = cookie.domain(domain.clone());
+        }
+        cookie.build()
+    }
+}
+
+// Parse a `SameSite` value as written in the Set-Cookie header, ignoring case
+pub fn parse_same_site(value: &str) -> Result<SameSite, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "strict" => Ok(SameSite::Strict),
+        "lax" => Ok(SameSite::Lax),
+        "none" => Ok(SameSite::None),
+        _ => Err(format!("Invalid SameSite value: {}", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_cookie_matches_previous_behaviour() {
+        let cookie = CookieSettings::default().auth_cookie("token".to_owned());
+
+        assert_eq!(cookie.name(), "jwt");
+        assert_eq!(cookie.path(), Some("/"));
+        assert_eq!(cookie.http_only(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+        assert_eq!(cookie.secure(), Some(false));
+        assert_eq!(cookie.domain(), None);
+        assert_eq!(cookie.max_age(), None);
+    }
+
+    #[test]
+    fn test_applies_every_setting() {
+        let settings = CookieSettings::new(
+            "session".to_owned(),
+            Some("example.com".to_owned()),
+            "/auth".to_owned(),
+            true,
+            SameSite::None,
+            Some(600),
+        )
+        .unwrap();
+
+        let cookie = settings.auth_cookie("token".to_owned());
+        assert_eq!(cookie.name(), "session");
+        assert_eq!(cookie.domain(), Some("example.com"));
+        assert_eq!(cookie.path(), Some("/auth"));
+        assert_eq!(cookie.secure(), Some(true));
+        assert_eq!(cookie.same_site(), Some(SameSite::None));
+        assert_eq!(cookie.max_age(), Some(time::Duration::seconds(600)));
+
+        let removal = settings.removal_cookie();
+        assert_eq!(removal.domain(), Some("example.com"));
+        assert_eq!(removal.path(), Some("/auth"));
+    }
+
+    #[test]
+    fn test_rejects_invalid_settings() {
+        let settings = |name: &str, path: &str, secure, same_site, max_age| {
+            CookieSettings::new(name.to_owned(), None, path.to_owned(), secure, same_site, max_age)
+        };
+
+        assert!(settings("", "/", false, SameSite::Lax, None).is_err());
+        assert!(settings("a b", "/", false, SameSite::Lax, None).is_err());
+        assert!(settings("jwt", "auth", false, SameSite::Lax, None).is_err());
+        assert!(settings("jwt", "/", false, SameSite::None, None).is_err());
+        assert!(settings("jwt", "/", false, SameSite::Lax, Some(0)).is_err());
+    }
+
+    #[test]
+    fn test_parses_same_site() {
+        assert_eq!(parse_same_site("Strict"), Ok(SameSite::Strict));
+        assert_eq!(parse_same_site("lax"), Ok(SameSite::Lax));
+        assert_eq!(parse_same_site("NONE"), Ok(SameSite::None));
+        assert!(parse_same_site("sometimes").is_err());
+    }
+}
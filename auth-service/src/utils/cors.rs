@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use axum::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+
+use super::{audit::REQUEST_ID_HEADER, constants::CSRF_HEADER_NAME};
+
+// How long browsers may cache a preflight response
+const PREFLIGHT_MAX_AGE: Duration = Duration::from_secs(600);
+
+// Cross-origin access to the API. Only the listed origins get CORS headers, and
+// with them the right to send credentialed (cookie-carrying) requests; every
+// other origin is left to the browser's same-origin policy.
+#[derive(Debug, Clone)]
+pub struct CorsConfig {
+    allowed_origins: Vec<HeaderValue>,
+    allowed_methods: Vec<Method>,
+    allowed_headers: Vec<HeaderName>,
+}
+
+impl Default for CorsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_origins: Vec::new(),
+            allowed_methods: vec![Method::GET, Method::POST, Method::DELETE],
+            allowed_headers: vec![
+                header::CONTENT_TYPE,
+                header::AUTHORIZATION,
+                HeaderName::from_static(CSRF_HEADER_NAME),
+                HeaderName::from_static(REQUEST_ID_HEADER),
+            ],
+        }
+    }
+}
+
+impl CorsConfig {
+    // Empty method or header lists keep the defaults
+    pub fn new(
+        allowed_origins: Vec<String>,
+        allowed_methods: Vec<String>,
+        allowed_headers: Vec<String>,
+    ) -> Result<Self, String> {
+        let default = Self::default();
+
+        let allowed_origins = allowed_origins
+            .iter()
+            .map(|origin| parse_origin(origin))
+            .collect::<Result<_, _>>()?;
+
+        let allowed_methods = if allowed_methods.is_empty() {
+            default.allowed_methods
+        } else {
+            allowed_methods
+                .iter()
+                .map(|method| {
+                    Method::from_bytes(method.to_uppercase().as_bytes())
+                        .map_err(|_| format!("Invalid CORS method: {}", method))
+                })
+                .collect::<Result<_, _>>()?
+        };
+
+        let allowed_headers = if allowed_headers.is_empty() {
+            default.allowed_headers
+        } else {
+            allowed_headers
+                .iter()
+                .map(|name| {
+                    HeaderName::from_bytes(name.as_bytes())
+                        .map_err(|_| format!("Invalid CORS header: {}", name))
+                })
+                .collect::<Result<_, _>>()?
+        };
+
+        Ok(Self {
+            allowed_origins,
+            allowed_methods,
+            allowed_headers,
+        })
+    }
+
+    pub fn allowed_origins(&self) -> &[HeaderValue] {
+        &self.allowed_origins
+    }
+
+    pub fn layer(&self) -> CorsLayer {
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::list(self.allowed_origins.clone()))
+            .allow_methods(self.allowed_methods.clone())
+            .allow_headers(self.allowed_headers.clone())
+            .allow_credentials(true)
+            .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
+            .max_age(PREFLIGHT_MAX_AGE)
+    }
+}
+
+// Browsers send `scheme://host[:port]`; a wildcard or a path cannot match anything
+fn parse_origin(origin: &str) -> Result<HeaderValue, String> {
+    let origin = origin.trim_end_matches('/');
+    let valid = match origin.split_once("://") {
+        Some((scheme, authority)) => {
+            !scheme.is_empty()
+                && !authority.is_empty()
+                && !authority.contains(['/', '*', ' '])
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(format!("Invalid CORS origin: {}", origin));
+    }
+    HeaderValue::from_str(origin).map_err(|_| format!("Invalid CORS origin: {}", origin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_empty_lists_keep_default_methods_and_headers() {
+        let config = CorsConfig::new(strings(&["http://localhost:8000/"]), vec![], vec![]).unwrap();
+
+        assert_eq!(config.allowed_origins(), &[HeaderValue::from_static("http://localhost:8000")]);
+        assert_eq!(config.allowed_methods, CorsConfig::default().allowed_methods);
+        assert_eq!(config.allowed_headers, CorsConfig::default().allowed_headers);
+    }
+
+    #[test]
+    fn test_parses_methods_and_headers() {
+        let config = CorsConfig::new(vec![], strings(&["get", "PUT"]), strings(&["X-Custom"])).unwrap();
+
+        assert_eq!(config.allowed_methods, vec![Method::GET, Method::PUT]);
+        assert_eq!(config.allowed_headers, vec![HeaderName::from_static("x-custom")]);
+    }
+
+    #[test]
+    fn test_rejects_invalid_origins() {
+        for origin in ["*", "localhost:8000", "http://localhost:8000/app", "http://local host"] {
+            assert!(CorsConfig::new(strings(&[origin]), vec![], vec![]).is_err(), "{:?}", origin);
+        }
+    }
+}
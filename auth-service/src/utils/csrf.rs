@@ -0,0 +1,228 @@
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use axum_extra::extract::{
+    cookie::{Cookie, SameSite},
+    CookieJar,
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+
+use crate::{app_state::AppState, domain::error::AuthAPIError};
+
+use super::constants::{CSRF_COOKIE_NAME, CSRF_HEADER_NAME};
+
+// Origins other than our own that may send state-changing requests,
+// e.g. app-service's pages calling `/logout`
+#[derive(Debug, Clone, Default)]
+pub struct CsrfConfig {
+    trusted_origins: Vec<String>,
+}
+
+impl CsrfConfig {
+    pub fn new(trusted_origins: Vec<String>) -> Self {
+        Self {
+            trusted_origins: trusted_origins
+                .into_iter()
+                .map(|origin| origin.trim_end_matches('/').to_owned())
+                .collect(),
+        }
+    }
+
+    pub fn trusted_origins(&self) -> &[String] {
+        &self.trusted_origins
+    }
+
+    fn is_trusted(&self, origin: &str) -> bool {
+        self.trusted_origins.iter().any(|trusted| trusted == origin)
+    }
+}
+
+// Cross-site request forgery protection for every state-changing request
+// (anything but GET, HEAD and OPTIONS):
+// - browsers that send `Sec-Fetch-Site: cross-site` are refused, unless the
+//   `Origin` is trusted;
+// - an `Origin` must be our own or a trusted one;
+// - requests carrying the auth cookie must repeat the value of the `csrf_token`
+//   cookie in the `X-CSRF-Token` header (double-submit cookie). Other sites can
+//   make the browser send our cookies, but cannot read them.
+// Requests without the cookie or with an `Authorization` header, such as service
+// calls with a bearer token, only go through the first two checks. Responses hand
+// out a `csrf_token` cookie to clients that do not have one yet.
+pub async fn csrf_protect(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    request: Request,
+    next: Next,
+) -> Response {
+    let safe_method = matches!(*request.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+
+    if !safe_method && !passes_csrf_checks(&state.csrf_config, state.cookie_settings.name(), &jar, request.headers()) {
+        return AuthAPIError::CsrfCheckFailed.into_response();
+    }
+
+    let mut response = next.run(request).await;
+
+    if jar.get(CSRF_COOKIE_NAME).is_none() {
+        let cookie = create_csrf_cookie(generate_csrf_token());
+        if let Ok(value) = HeaderValue::from_str(&cookie.to_string()) {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+    }
+
+    response
+}
+
+fn passes_csrf_checks(
+    config: &CsrfConfig,
+    auth_cookie_name: &str,
+    jar: &CookieJar,
+    headers: &HeaderMap,
+) -> bool {
+    let origin = header_value(headers, header::ORIGIN.as_str());
+    let trusted_origin = origin.is_some_and(|origin| config.is_trusted(origin));
+
+    if !trusted_origin {
+        if header_value(headers, "sec-fetch-site") == Some("cross-site") {
+            return false;
+        }
+        if let Some(origin) = origin {
+            if !is_same_origin(origin, headers) {
+                return false;
+            }
+        }
+    }
+
+    // Other sites cannot set `Authorization` without a CORS preflight, so requests
+    // carrying one do not rely on the ambient cookie
+    if jar.get(auth_cookie_name).is_some() && !headers.contains_key(header::AUTHORIZATION) {
+        let expected = jar.get(CSRF_COOKIE_NAME).map(|cookie| cookie.value());
+        let submitted = header_value(headers, CSRF_HEADER_NAME);
+        return match (expected, submitted) {
+            (Some(expected), Some(submitted)) if !expected.is_empty() => {
+                constant_time_eq(expected.as_bytes(), submitted.as_bytes())
+            }
+            _ => false,
+        };
+    }
+
+    true
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+// The scheme is left out on purpose: behind a TLS-terminating proxy we only see plain HTTP
+fn is_same_origin(origin: &str, headers: &HeaderMap) -> bool {
+    let Some(host) = header_value(headers, header::HOST.as_str()) else {
+        return false;
+    };
+    origin
+        .split_once("://")
+        .is_some_and(|(_, authority)| authority.eq_ignore_ascii_case(host))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+pub fn generate_csrf_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// Readable by our own scripts, which copy it into the `X-CSRF-Token` header
+fn create_csrf_cookie(token: String) -> Cookie<'static> {
+    Cookie::build((CSRF_COOKIE_NAME, token))
+        .path("/")
+        .http_only(false)
+        .same_site(SameSite::Strict)
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+    use crate::utils::constants::JWT_COOKIE_NAME;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn session_jar(csrf_token: &str) -> CookieJar {
+        CookieJar::new()
+            .add(Cookie::new(JWT_COOKIE_NAME, "token"))
+            .add(Cookie::new(CSRF_COOKIE_NAME, csrf_token.to_owned()))
+    }
+
+    #[test]
+    fn test_requests_without_cookies_only_need_a_matching_origin() {
+        let config = CsrfConfig::default();
+        let jar = CookieJar::new();
+
+        assert!(passes_csrf_checks(&config, JWT_COOKIE_NAME, &jar, &headers(&[])));
+        assert!(passes_csrf_checks(
+            &config,
+            JWT_COOKIE_NAME,
+            &jar,
+            &headers(&[("host", "localhost:3000"), ("origin", "http://localhost:3000")])
+        ));
+        assert!(!passes_csrf_checks(
+            &config,
+            JWT_COOKIE_NAME,
+            &jar,
+            &headers(&[("host", "localhost:3000"), ("origin", "http://evil.example")])
+        ));
+        assert!(!passes_csrf_checks(&config, JWT_COOKIE_NAME, &jar, &headers(&[("sec-fetch-site", "cross-site")])));
+    }
+
+    #[test]
+    fn test_trusted_origins_may_send_cross_site_requests() {
+        let config = CsrfConfig::new(vec!["http://localhost:8000/".to_owned()]);
+
+        assert!(passes_csrf_checks(
+            &config,
+            JWT_COOKIE_NAME,
+            &session_jar("abc"),
+            &headers(&[
+                ("host", "localhost:3000"),
+                ("origin", "http://localhost:8000"),
+                ("sec-fetch-site", "cross-site"),
+                ("x-csrf-token", "abc"),
+            ])
+        ));
+    }
+
+    #[test]
+    fn test_cookie_authenticated_requests_need_the_token() {
+        let config = CsrfConfig::default();
+
+        assert!(passes_csrf_checks(&config, JWT_COOKIE_NAME, &session_jar("abc"), &headers(&[("x-csrf-token", "abc")])));
+        assert!(!passes_csrf_checks(&config, JWT_COOKIE_NAME, &session_jar("abc"), &headers(&[("x-csrf-token", "abd")])));
+        assert!(!passes_csrf_checks(&config, JWT_COOKIE_NAME, &session_jar("abc"), &headers(&[])));
+        assert!(!passes_csrf_checks(&config, JWT_COOKIE_NAME, &session_jar(""), &headers(&[("x-csrf-token", "")])));
+        assert!(passes_csrf_checks(
+            &config,
+            JWT_COOKIE_NAME,
+            &session_jar("abc"),
+            &headers(&[("authorization", "Bearer token")])
+        ));
+    }
+
+    #[test]
+    fn test_generated_tokens_are_unique() {
+        assert_ne!(generate_csrf_token(), generate_csrf_token());
+        assert_eq!(generate_csrf_token().len(), 43);
+    }
+}
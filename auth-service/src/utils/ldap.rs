@@ -0,0 +1,202 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use ldap3::{ldap_escape, Ldap, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+
+use crate::domain::{
+    email::Email, ldap_directory::LdapDirectory, password::Password, tenant::TenantId,
+};
+
+use super::constants::LDAP_TIMEOUT_SECONDS;
+
+// Result code of a bind with a wrong DN or password, RFC 4511
+const INVALID_CREDENTIALS: u32 = 49;
+
+// The LDAP directories logins are checked against, by tenant and email domain.
+// Emails of other domains keep logging in with the password in the user store.
+#[derive(Debug, Clone, Default)]
+pub struct LdapConfig {
+    directories: BTreeMap<(TenantId, String), LdapDirectory>,
+}
+
+// What the directory knows about the user logging in
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryUser {
+    pub dn: String,
+    // DNs of the groups listed in the directory's group attribute
+    pub groups: Vec<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LdapLoginError {
+    // No single entry has the email, or the password does not bind as it
+    IncorrectCredentials,
+    Unavailable(String),
+}
+
+impl LdapConfig {
+    pub fn new(directories: Vec<LdapDirectory>) -> Result<Self, String> {
+        let mut registry = BTreeMap::new();
+        for directory in directories {
+            for domain in directory.domains() {
+                let key = (directory.tenant().clone(), domain.clone());
+                if registry.contains_key(&key) {
+                    return Err(format!(
+                        "Domain {} of tenant {} is served by more than one LDAP directory",
+                        domain,
+                        directory.tenant()
+                    ));
+                }
+                registry.insert(key, directory.clone());
+            }
+        }
+
+        Ok(Self {
+            directories: registry,
+        })
+    }
+
+    // The directory that holds the password of `email`, if any
+    pub fn directory_for(&self, tenant: &TenantId, email: &Email) -> Option<&LdapDirectory> {
+        self.directories
+            .get(&(tenant.clone(), email.domain().to_owned()))
+    }
+
+    // Finds the entry of `email` and binds as it with `password`. The connection
+    // is only used for this login.
+    pub async fn authenticate(
+        &self,
+        directory: &LdapDirectory,
+        email: &Email,
+        password: &Password,
+    ) -> Result<DirectoryUser, LdapLoginError> {
+        let timeout = Duration::from_secs(LDAP_TIMEOUT_SECONDS);
+        let settings = LdapConnSettings::new().set_conn_timeout(timeout);
+        let (connection, mut ldap) = LdapConnAsync::with_settings(settings, directory.url())
+            .await
+            .map_err(unavailable)?;
+        ldap3::drive!(connection);
+
+        let result = search_and_bind(&mut ldap, directory, email, password, timeout).await;
+        let _ = ldap.unbind().await;
+        result
+    }
+}
+
+async fn search_and_bind(
+    ldap: &mut Ldap,
+    directory: &LdapDirectory,
+    email: &Email,
+    password: &Password,
+    timeout: Duration,
+) -> Result<DirectoryUser, LdapLoginError> {
+    if let (Some(bind_dn), Some(bind_password)) = (directory.bind_dn(), directory.bind_password()) {
+        ldap.with_timeout(timeout)
+            .simple_bind(bind_dn, bind_password)
+            .await
+            .and_then(|result| result.success())
+            .map_err(unavailable)?;
+    }
+
+    let filter = directory
+        .user_filter()
+        .replace("{email}", &ldap_escape(email.as_ref()));
+    let (mut entries, _) = ldap
+        .with_timeout(timeout)
+        .search(
+            directory.base_dn(),
+            Scope::Subtree,
+            &filter,
+            vec![directory.group_attribute()],
+        )
+        .await
+        .and_then(|result| result.success())
+        .map_err(unavailable)?;
+
+    // Binding as one of several entries would log in as whoever the password fits
+    if entries.len() != 1 {
+        return Err(LdapLoginError::IncorrectCredentials);
+    }
+    let entry = SearchEntry::construct(entries.remove(0));
+
+    // A bind without a password is an anonymous bind, which always succeeds
+    if password.as_ref().is_empty() {
+        return Err(LdapLoginError::IncorrectCredentials);
+    }
+    let result = ldap
+        .with_timeout(timeout)
+        .simple_bind(&entry.dn, password.as_ref())
+        .await
+        .map_err(unavailable)?;
+    match result.rc {
+        0 => {}
+        INVALID_CREDENTIALS => return Err(LdapLoginError::IncorrectCredentials),
+        _ => return Err(unavailable(LdapError::from(result))),
+    }
+
+    let groups = entry
+        .attrs
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(directory.group_attribute()))
+        .map(|(_, groups)| groups)
+        .unwrap_or_default();
+
+    Ok(DirectoryUser {
+        dn: entry.dn,
+        groups,
+    })
+}
+
+fn unavailable(e: LdapError) -> LdapLoginError {
+    LdapLoginError::Unavailable(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::ldap_directory::parse_ldap_directories;
+
+    fn directories(json: &str) -> Vec<LdapDirectory> {
+        parse_ldap_directories(json).unwrap()
+    }
+
+    #[test]
+    fn test_directory_for() {
+        let config = LdapConfig::new(directories(
+            r#"[{"id": "corp", "url": "ldap://localhost", "domains": ["corp.example.com"], "baseDn": "dc=corp"},
+                {"id": "acme", "tenant": "acme", "url": "ldap://localhost", "domains": ["corp.example.com"], "baseDn": "dc=acme"}]"#,
+        ))
+        .unwrap();
+        let email = |email: &str| Email::parse(email.to_owned()).unwrap();
+        let acme = TenantId::parse("acme".to_owned()).unwrap();
+
+        let directory = config.directory_for(&TenantId::default(), &email("Alice@Corp.Example.com"));
+        assert_eq!(directory.map(LdapDirectory::id), Some("corp"));
+        let directory = config.directory_for(&acme, &email("alice@corp.example.com"));
+        assert_eq!(directory.map(LdapDirectory::id), Some("acme"));
+        assert!(config
+            .directory_for(&TenantId::default(), &email("alice@example.com"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_rejects_domains_served_twice() {
+        let json = r#"[{"id": "a", "url": "ldap://localhost", "domains": ["corp.example.com"], "baseDn": "dc=a"},
+                       {"id": "b", "url": "ldap://localhost", "domains": ["CORP.example.com"], "baseDn": "dc=b"}]"#;
+        assert!(LdapConfig::new(directories(json)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_directory_is_unavailable() {
+        // Nothing listens on port 1
+        let config = LdapConfig::new(directories(
+            r#"[{"id": "corp", "url": "ldap://127.0.0.1:1", "domains": ["corp.example.com"], "baseDn": "dc=corp"}]"#,
+        ))
+        .unwrap();
+        let email = Email::parse("alice@corp.example.com".to_owned()).unwrap();
+        let directory = config.directory_for(&TenantId::default(), &email).unwrap();
+        let password = Password::parse("password123".to_owned()).unwrap();
+
+        let result = config.authenticate(directory, &email, &password).await;
+        assert!(matches!(result, Err(LdapLoginError::Unavailable(_))));
+    }
+}
@@ -0,0 +1,166 @@
+use chrono::Utc;
+use jsonwebtoken::{decode, encode, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::domain::{
+    email::Email,
+    tenant::{Tenant, TenantId},
+};
+
+use super::{
+    auth::GenerateTokenError,
+    constants::{MAGIC_LINK_AUDIENCE, MAGIC_LINK_TTL_SECONDS},
+};
+
+// Where the links emailed by `/login/magic-link` point. The login page there
+// posts the token to `/login/magic-link/verify`, so that link scanners fetching
+// the URL do not use up the link.
+#[derive(Debug, Clone)]
+pub struct MagicLinkConfig {
+    base_url: String,
+}
+
+impl MagicLinkConfig {
+    // `base_url` is where users reach auth-service, e.g. `https://auth.example.com`
+    pub fn new(base_url: &str) -> Result<Self, String> {
+        if !base_url.starts_with("http://") && !base_url.starts_with("https://") {
+            return Err(format!("Magic link base URL must be http(s): {}", base_url));
+        }
+
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        })
+    }
+
+    // Links for other tenants than the default one name the tenant, whose
+    // routes the login page then uses
+    pub fn link(&self, tenant: &TenantId, token: &str) -> String {
+        if tenant.is_default() {
+            format!("{}/?magic_token={}", self.base_url, token)
+        } else {
+            format!("{}/?tenant={}&magic_token={}", self.base_url, tenant, token)
+        }
+    }
+}
+
+impl Default for MagicLinkConfig {
+    fn default() -> Self {
+        Self::new("http://localhost:3000").unwrap()
+    }
+}
+
+// The audience keeps link tokens apart from auth tokens, and the random `jti`
+// makes every link unique, so a used one can be banned without affecting others
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct MagicLinkClaims {
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub iat: usize,
+    pub jti: String,
+}
+
+// Signed with the tenant's key, so the link only works for that tenant
+pub fn generate_magic_link_token(email: &Email, tenant: &Tenant) -> Result<String, GenerateTokenError> {
+    let iat: usize = Utc::now()
+        .timestamp()
+        .try_into()
+        .map_err(|_| GenerateTokenError::UnexpectedError)?;
+
+    let claims = MagicLinkClaims {
+        sub: email.as_ref().to_owned(),
+        aud: MAGIC_LINK_AUDIENCE.to_owned(),
+        exp: iat + MAGIC_LINK_TTL_SECONDS as usize,
+        iat,
+        jti: Uuid::new_v4().to_string(),
+    };
+
+    encode(&jsonwebtoken::Header::default(), &claims, &tenant.encoding_key())
+        .map_err(GenerateTokenError::TokenError)
+}
+
+// Checks the signature and expiry only; whether the link was used already is
+// up to the caller
+pub fn validate_magic_link_token(
+    token: &str,
+    tenant: &Tenant,
+) -> Result<MagicLinkClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[MAGIC_LINK_AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "sub", "aud"]);
+
+    decode::<MagicLinkClaims>(token, &tenant.decoding_key(), &validation).map(|data| data.claims)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        domain::{password::Password, tenant::TenantRegistry, user::User},
+        utils::auth::{generate_auth_token, validate_token},
+    };
+
+    fn email() -> Email {
+        Email::parse("test@example.com".to_owned()).unwrap()
+    }
+
+    fn tenant() -> Tenant {
+        TenantRegistry::default().default_tenant().clone()
+    }
+
+    #[test]
+    fn test_link_tokens_are_unique_and_valid() {
+        let first = generate_magic_link_token(&email(), &tenant()).unwrap();
+        let second = generate_magic_link_token(&email(), &tenant()).unwrap();
+        assert_ne!(first, second);
+
+        let claims = validate_magic_link_token(&first, &tenant()).unwrap();
+        assert_eq!(claims.sub, "test@example.com");
+        assert_eq!(claims.exp - claims.iat, MAGIC_LINK_TTL_SECONDS as usize);
+    }
+
+    #[test]
+    fn test_link_tokens_only_work_for_their_tenant() {
+        let acme = Tenant::new(
+            TenantId::parse("acme".to_owned()).unwrap(),
+            "acme-secret-with-plenty-of-entropy".to_owned(),
+        );
+        let token = generate_magic_link_token(&email(), &acme).unwrap();
+        assert!(validate_magic_link_token(&token, &acme).is_ok());
+        assert!(validate_magic_link_token(&token, &tenant()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_link_and_auth_tokens_are_not_interchangeable() {
+        let user = User::new(
+            email(),
+            Password::parse("password123".to_owned()).unwrap(),
+            false,
+        );
+        let auth_token = generate_auth_token(&user, &tenant()).unwrap();
+        assert!(validate_magic_link_token(&auth_token, &tenant()).is_err());
+
+        let link_token = generate_magic_link_token(&email(), &tenant()).unwrap();
+        let banned_token_store = std::sync::Arc::new(tokio::sync::RwLock::new(
+            crate::services::hashset_banned_token_store::HashsetBannedTokenStore::default(),
+        ));
+        let tenants = TenantRegistry::default();
+        assert!(validate_token(&link_token, &tenants, banned_token_store).await.is_err());
+    }
+
+    #[test]
+    fn test_config_requires_an_http_url() {
+        assert!(MagicLinkConfig::new("auth.example.com").is_err());
+
+        let config = MagicLinkConfig::new("https://auth.example.com/").unwrap();
+        assert_eq!(
+            config.link(&TenantId::default(), "t"),
+            "https://auth.example.com/?magic_token=t"
+        );
+        assert_eq!(
+            config.link(&TenantId::parse("acme".to_owned()).unwrap(), "t"),
+            "https://auth.example.com/?tenant=acme&magic_token=t"
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::time::Instant;
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+
+use crate::app_state::AppState;
+
+// Prometheus metrics of one running service. Each `AppState` owns its own registry,
+// so test apps running side by side do not see each other's numbers.
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    logins: IntCounterVec,
+    signups: IntCounterVec,
+    token_verifications: IntCounterVec,
+    store_size: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by route",
+            ),
+            &["method", "route"],
+        )
+        .unwrap();
+        let logins = IntCounterVec::new(
+            Opts::new("logins_total", "Login attempts by result"),
+            &["result"],
+        )
+        .unwrap();
+        let signups = IntCounterVec::new(
+            Opts::new("signups_total", "Signup attempts by result"),
+            &["result"],
+        )
+        .unwrap();
+        let token_verifications = IntCounterVec::new(
+            Opts::new("token_verifications_total", "Token verifications by result"),
+            &["result"],
+        )
+        .unwrap();
+        let store_size = IntGaugeVec::new(
+            Opts::new("store_entries", "Number of entries held by each store"),
+            &["store"],
+        )
+        .unwrap();
+
+        registry.register(Box::new(http_requests.clone())).unwrap();
+        registry.register(Box::new(http_request_duration.clone())).unwrap();
+        registry.register(Box::new(logins.clone())).unwrap();
+        registry.register(Box::new(signups.clone())).unwrap();
+        registry.register(Box::new(token_verifications.clone())).unwrap();
+        registry.register(Box::new(store_size.clone())).unwrap();
+
+        Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            logins,
+            signups,
+            token_verifications,
+            store_size,
+        }
+    }
+
+    pub fn record_login(&self, result: &str) {
+        self.logins.with_label_values(&[result]).inc();
+    }
+
+    pub fn record_signup(&self, result: &str) {
+        self.signups.with_label_values(&[result]).inc();
+    }
+
+    pub fn record_token_verification(&self, result: &str) {
+        self.token_verifications.with_label_values(&[result]).inc();
+    }
+
+    pub fn set_store_size(&self, store: &str, size: usize) {
+        self.store_size
+            .with_label_values(&[store])
+            .set(size.try_into().unwrap_or(i64::MAX));
+    }
+
+    // Everything gathered so far, in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("Failed to encode metrics");
+        String::from_utf8(buffer).expect("Metrics are not valid UTF-8")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Count and time every request to a known route. The route template (e.g. `/api-keys/{id}`)
+// is used as label rather than the raw path, to keep the number of series bounded.
+pub async fn track_metrics(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned())
+        .unwrap_or("unmatched".to_owned());
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let status = response.status().as_u16().to_string();
+    state
+        .metrics
+        .http_requests
+        .with_label_values(&[&method, &route, &status])
+        .inc();
+    state
+        .metrics
+        .http_request_duration
+        .with_label_values(&[&method, &route])
+        .observe(elapsed);
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_contains_recorded_metrics() {
+        let metrics = Metrics::new();
+        metrics.record_login("success");
+        metrics.record_login("success");
+        metrics.record_login("failure");
+        metrics.set_store_size("users", 3);
+
+        let output = metrics.render();
+
+        assert!(output.contains("logins_total{result=\"success\"} 2"));
+        assert!(output.contains("logins_total{result=\"failure\"} 1"));
+        assert!(output.contains("store_entries{store=\"users\"} 3"));
+    }
+}
@@ -0,0 +1,12 @@
+pub mod audit;
+pub mod auth;
+pub mod constants;
+pub mod cookies;
+pub mod cors;
+pub mod csrf;
+pub mod ldap;
+pub mod magic_link;
+pub mod metrics;
+pub mod oidc;
+pub mod revocations;
+pub mod tenant;
@@ -0,0 +1,355 @@
+use std::{collections::BTreeMap, time::Duration};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use jsonwebtoken::{
+    decode, decode_header, encode, jwk::JwkSet, Algorithm, DecodingKey, Validation,
+};
+use rand::RngCore;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::domain::{
+    identity_provider::{IdentityProvider, ProviderId},
+    tenant::{Tenant, TenantId},
+};
+
+use super::{
+    auth::GenerateTokenError,
+    constants::{OIDC_HTTP_TIMEOUT_SECONDS, OIDC_LOGIN_AUDIENCE, OIDC_LOGIN_TTL_SECONDS},
+};
+
+// Signatures we accept on ID tokens. Only asymmetric ones: the provider's keys
+// are fetched from its JWKS, never shared with us.
+const ID_TOKEN_ALGORITHMS: [Algorithm; 9] = [
+    Algorithm::RS256,
+    Algorithm::RS384,
+    Algorithm::RS512,
+    Algorithm::PS256,
+    Algorithm::PS384,
+    Algorithm::PS512,
+    Algorithm::ES256,
+    Algorithm::ES384,
+    Algorithm::EdDSA,
+];
+
+// The upstream identity providers users can log in with, and where they send
+// users back to: the login page, which posts what it receives to
+// `/login/oidc/callback`. Each provider must list the redirect URI of every
+// tenant that uses it, see `redirect_uri`.
+#[derive(Debug, Clone)]
+pub struct OidcConfig {
+    redirect_base_url: String,
+    providers: BTreeMap<ProviderId, IdentityProvider>,
+    http_client: reqwest::Client,
+}
+
+impl OidcConfig {
+    // `redirect_base_url` is where users reach auth-service, e.g. `https://auth.example.com`
+    pub fn new(redirect_base_url: &str, providers: Vec<IdentityProvider>) -> Result<Self, String> {
+        if !redirect_base_url.starts_with("http://") && !redirect_base_url.starts_with("https://") {
+            return Err(format!("OIDC redirect base URL must be http(s): {}", redirect_base_url));
+        }
+
+        let mut registry = BTreeMap::new();
+        for provider in providers {
+            if registry.contains_key(provider.id()) {
+                return Err(format!("Duplicate identity provider {}", provider.id()));
+            }
+            registry.insert(provider.id().clone(), provider);
+        }
+
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(OIDC_HTTP_TIMEOUT_SECONDS))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+        Ok(Self {
+            redirect_base_url: redirect_base_url.trim_end_matches('/').to_owned(),
+            providers: registry,
+            http_client,
+        })
+    }
+
+    pub fn provider(&self, id: &ProviderId) -> Option<&IdentityProvider> {
+        self.providers.get(id)
+    }
+
+    pub fn providers(&self) -> impl Iterator<Item = &IdentityProvider> {
+        self.providers.values()
+    }
+
+    // Like login links, the login page of other tenants than the default one
+    // names the tenant
+    pub fn redirect_uri(&self, tenant: &TenantId) -> String {
+        if tenant.is_default() {
+            format!("{}/", self.redirect_base_url)
+        } else {
+            format!("{}/?tenant={}", self.redirect_base_url, tenant)
+        }
+    }
+
+    // Where to send the browser to log in at `provider`, and the login state
+    // to keep in the `oidc_login` cookie until it comes back
+    pub fn start_login(
+        &self,
+        provider: &IdentityProvider,
+        tenant: &Tenant,
+    ) -> Result<(String, String), GenerateTokenError> {
+        let iat: usize = Utc::now()
+            .timestamp()
+            .try_into()
+            .map_err(|_| GenerateTokenError::UnexpectedError)?;
+        let login = OidcLoginClaims {
+            aud: OIDC_LOGIN_AUDIENCE.to_owned(),
+            exp: iat + OIDC_LOGIN_TTL_SECONDS as usize,
+            iat,
+            provider: provider.id().as_ref().to_owned(),
+            state: random_token(),
+            nonce: random_token(),
+            code_verifier: random_token(),
+        };
+
+        let url = Url::parse_with_params(
+            provider.authorization_endpoint(),
+            &[
+                ("response_type", "code"),
+                ("client_id", provider.client_id()),
+                ("redirect_uri", &self.redirect_uri(tenant.id())),
+                ("scope", &provider.scopes().join(" ")),
+                ("state", &login.state),
+                ("nonce", &login.nonce),
+                ("code_challenge", &code_challenge(&login.code_verifier)),
+                ("code_challenge_method", "S256"),
+            ],
+        )
+        .map_err(|_| GenerateTokenError::UnexpectedError)?;
+
+        let token = encode(&jsonwebtoken::Header::default(), &login, &tenant.encoding_key())
+            .map_err(GenerateTokenError::TokenError)?;
+
+        Ok((url.into(), token))
+    }
+
+    // Redeems the authorization code and checks the ID token that comes with
+    // it. On failure, says why, for the audit log only.
+    pub async fn fetch_identity(
+        &self,
+        provider: &IdentityProvider,
+        tenant: &TenantId,
+        login: &OidcLoginClaims,
+        code: &str,
+    ) -> Result<UpstreamIdentity, String> {
+        let response = self
+            .http_client
+            .post(provider.token_endpoint())
+            .basic_auth(provider.client_id(), Some(provider.client_secret()))
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &self.redirect_uri(tenant)),
+                ("code_verifier", &login.code_verifier),
+            ])
+            .send()
+            .await
+            .map_err(|_| "Token endpoint unreachable".to_owned())?;
+        if !response.status().is_success() {
+            return Err(format!("Token endpoint answered {}", response.status()));
+        }
+        let id_token = response
+            .json::<TokenEndpointResponse>()
+            .await
+            .map_err(|_| "No ID token in the token response".to_owned())?
+            .id_token;
+
+        let claims = self.validate_id_token(provider, &id_token).await?;
+        if claims.nonce.as_deref() != Some(login.nonce.as_str()) {
+            return Err("ID token nonce mismatch".to_owned());
+        }
+
+        Ok(UpstreamIdentity {
+            subject: claims.sub,
+            email: claims.email,
+            email_verified: claims.email_verified,
+        })
+    }
+
+    // Keys rotate, so the provider's JWKS is fetched for every login
+    async fn validate_id_token(
+        &self,
+        provider: &IdentityProvider,
+        id_token: &str,
+    ) -> Result<IdTokenClaims, String> {
+        let header = decode_header(id_token).map_err(|_| "Malformed ID token".to_owned())?;
+        if !ID_TOKEN_ALGORITHMS.contains(&header.alg) {
+            return Err(format!("ID token signed with {:?}", header.alg));
+        }
+
+        let jwks = self
+            .http_client
+            .get(provider.jwks_uri())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|_| "JWKS unreachable".to_owned())?
+            .json::<JwkSet>()
+            .await
+            .map_err(|_| "Malformed JWKS".to_owned())?;
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None if jwks.keys.len() == 1 => jwks.keys.first(),
+            None => None,
+        }
+        .ok_or_else(|| "No key for the ID token".to_owned())?;
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| "Unusable JWK".to_owned())?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[provider.issuer()]);
+        validation.set_audience(&[provider.client_id()]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| format!("Invalid ID token: {}", e))
+    }
+}
+
+impl Default for OidcConfig {
+    fn default() -> Self {
+        Self::new("http://localhost:3000", Vec::new()).unwrap()
+    }
+}
+
+// The state of a login at an identity provider, kept in a cookie signed with
+// the tenant's key. `state` ties the callback to this browser, `nonce` the ID
+// token to this login, and `code_verifier` (PKCE) the authorization code.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct OidcLoginClaims {
+    pub aud: String,
+    pub exp: usize,
+    pub iat: usize,
+    pub provider: String,
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+}
+
+pub fn validate_login_state(
+    token: &str,
+    tenant: &Tenant,
+) -> Result<OidcLoginClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[OIDC_LOGIN_AUDIENCE]);
+    validation.set_required_spec_claims(&["exp", "aud"]);
+
+    decode::<OidcLoginClaims>(token, &tenant.decoding_key(), &validation).map(|data| data.claims)
+}
+
+// Who the user is at the identity provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpstreamIdentity {
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+}
+
+#[derive(Deserialize)]
+struct TokenEndpointResponse {
+    id_token: String,
+}
+
+#[derive(Deserialize)]
+struct IdTokenClaims {
+    sub: String,
+    nonce: Option<String>,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+}
+
+fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// RFC 7636 `S256`: the unpadded base64url SHA-256 of the verifier
+fn code_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{identity_provider::parse_identity_providers, tenant::TenantRegistry};
+
+    fn config() -> OidcConfig {
+        let providers = parse_identity_providers(
+            r#"[{"id": "corp", "name": "Corp SSO", "issuer": "https://idp.example.com",
+                "authorizationEndpoint": "https://idp.example.com/authorize?prompt=login",
+                "tokenEndpoint": "https://idp.example.com/token", "jwksUri": "https://idp.example.com/jwks",
+                "clientId": "auth-service", "clientSecret": "secret"}]"#,
+        )
+        .unwrap();
+        OidcConfig::new("https://auth.example.com/", providers).unwrap()
+    }
+
+    fn tenant() -> Tenant {
+        TenantRegistry::default().default_tenant().clone()
+    }
+
+    #[test]
+    fn test_code_challenge() {
+        // The example of RFC 7636, appendix B
+        assert_eq!(
+            code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_redirect_uri_names_the_tenant() {
+        let config = config();
+        assert_eq!(config.redirect_uri(&TenantId::default()), "https://auth.example.com/");
+        assert_eq!(
+            config.redirect_uri(&TenantId::parse("acme".to_owned()).unwrap()),
+            "https://auth.example.com/?tenant=acme"
+        );
+    }
+
+    #[test]
+    fn test_start_login() {
+        let config = config();
+        let provider = config.provider(&ProviderId::parse("corp".to_owned()).unwrap()).unwrap();
+        let (url, token) = config.start_login(provider, &tenant()).unwrap();
+
+        let login = validate_login_state(&token, &tenant()).unwrap();
+        assert_eq!(login.provider, "corp");
+
+        let url = Url::parse(&url).unwrap();
+        let params: BTreeMap<_, _> = url.query_pairs().into_owned().collect();
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(params["prompt"], "login");
+        assert_eq!(params["client_id"], "auth-service");
+        assert_eq!(params["redirect_uri"], "https://auth.example.com/");
+        assert_eq!(params["scope"], "openid email profile");
+        assert_eq!(params["state"], login.state);
+        assert_eq!(params["nonce"], login.nonce);
+        assert_eq!(params["code_challenge"], code_challenge(&login.code_verifier));
+        assert_ne!(login.state, login.nonce);
+    }
+
+    #[test]
+    fn test_login_state_only_works_for_its_tenant() {
+        let config = config();
+        let provider = config.provider(&ProviderId::parse("corp".to_owned()).unwrap()).unwrap();
+        let (_, token) = config.start_login(provider, &tenant()).unwrap();
+
+        let acme = Tenant::new(
+            TenantId::parse("acme".to_owned()).unwrap(),
+            "acme-secret-with-plenty-of-entropy".to_owned(),
+        );
+        assert!(validate_login_state(&token, &acme).is_err());
+    }
+}
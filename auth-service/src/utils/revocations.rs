@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use auth_sdk::RevocationRequest;
+
+use super::{auth::generate_own_service_token, constants::REVOCATION_WEBHOOK_TIMEOUT_SECONDS};
+
+// The services told about tokens that are banned before they expire, so that
+// they stop accepting tokens they cached as valid, e.g. app-service's
+// `POST /revocations`. Each call carries a service token of auth-service.
+#[derive(Debug, Clone)]
+pub struct RevocationNotifier {
+    urls: Vec<String>,
+    http_client: reqwest::Client,
+}
+
+impl RevocationNotifier {
+    pub fn new(urls: Vec<String>) -> Result<Self, String> {
+        for url in &urls {
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(format!("Revocation webhook URL must be http(s): {}", url));
+            }
+        }
+
+        let http_client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(REVOCATION_WEBHOOK_TIMEOUT_SECONDS))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+        Ok(Self { urls, http_client })
+    }
+
+    // Pushes `token` in the background; the token is banned here already, so
+    // a service that misses the push only accepts it until its cache expires
+    pub fn notify(&self, token: &str) {
+        if self.urls.is_empty() {
+            return;
+        }
+
+        let service_token = match generate_own_service_token() {
+            Ok(service_token) => service_token,
+            Err(e) => {
+                eprintln!("Failed to push revoked token: {:?}", e);
+                return;
+            }
+        };
+        let request = RevocationRequest {
+            token: token.to_owned(),
+        };
+
+        for url in self.urls.clone() {
+            let call = self
+                .http_client
+                .post(&url)
+                .bearer_auth(&service_token)
+                .json(&request);
+            tokio::spawn(async move {
+                match call.send().await.and_then(|response| response.error_for_status()) {
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Failed to push revoked token to {}: {}", url, e),
+                }
+            });
+        }
+    }
+}
+
+impl Default for RevocationNotifier {
+    fn default() -> Self {
+        Self::new(Vec::new()).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_urls_that_are_not_http() {
+        assert!(RevocationNotifier::new(vec!["https://app.example.com/revocations".to_owned()]).is_ok());
+        assert!(RevocationNotifier::new(vec!["app.example.com/revocations".to_owned()]).is_err());
+    }
+}
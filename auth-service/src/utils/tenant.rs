@@ -0,0 +1,45 @@
+use axum::extract::{rejection::RawPathParamsRejection, FromRequestParts, RawPathParams};
+use axum::http::request::Parts;
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        error::AuthAPIError,
+        tenant::{Tenant, TenantId},
+    },
+};
+
+// Tenant-scoped routes are served both as they are, for the default tenant, and
+// under `/t/{tenant}` for the others. Handlers take this extractor to learn
+// which tenant a request is for; unknown tenants get a 404.
+impl FromRequestParts<AppState> for Tenant {
+    type Rejection = AuthAPIError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let Some(id) = requested_tenant(parts, state).await else {
+            return Ok(state.tenants.default_tenant().clone());
+        };
+
+        TenantId::parse(id)
+            .ok()
+            .and_then(|id| state.tenants.get(&id))
+            .cloned()
+            .ok_or(AuthAPIError::TenantNotFound)
+    }
+}
+
+// The `{tenant}` segment of the matched route, if it has one. A segment that is
+// not valid UTF-8 names no tenant at all, rather than the default one.
+pub async fn requested_tenant<S>(parts: &mut Parts, state: &S) -> Option<String>
+where
+    S: Send + Sync,
+{
+    match RawPathParams::from_request_parts(parts, state).await {
+        Ok(params) => params
+            .iter()
+            .find(|(name, _)| *name == "tenant")
+            .map(|(_, value)| value.to_owned()),
+        Err(RawPathParamsRejection::MissingPathParams(_)) => None,
+        Err(_) => Some(String::new()),
+    }
+}
@@ -0,0 +1,280 @@
+use auth_service::{
+    domain::api_key::{ApiKeyId, ApiKeySecret},
+    routes::{ApiKeyResponse, CreateApiKeyResponse},
+    utils::{auth::Claims, constants::JWT_COOKIE_NAME},
+    ErrorResponse,
+};
+
+use crate::helpers::{get_random_email, TestApp};
+
+async fn create_key(app: &TestApp) -> CreateApiKeyResponse {
+    let response = app
+        .post_api_key(&serde_json::json!({
+            "name": "ci",
+            "scopes": ["user"],
+            "expiresInDays": 30
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    response
+        .json::<CreateApiKeyResponse>()
+        .await
+        .expect("Could not deserialize response body to CreateApiKeyResponse")
+}
+
+#[tokio::test]
+async fn should_return_400_if_not_logged_in() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_api_key(&serde_json::json!({
+            "name": "ci",
+            "scopes": [],
+            "expiresInDays": 30
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.get_api_keys().await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+
+    let test_cases = [
+        serde_json::json!({
+            "scopes": ["user"],
+            "expiresInDays": 30
+        }),
+        serde_json::json!({
+            "name": "ci",
+            "expiresInDays": 30
+        }),
+        serde_json::json!({
+            "name": "ci",
+            "scopes": ["user"],
+            "expiresInDays": -1
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_api_key(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            422,
+            "Failed for input: {:?}",
+            test_case
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_input() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+
+    let test_cases = [
+        serde_json::json!({
+            "name": " ",
+            "scopes": ["user"],
+            "expiresInDays": 30
+        }),
+        serde_json::json!({
+            "name": "ci",
+            "scopes": ["user"],
+            "expiresInDays": 0
+        }),
+        serde_json::json!({
+            "name": "ci",
+            "scopes": ["user"],
+            "expiresInDays": 10_000
+        }),
+        serde_json::json!({
+            "name": "ci",
+            "scopes": ["not a role"],
+            "expiresInDays": 30
+        }),
+        // A regular user cannot hand out the admin role
+        serde_json::json!({
+            "name": "ci",
+            "scopes": ["admin"],
+            "expiresInDays": 30
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_api_key(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "Failed for input: {:?}",
+            test_case
+        );
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            "Invalid API key request".to_owned()
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_show_secret_once_and_list_keys_without_it() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+
+    let created = create_key(&app).await;
+    assert!(created.secret.starts_with("lgr_"));
+    assert_eq!(created.key.name, "ci");
+    assert_eq!(created.key.scopes, vec!["user".to_owned()]);
+    assert_eq!(created.key.last_used_at, None);
+
+    let response = app.get_api_keys().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let body = response.text().await.unwrap();
+    assert!(!body.contains(&created.secret));
+
+    let keys: Vec<ApiKeyResponse> = serde_json::from_str(&body).unwrap();
+    assert_eq!(keys, vec![created.key.clone()]);
+
+    let (id, secret) = ApiKeySecret::parse(created.secret.clone()).unwrap();
+    assert_eq!(id, ApiKeyId::parse(created.key.id).unwrap());
+
+    let stored = app.api_key_store.read().await.get_key(&id).await.unwrap();
+    assert!(stored.matches(&secret));
+    assert!(!format!("{:?}", stored).contains(&created.secret));
+}
+
+#[tokio::test]
+async fn should_verify_api_key_and_record_last_use() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    app.signup_and_login(&random_email, false).await;
+
+    let created = create_key(&app).await;
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": created.secret }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let claims = response
+        .json::<Claims>()
+        .await
+        .expect("Could not deserialize response body to Claims");
+    assert_eq!(claims.sub, random_email);
+    assert_eq!(claims.roles, vec!["user".to_owned()]);
+    assert_eq!(claims.exp as i64, created.key.expires_at.timestamp());
+
+    let keys = app
+        .get_api_keys()
+        .await
+        .json::<Vec<ApiKeyResponse>>()
+        .await
+        .unwrap();
+    assert!(keys[0].last_used_at.is_some());
+}
+
+#[tokio::test]
+async fn should_reject_revoked_api_key() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+
+    let created = create_key(&app).await;
+
+    let response = app.delete_api_key(&created.key.id).await;
+    assert_eq!(response.status().as_u16(), 204);
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": created.secret }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let keys = app
+        .get_api_keys()
+        .await
+        .json::<Vec<ApiKeyResponse>>()
+        .await
+        .unwrap();
+    assert!(keys.is_empty());
+
+    let response = app.delete_api_key(&created.key.id).await;
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn should_not_revoke_keys_of_other_users() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+    let created = create_key(&app).await;
+
+    // Log in as somebody else in the same cookie jar
+    app.signup_and_login(&get_random_email(), false).await;
+
+    let response = app.delete_api_key(&created.key.id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": created.secret }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_not_accept_api_key_for_key_management() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+    let created = create_key(&app).await;
+
+    let response = app
+        .http_client
+        .get(format!("{}/api-keys", &app.address))
+        .header("Cookie", format!("jwt={}", created.secret))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/api-keys", &app.address))
+        .bearer_auth(&created.secret)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_accept_session_token_as_bearer_for_key_management() {
+    let app = TestApp::new().await;
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+    let created = create_key(&app).await;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/api-keys", &app.address))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let keys = response
+        .json::<Vec<ApiKeyResponse>>()
+        .await
+        .expect("Could not deserialize response body to API keys");
+    assert_eq!(keys, vec![created.key]);
+}
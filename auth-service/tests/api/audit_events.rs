@@ -0,0 +1,190 @@
+use std::collections::BTreeSet;
+
+use auth_service::{
+    domain::{
+        audit::{AuditEvent, AuditEventType, AuditOutcome, AuditQuery},
+        email::Email,
+        role::Role,
+        tenant::TenantId,
+    },
+    ErrorResponse,
+};
+
+use crate::helpers::{get_random_email, TestApp, TEST_PASSWORD};
+
+// Sign up a user, grant it the admin role and log in, so the roles end up in the cookie
+async fn login_as_admin(app: &TestApp) -> String {
+    let email = get_random_email();
+
+    let signup_body = serde_json::json!({
+        "email": email,
+        "password": TEST_PASSWORD,
+        "requires2FA": false
+    });
+    app.post_signup(&signup_body).await;
+
+    app.user_store
+        .write()
+        .await
+        .set_roles(
+            &TenantId::default(),
+            &Email::parse(email.clone()).unwrap(),
+            BTreeSet::from([Role::user(), Role::admin()]),
+        )
+        .await
+        .unwrap();
+
+    let response = app
+        .post_login(&serde_json::json!({
+            "email": email,
+            "password": TEST_PASSWORD,
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    email
+}
+
+async fn events_for(app: &TestApp, email: &str) -> Vec<AuditEvent> {
+    app.audit_sink
+        .query(&AuditQuery {
+            email: Some(email.to_owned()),
+            limit: 100,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn should_record_signup_and_login_with_request_context() {
+    let app = TestApp::new().await;
+    let email = get_random_email();
+
+    let response = app.signup_and_login(&email, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let events = events_for(&app, &email).await;
+    let kinds: Vec<_> = events.iter().map(|e| e.event_type).collect();
+    assert_eq!(kinds, vec![AuditEventType::Login, AuditEventType::Signup]);
+
+    for event in &events {
+        assert_eq!(event.outcome, AuditOutcome::Success);
+        assert_eq!(event.ip.as_deref(), Some("127.0.0.1"));
+        assert!(event.request_id.is_some());
+    }
+}
+
+#[tokio::test]
+async fn should_record_failed_logins() {
+    let app = TestApp::new().await;
+    let email = get_random_email();
+    app.signup_and_login(&email, false).await;
+
+    let response = app
+        .post_login(&serde_json::json!({
+            "email": email,
+            "password": "wrong-password",
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let events = events_for(&app, &email).await;
+    let latest = events.first().expect("No audit events recorded");
+    assert_eq!(latest.event_type, AuditEventType::Login);
+    assert_eq!(latest.outcome, AuditOutcome::Failure);
+    assert_eq!(latest.detail.as_deref(), Some("Incorrect credentials"));
+}
+
+#[tokio::test]
+async fn should_echo_and_record_caller_request_id() {
+    let app = TestApp::new().await;
+    let email = get_random_email();
+
+    let response = app
+        .http_client
+        .post(format!("{}/signup", &app.address))
+        .header("x-request-id", "trace-me")
+        .json(&serde_json::json!({
+            "email": email,
+            "password": TEST_PASSWORD,
+            "requires2FA": false
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(
+        response.headers().get("x-request-id").unwrap().to_str().unwrap(),
+        "trace-me"
+    );
+
+    let events = events_for(&app, &email).await;
+    assert_eq!(events[0].request_id.as_deref(), Some("trace-me"));
+}
+
+#[tokio::test]
+async fn should_return_403_if_not_admin() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+
+    let response = app.get_audit_events(&[]).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Forbidden".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn should_return_400_if_not_logged_in() {
+    let app = TestApp::new().await;
+
+    let response = app.get_audit_events(&[]).await;
+    assert_eq!(response.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn should_let_admins_query_by_email_and_time_range() {
+    let app = TestApp::new().await;
+    let admin = login_as_admin(&app).await;
+
+    let email = get_random_email();
+    let before = chrono::Utc::now();
+    app.post_signup(&serde_json::json!({
+        "email": email,
+        "password": TEST_PASSWORD,
+        "requires2FA": false
+    }))
+    .await;
+
+    let response = app.get_audit_events(&[("email", email.as_str())]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let events = response
+        .json::<Vec<AuditEvent>>()
+        .await
+        .expect("Could not deserialize response body to audit events");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, AuditEventType::Signup);
+
+    let before = before.to_rfc3339();
+    let response = app
+        .get_audit_events(&[("email", email.as_str()), ("to", before.as_str())])
+        .await;
+    let events = response
+        .json::<Vec<AuditEvent>>()
+        .await
+        .expect("Could not deserialize response body to audit events");
+    assert!(events.is_empty());
+
+    // The query itself ends up in the log
+    let events = events_for(&app, &admin).await;
+    assert_eq!(
+        events[0].event_type,
+        AuditEventType::AuditEventsQueried
+    );
+}
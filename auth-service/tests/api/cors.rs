@@ -0,0 +1,87 @@
+use reqwest::{header, Method};
+
+use crate::helpers::{TestApp, TEST_TRUSTED_ORIGIN};
+
+async fn preflight(app: &TestApp, origin: &str, method: &str, headers: &str) -> reqwest::Response {
+    app.http_client
+        .request(Method::OPTIONS, format!("{}/logout", &app.address))
+        .header(header::ORIGIN, origin)
+        .header(header::ACCESS_CONTROL_REQUEST_METHOD, method)
+        .header(header::ACCESS_CONTROL_REQUEST_HEADERS, headers)
+        .send()
+        .await
+        .expect("Failed to execute request.")
+}
+
+fn header_value(response: &reqwest::Response, name: header::HeaderName) -> Option<&str> {
+    response.headers().get(name).and_then(|value| value.to_str().ok())
+}
+
+#[tokio::test]
+async fn should_answer_preflight_for_allowed_origin() {
+    let app = TestApp::new().await;
+
+    let response = preflight(&app, TEST_TRUSTED_ORIGIN, "POST", "content-type,x-csrf-token").await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        Some(TEST_TRUSTED_ORIGIN)
+    );
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+        Some("true")
+    );
+
+    let methods = header_value(&response, header::ACCESS_CONTROL_ALLOW_METHODS).unwrap();
+    assert!(methods.contains("POST"));
+    let headers = header_value(&response, header::ACCESS_CONTROL_ALLOW_HEADERS).unwrap();
+    assert!(headers.contains("content-type"));
+    assert!(headers.contains("x-csrf-token"));
+    assert!(header_value(&response, header::ACCESS_CONTROL_MAX_AGE).is_some());
+}
+
+#[tokio::test]
+async fn should_not_allow_unlisted_origin() {
+    let app = TestApp::new().await;
+
+    let response = preflight(&app, "http://evil.example", "POST", "content-type").await;
+
+    // Without `Access-Control-Allow-Origin` the browser refuses the actual request
+    assert!(header_value(&response, header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+}
+
+#[tokio::test]
+async fn should_add_cors_headers_to_actual_requests() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .http_client
+        .get(format!("{}/health/live", &app.address))
+        .header(header::ORIGIN, TEST_TRUSTED_ORIGIN)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_ALLOW_ORIGIN),
+        Some(TEST_TRUSTED_ORIGIN)
+    );
+    assert_eq!(
+        header_value(&response, header::ACCESS_CONTROL_ALLOW_CREDENTIALS),
+        Some("true")
+    );
+    let exposed = header_value(&response, header::ACCESS_CONTROL_EXPOSE_HEADERS).unwrap();
+    assert!(exposed.contains("x-request-id"));
+
+    let response = app
+        .http_client
+        .get(format!("{}/health/live", &app.address))
+        .header(header::ORIGIN, "http://evil.example")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(header_value(&response, header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+}
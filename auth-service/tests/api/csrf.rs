@@ -0,0 +1,165 @@
+use auth_service::{
+    utils::constants::{CSRF_COOKIE_NAME, CSRF_HEADER_NAME},
+    ErrorResponse,
+};
+
+use crate::helpers::{get_random_email, TestApp, TEST_PASSWORD, TEST_TRUSTED_ORIGIN};
+
+async fn assert_csrf_rejected(response: reqwest::Response) {
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "CSRF check failed".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn should_hand_out_csrf_cookie() {
+    let app = TestApp::new().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == CSRF_COOKIE_NAME)
+        .expect("No csrf cookie found");
+    assert!(!cookie.value().is_empty());
+    assert!(!cookie.http_only());
+    assert!(cookie.same_site_strict());
+}
+
+#[tokio::test]
+async fn should_reject_cookie_authenticated_request_without_csrf_token() {
+    let app = TestApp::new().await;
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .http_client
+        .post(format!("{}/logout", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_csrf_rejected(response).await;
+
+    let response = app
+        .http_client
+        .post(format!("{}/logout", &app.address))
+        .header(CSRF_HEADER_NAME, "not-the-token")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_csrf_rejected(response).await;
+
+    // The session survived the forged requests
+    let response = app.post_logout().await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_reject_cross_site_requests() {
+    let app = TestApp::new().await;
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = app.csrf_token().expect("No csrf cookie found");
+
+    let response = app
+        .http_client
+        .post(format!("{}/logout", &app.address))
+        .header(CSRF_HEADER_NAME, &token)
+        .header("sec-fetch-site", "cross-site")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_csrf_rejected(response).await;
+
+    let response = app
+        .http_client
+        .post(format!("{}/logout", &app.address))
+        .header(CSRF_HEADER_NAME, &token)
+        .header("origin", "http://evil.example")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_csrf_rejected(response).await;
+}
+
+#[tokio::test]
+async fn should_reject_foreign_origin_without_cookies() {
+    let app = TestApp::new().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/signup", &app.address))
+        .header("origin", "http://evil.example")
+        .json(&serde_json::json!({
+            "email": get_random_email(),
+            "password": TEST_PASSWORD,
+            "requires2FA": false
+        }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_csrf_rejected(response).await;
+}
+
+#[tokio::test]
+async fn should_allow_same_and_trusted_origins() {
+    let app = TestApp::new().await;
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = app.csrf_token().expect("No csrf cookie found");
+
+    let same_origin = app.address.clone();
+    let response = app
+        .http_client
+        .post(format!("{}/api-keys", &app.address))
+        .header(CSRF_HEADER_NAME, &token)
+        .header("origin", &same_origin)
+        .header("sec-fetch-site", "same-origin")
+        .json(&serde_json::json!({ "name": "ci", "scopes": [], "expiresInDays": 30 }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app
+        .http_client
+        .post(format!("{}/logout", &app.address))
+        .header(CSRF_HEADER_NAME, &token)
+        .header("origin", TEST_TRUSTED_ORIGIN)
+        .header("sec-fetch-site", "cross-site")
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_not_affect_bearer_authenticated_requests() {
+    let app = TestApp::new().await;
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // The session cookie is sent along, but the service authenticates with its token
+    let response = app
+        .http_client
+        .post(format!("{}/verify-token", &app.address))
+        .bearer_auth(&app.service_token)
+        .json(&serde_json::json!({ "token": "invalid" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 401);
+}
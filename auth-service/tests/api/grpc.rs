@@ -0,0 +1,303 @@
+use auth_service::{
+    grpc::proto::{GetUserRequest, RevokeTokenRequest, VerifyTokenRequest},
+    routes::{CreateApiKeyResponse, TokenResponse},
+    utils::constants::JWT_COOKIE_NAME,
+};
+use tonic::{transport::Channel, Code, Request};
+use tonic_reflection::pb::v1::{
+    server_reflection_client::ServerReflectionClient,
+    server_reflection_request::MessageRequest, server_reflection_response::MessageResponse,
+    ServerReflectionRequest,
+};
+
+use crate::helpers::{get_random_email, TestApp, TEST_OTHER_CLIENT_ID, TEST_OTHER_CLIENT_SECRET};
+
+async fn login_and_get_token(app: &TestApp, email: &str) -> String {
+    let response = app.signup_and_login(email, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+    token
+}
+
+// Calls carry the service token in their metadata, as `/verify-token` requests do in a header
+fn with_service_token<T>(app: &TestApp, message: T) -> Request<T> {
+    let mut request = Request::new(message);
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", app.service_token).parse().unwrap(),
+    );
+    request
+}
+
+#[tokio::test]
+async fn should_verify_a_session_token() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let token = login_and_get_token(&app, &random_email).await;
+
+    let response = app
+        .grpc_client()
+        .await
+        .verify_token(with_service_token(&app, VerifyTokenRequest { token }))
+        .await
+        .expect("Verification failed");
+
+    let claims = response.into_inner().claims.expect("No claims");
+    assert_eq!(claims.sub, random_email);
+    assert_eq!(claims.roles, vec!["user".to_owned()]);
+    assert!(claims.exp > claims.iat);
+}
+
+#[tokio::test]
+async fn should_reject_invalid_tokens() {
+    let app = TestApp::new().await;
+    let mut client = app.grpc_client().await;
+
+    let test_cases = [("", Code::InvalidArgument), ("invalid", Code::Unauthenticated)];
+
+    for (token, code) in test_cases {
+        let status = client
+            .verify_token(with_service_token(
+                &app,
+                VerifyTokenRequest {
+                    token: token.to_owned(),
+                },
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), code, "Failed for token: {:?}", token);
+    }
+}
+
+#[tokio::test]
+async fn should_require_a_service_token() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let token = login_and_get_token(&app, &random_email).await;
+    let mut client = app.grpc_client().await;
+
+    // A user token does not make the caller a service
+    let mut request = Request::new(VerifyTokenRequest {
+        token: token.clone(),
+    });
+    request
+        .metadata_mut()
+        .insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    let status = client.verify_token(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    assert_eq!(status.message(), "Service authentication required");
+
+    let status = client
+        .get_user(GetUserRequest {
+            email: random_email.clone(),
+            tenant: String::new(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    let status = client
+        .revoke_token(RevokeTokenRequest { token })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn should_get_a_user() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    app.signup_and_login(&random_email, false).await;
+    let mut client = app.grpc_client().await;
+
+    let user = client
+        .get_user(with_service_token(
+            &app,
+            GetUserRequest {
+                email: random_email.clone(),
+                tenant: String::new(),
+            },
+        ))
+        .await
+        .expect("Lookup failed")
+        .into_inner()
+        .user
+        .expect("No user");
+    assert_eq!(user.email, random_email);
+    assert_eq!(user.roles, vec!["user".to_owned()]);
+    assert!(!user.requires_2fa);
+
+    let status = client
+        .get_user(with_service_token(
+            &app,
+            GetUserRequest {
+                email: get_random_email(),
+                tenant: String::new(),
+            },
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn should_revoke_a_session_token() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let token = login_and_get_token(&app, &random_email).await;
+    let mut client = app.grpc_client().await;
+
+    client
+        .revoke_token(with_service_token(
+            &app,
+            RevokeTokenRequest {
+                token: token.clone(),
+            },
+        ))
+        .await
+        .expect("Revocation failed");
+
+    let status = client
+        .verify_token(with_service_token(
+            &app,
+            VerifyTokenRequest {
+                token: token.clone(),
+            },
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    // The HTTP API shares the banned token store
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_revoke_an_api_key() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    app.signup_and_login(&random_email, false).await;
+    let created = app
+        .post_api_key(&serde_json::json!({
+            "name": "ci",
+            "scopes": [],
+            "expiresInDays": 1
+        }))
+        .await
+        .json::<CreateApiKeyResponse>()
+        .await
+        .unwrap();
+    let mut client = app.grpc_client().await;
+
+    client
+        .revoke_token(with_service_token(
+            &app,
+            RevokeTokenRequest {
+                token: created.secret.clone(),
+            },
+        ))
+        .await
+        .expect("Revocation failed");
+
+    let status = client
+        .verify_token(with_service_token(
+            &app,
+            VerifyTokenRequest {
+                token: created.secret,
+            },
+        ))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn should_not_revoke_without_the_revoke_scope() {
+    let app = TestApp::new().await;
+    // Fetched before logging in, as `/token` refuses requests carrying a session cookie
+    let other_service_token = app
+        .post_token(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", TEST_OTHER_CLIENT_ID),
+            ("client_secret", TEST_OTHER_CLIENT_SECRET),
+        ])
+        .await
+        .json::<TokenResponse>()
+        .await
+        .unwrap()
+        .access_token;
+    let token = login_and_get_token(&app, &get_random_email()).await;
+    let mut client = app.grpc_client().await;
+
+    let mut request = Request::new(RevokeTokenRequest {
+        token: token.clone(),
+    });
+    request.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", other_service_token).parse().unwrap(),
+    );
+    client
+        .revoke_token(request)
+        .await
+        .expect("Revocation should be answered as done");
+
+    client
+        .verify_token(with_service_token(&app, VerifyTokenRequest { token }))
+        .await
+        .expect("The token should still be valid");
+}
+
+#[tokio::test]
+async fn should_answer_revocations_of_invalid_tokens_as_done() {
+    let app = TestApp::new().await;
+    let mut client = app.grpc_client().await;
+
+    client
+        .revoke_token(with_service_token(
+            &app,
+            RevokeTokenRequest {
+                token: "not-a-token".to_owned(),
+            },
+        ))
+        .await
+        .expect("Revocation should be answered as done");
+}
+
+#[tokio::test]
+async fn should_list_services_through_reflection() {
+    let app = TestApp::new().await;
+    let channel = Channel::from_shared(app.grpc_address.clone())
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = ServerReflectionClient::new(channel);
+
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    };
+    let mut responses = client
+        .server_reflection_info(tokio_stream::once(request))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let response = responses.message().await.unwrap().expect("No response");
+    let services: Vec<String> = match response.message_response {
+        Some(MessageResponse::ListServicesResponse(list)) => {
+            list.service.into_iter().map(|service| service.name).collect()
+        }
+        other => panic!("Expected a list of services, got {:?}", other),
+    };
+    assert!(services.contains(&"auth.v1.AuthService".to_owned()));
+}
@@ -0,0 +1,57 @@
+use auth_service::routes::{HealthResponse, HealthStatus};
+
+use crate::helpers::TestApp;
+
+#[tokio::test]
+async fn live_should_return_200() {
+    let app = TestApp::new().await;
+
+    let response = app.get_health("live").await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response
+        .json::<HealthResponse>()
+        .await
+        .expect("Could not deserialize response body to HealthResponse");
+    assert_eq!(body.status, HealthStatus::Up);
+}
+
+#[tokio::test]
+async fn ready_should_report_each_dependency() {
+    let app = TestApp::new().await;
+
+    let response = app.get_health("ready").await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response
+        .json::<HealthResponse>()
+        .await
+        .expect("Could not deserialize response body to HealthResponse");
+    assert_eq!(body.status, HealthStatus::Up);
+    for dependency in ["user_store", "banned_token_store", "email_client"] {
+        assert_eq!(body.checks[dependency].status, HealthStatus::Up);
+    }
+}
+
+#[tokio::test]
+async fn ready_should_return_503_if_a_dependency_does_not_answer() {
+    let app = TestApp::new().await;
+
+    // Holding the write lock makes the user store unreachable for the probe
+    let _guard = app.user_store.write().await;
+
+    let response = app.get_health("ready").await;
+
+    assert_eq!(response.status().as_u16(), 503);
+    let body = response
+        .json::<HealthResponse>()
+        .await
+        .expect("Could not deserialize response body to HealthResponse");
+    assert_eq!(body.status, HealthStatus::Down);
+    assert_eq!(body.checks["user_store"].status, HealthStatus::Down);
+    assert_eq!(body.checks["user_store"].error.as_deref(), Some("timed out"));
+    assert_eq!(body.checks["email_client"].status, HealthStatus::Up);
+
+    // Liveness does not depend on the stores
+    assert_eq!(app.get_health("live").await.status().as_u16(), 200);
+}
@@ -0,0 +1,255 @@
+use auth_service::{
+    domain::role::Role,
+    routes::{ApiKeyResponse, CreateApiKeyResponse, IntrospectResponse, SERVICE_TOKEN_TYPE},
+    utils::{
+        auth::Claims,
+        constants::{JWT_COOKIE_NAME, JWT_SECRET},
+    },
+    ErrorResponse,
+};
+use jsonwebtoken::{encode, EncodingKey, Header};
+
+use crate::helpers::{get_random_email, TestApp, TEST_CLIENT_ID, TEST_CLIENT_SCOPE};
+
+async fn login_and_get_token(app: &TestApp, email: &str) -> String {
+    let response = app.signup_and_login(email, false).await;
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+    token
+}
+
+#[tokio::test]
+async fn should_report_inactive_for_invalid_token() {
+    let app = TestApp::new().await;
+
+    for token in ["invalid", "lgr_invalid"] {
+        let response = app.post_introspect(&[("token", token)], None).await;
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(
+            response.json::<IntrospectResponse>().await.unwrap(),
+            IntrospectResponse::default()
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_describe_access_token() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+
+    let response = app.signup_and_login(&random_email, false).await;
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    let body = app
+        .post_introspect(&[("token", &token)], None)
+        .await
+        .json::<IntrospectResponse>()
+        .await
+        .unwrap();
+
+    assert!(body.active);
+    assert_eq!(body.sub, Some(random_email));
+    assert_eq!(body.scope, Some("user".to_owned()));
+    assert_eq!(body.token_type, Some("access_token".to_owned()));
+}
+
+#[tokio::test]
+async fn should_describe_api_key() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    app.signup_and_login(&random_email, false).await;
+
+    let created = app
+        .post_api_key(&serde_json::json!({
+            "name": "ci",
+            "scopes": [],
+            "expiresInDays": 1
+        }))
+        .await
+        .json::<CreateApiKeyResponse>()
+        .await
+        .unwrap();
+
+    let body = app
+        .post_introspect(&[("token", &created.secret)], None)
+        .await
+        .json::<IntrospectResponse>()
+        .await
+        .unwrap();
+
+    assert!(body.active);
+    assert_eq!(body.sub, Some(random_email));
+    assert_eq!(body.scope, Some("".to_owned()));
+    assert_eq!(body.token_type, Some("api_key".to_owned()));
+    assert_eq!(body.exp, Some(created.key.expires_at.timestamp() as usize));
+}
+
+#[tokio::test]
+async fn should_not_record_api_key_use() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+
+    let created = app
+        .post_api_key(&serde_json::json!({
+            "name": "ci",
+            "scopes": [],
+            "expiresInDays": 1
+        }))
+        .await
+        .json::<CreateApiKeyResponse>()
+        .await
+        .unwrap();
+
+    // Looking at a key is not using it
+    let response = app
+        .post_introspect(&[("token", &created.secret)], None)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let keys = app
+        .get_api_keys()
+        .await
+        .json::<Vec<ApiKeyResponse>>()
+        .await
+        .unwrap();
+    assert_eq!(keys[0].last_used_at, None);
+}
+
+#[tokio::test]
+async fn should_require_client_authentication() {
+    let app = TestApp::new().await;
+    let token = login_and_get_token(&app, &get_random_email()).await;
+
+    for credentials in [(TEST_CLIENT_ID, "wrong secret"), ("unknown", "secret")] {
+        let response = app
+            .post_introspect(&[("token", &token)], Some(credentials))
+            .await;
+        assert_eq!(response.status().as_u16(), 401);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.unwrap().error,
+            "invalid_client"
+        );
+    }
+
+    // A user token does not authenticate a client
+    let response = app
+        .http_client
+        .post(format!("{}/introspect", &app.address))
+        .bearer_auth(&token)
+        .form(&[("token", &token)])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_return_400_if_token_parameter_missing() {
+    let app = TestApp::new().await;
+
+    for form in [vec![], vec![("token", "")]] {
+        let response = app.post_introspect(&form, None).await;
+        assert_eq!(response.status().as_u16(), 400);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.unwrap().error,
+            "invalid_request"
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_describe_tokens_to_clients() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let token = login_and_get_token(&app, &random_email).await;
+
+    let body = app
+        .post_introspect(&[("token", &token), ("token_type_hint", "refresh_token")], None)
+        .await
+        .json::<IntrospectResponse>()
+        .await
+        .unwrap();
+    assert!(body.active);
+    assert_eq!(body.sub, Some(random_email));
+    assert_eq!(body.scope, Some("user".to_owned()));
+    assert_eq!(body.client_id, None);
+    assert_eq!(body.token_type, Some("access_token".to_owned()));
+    assert!(body.exp.unwrap() > body.iat.unwrap());
+
+    let body = app
+        .post_introspect(&[("token", &app.service_token)], None)
+        .await
+        .json::<IntrospectResponse>()
+        .await
+        .unwrap();
+    assert!(body.active);
+    assert_eq!(body.sub, Some(TEST_CLIENT_ID.to_owned()));
+    assert_eq!(body.client_id, Some(TEST_CLIENT_ID.to_owned()));
+    assert_eq!(body.scope, Some(format!("{} {}", Role::REVOKE, TEST_CLIENT_SCOPE)));
+    assert_eq!(body.token_type, Some(SERVICE_TOKEN_TYPE.to_owned()));
+}
+
+#[tokio::test]
+async fn should_find_tokens_whatever_the_hint() {
+    let app = TestApp::new().await;
+    let token = login_and_get_token(&app, &get_random_email()).await;
+
+    // The hint only says where to look first
+    for hint in ["access_token", "api_key", SERVICE_TOKEN_TYPE, "refresh_token"] {
+        for (token, token_type) in [
+            (token.as_str(), "access_token"),
+            (app.service_token.as_str(), SERVICE_TOKEN_TYPE),
+        ] {
+            let body = app
+                .post_introspect(&[("token", token), ("token_type_hint", hint)], None)
+                .await
+                .json::<IntrospectResponse>()
+                .await
+                .unwrap();
+            assert!(body.active, "Failed for hint: {}", hint);
+            assert_eq!(body.token_type.as_deref(), Some(token_type), "Failed for hint: {}", hint);
+        }
+    }
+}
+
+#[tokio::test]
+async fn should_report_banned_and_expired_tokens_as_inactive() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let token = login_and_get_token(&app, &random_email).await;
+    assert_eq!(app.post_logout().await.status().as_u16(), 200);
+
+    let now = chrono::Utc::now().timestamp() as usize;
+    let expired = encode(
+        &Header {
+            kid: Some("default".to_owned()),
+            ..Header::default()
+        },
+        &Claims {
+            sub: random_email,
+            exp: now - 3600,
+            iat: now - 7200,
+            roles: vec!["user".to_owned()],
+            iss: Some("auth-service/t/default".to_owned()),
+            tenant: Some("default".to_owned()),
+        },
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .unwrap();
+
+    for token in [token.as_str(), expired.as_str(), "lgr_invalid"] {
+        let response = app.post_introspect(&[("token", token)], None).await;
+        assert_eq!(response.status().as_u16(), 200);
+        // Nothing tells why the token is inactive
+        assert_eq!(response.text().await.unwrap(), r#"{"active":false}"#);
+    }
+}
@@ -0,0 +1,104 @@
+use auth_service::{
+    routes::JsonWebKeySet,
+    utils::{auth::Claims, constants::JWT_COOKIE_NAME},
+};
+use jsonwebtoken::{
+    decode, decode_header, encode, jwk::Jwk, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+
+use crate::helpers::{get_random_email, TestApp, TEST_PASSWORD, TEST_TENANT, TEST_TENANT_SECRET};
+
+async fn tenant_login(app: &TestApp, email: &str) -> String {
+    let signup = serde_json::json!({
+        "email": email,
+        "password": TEST_PASSWORD,
+        "requires2FA": false
+    });
+    let response = app.post_tenant(TEST_TENANT, "signup", &signup).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let login = serde_json::json!({ "email": email, "password": TEST_PASSWORD });
+    let response = app.post_tenant(TEST_TENANT, "login", &login).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+    token
+}
+
+#[tokio::test]
+async fn should_publish_the_key_that_signs_the_tenants_tokens() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let token = tenant_login(&app, &random_email).await;
+
+    let header = decode_header(&token).unwrap();
+    assert_eq!(header.alg, Algorithm::EdDSA);
+    assert_eq!(header.kid.as_deref(), Some(TEST_TENANT));
+
+    let response = app.get_tenant(TEST_TENANT, ".well-known/jwks.json").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let keys = response.json::<JsonWebKeySet>().await.unwrap().keys;
+    assert_eq!(keys.len(), 1);
+    assert_eq!(keys[0].kid, TEST_TENANT);
+
+    // What a service verifying tokens locally does
+    let jwk: Jwk = serde_json::from_value(serde_json::to_value(&keys[0]).unwrap()).unwrap();
+    let key = DecodingKey::from_jwk(&jwk).unwrap();
+    let mut validation = Validation::new(Algorithm::EdDSA);
+    validation.set_required_spec_claims(&["exp", "sub", "iss"]);
+    let claims = decode::<Claims>(&token, &key, &validation).unwrap().claims;
+    assert_eq!(claims.sub, random_email);
+    assert_eq!(claims.tenant.as_deref(), Some(TEST_TENANT));
+
+    // auth-service itself still accepts the token
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_publish_no_keys_for_tenants_signing_with_a_secret() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .http_client
+        .get(format!("{}/.well-known/jwks.json", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.json::<JsonWebKeySet>().await.unwrap().keys.is_empty());
+
+    let response = app.get_tenant("unknown", ".well-known/jwks.json").await;
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn should_reject_tokens_signed_with_the_secret_of_a_keyed_tenant() {
+    let app = TestApp::new().await;
+    let token = tenant_login(&app, &get_random_email()).await;
+
+    // The same claims, signed the way tenants without a key sign them
+    let claims = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await
+        .json::<Claims>()
+        .await
+        .unwrap();
+    let header = Header {
+        kid: Some(TEST_TENANT.to_owned()),
+        ..Default::default()
+    };
+    let secret = EncodingKey::from_secret(TEST_TENANT_SECRET.as_bytes());
+    let forged = encode(&header, &claims, &secret).unwrap();
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": forged }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+}
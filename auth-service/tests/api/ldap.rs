@@ -0,0 +1,290 @@
+use auth_service::{
+    domain::{
+        audit::{AuditEvent, AuditEventType, AuditOutcome, AuditQuery},
+        email::Email,
+        identity_provider::{ExternalIdentity, ProviderId},
+        password::Password,
+        tenant::TenantId,
+        user::User,
+    },
+    utils::{auth::Claims, constants::JWT_COOKIE_NAME},
+    ErrorResponse,
+};
+use uuid::Uuid;
+
+use crate::{
+    helpers::{TestApp, TEST_PASSWORD, TEST_TENANT},
+    mock_idp::{MockIdentity, MOCK_IDP_ID},
+    mock_ldap::{MOCK_LDAP_ADMINS_GROUP, MOCK_LDAP_DOMAIN},
+    oidc::oidc_login,
+};
+
+const DIRECTORY_PASSWORD: &str = "directory-Quiet-harbour-81";
+
+fn get_random_directory_email() -> String {
+    format!("{}@{}", Uuid::new_v4(), MOCK_LDAP_DOMAIN)
+}
+
+async fn login(app: &TestApp, email: &str, password: &str) -> reqwest::Response {
+    app.post_login(&serde_json::json!({ "email": email, "password": password }))
+        .await
+}
+
+// The roles in the token the login issued
+async fn token_roles(app: &TestApp, response: &reqwest::Response) -> Vec<String> {
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await;
+    response.json::<Claims>().await.unwrap().roles
+}
+
+async fn events_for(app: &TestApp, email: &str) -> Vec<AuditEvent> {
+    app.audit_sink
+        .query(&AuditQuery {
+            email: Some(email.to_owned()),
+            limit: 100,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn should_create_the_user_on_first_login() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    app.ldap.add_person(&email, DIRECTORY_PASSWORD, &[]);
+
+    let response = login(&app, &email, DIRECTORY_PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(token_roles(&app, &response).await, ["user"]);
+
+    let user_store = app.user_store.read().await;
+    let user = user_store
+        .get_user(&TenantId::default(), &Email::parse(email.clone()).unwrap())
+        .await
+        .unwrap();
+    assert!(user.password().is_none());
+    drop(user_store);
+
+    let events = events_for(&app, &email).await;
+    let signup = events
+        .iter()
+        .find(|event| event.event_type == AuditEventType::Signup)
+        .expect("No signup recorded");
+    assert_eq!(signup.outcome, AuditOutcome::Success);
+    assert_eq!(signup.detail.as_deref(), Some("Directory corp"));
+    let login = events
+        .iter()
+        .find(|event| event.event_type == AuditEventType::Login)
+        .expect("No login recorded");
+    assert_eq!(login.detail.as_deref(), Some("Directory corp"));
+
+    // Later logins use the same user
+    let response = self::login(&app, &email, DIRECTORY_PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(app.user_store.read().await.count_users().await.unwrap(), 1);
+}
+
+#[tokio::test]
+async fn should_return_401_if_directory_rejects_the_login() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    app.ldap.add_person(&email, DIRECTORY_PASSWORD, &[]);
+
+    for (email, password) in [
+        (email.as_str(), "not-the-directory-password"),
+        (&get_random_directory_email(), DIRECTORY_PASSWORD),
+    ] {
+        let response = login(&app, email, password).await;
+        assert_eq!(response.status().as_u16(), 401);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.unwrap().error,
+            "Incorrect credentials".to_owned()
+        );
+    }
+    assert_eq!(app.user_store.read().await.count_users().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn should_ignore_local_passwords_of_directory_domains() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    app.ldap.add_person(&email, DIRECTORY_PASSWORD, &[]);
+
+    // A user created before the domain moved to the directory
+    let user = User::new(
+        Email::parse(email.clone()).unwrap(),
+        Password::parse(TEST_PASSWORD.to_owned()).unwrap(),
+        false,
+    );
+    app.user_store.write().await.add_user(user).await.unwrap();
+
+    assert_eq!(login(&app, &email, TEST_PASSWORD).await.status().as_u16(), 401);
+    assert_eq!(login(&app, &email, DIRECTORY_PASSWORD).await.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_follow_directory_groups_with_roles() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    app.ldap
+        .add_person(&email, DIRECTORY_PASSWORD, &[MOCK_LDAP_ADMINS_GROUP, "cn=other,dc=example,dc=com"]);
+
+    let response = login(&app, &email, DIRECTORY_PASSWORD).await;
+    assert_eq!(token_roles(&app, &response).await, ["admin", "user"]);
+    assert_eq!(app.get_audit_events(&[]).await.status().as_u16(), 200);
+
+    // Leaving the group takes the role away at the next login
+    app.ldap.add_person(&email, DIRECTORY_PASSWORD, &[]);
+    let response = login(&app, &email, DIRECTORY_PASSWORD).await;
+    assert_eq!(token_roles(&app, &response).await, ["user"]);
+    assert_eq!(app.get_audit_events(&[]).await.status().as_u16(), 403);
+
+    let changed = events_for(&app, &email)
+        .await
+        .into_iter()
+        .find(|event| event.event_type == AuditEventType::RolesChanged)
+        .expect("No role change recorded");
+    assert_eq!(changed.detail.as_deref(), Some("Directory corp: user"));
+}
+
+#[tokio::test]
+async fn should_return_503_if_directory_is_unavailable() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    app.ldap.add_person(&email, DIRECTORY_PASSWORD, &[]);
+    app.ldap.set_unavailable(true);
+
+    let response = login(&app, &email, DIRECTORY_PASSWORD).await;
+    assert_eq!(response.status().as_u16(), 503);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "The user directory is unavailable".to_owned()
+    );
+
+    let login = events_for(&app, &email)
+        .await
+        .into_iter()
+        .find(|event| event.event_type == AuditEventType::Login)
+        .expect("No login recorded");
+    assert_eq!(login.outcome, AuditOutcome::Failure);
+    assert!(login.detail.unwrap().starts_with("Directory corp: "));
+}
+
+#[tokio::test]
+async fn should_return_403_if_signing_up_with_a_directory_email() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": get_random_directory_email(),
+            "password": TEST_PASSWORD,
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "Accounts with this email are managed by a directory".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn should_keep_password_logins_for_tenants_without_the_directory() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    app.ldap.add_person(&email, DIRECTORY_PASSWORD, &[]);
+
+    let signup = serde_json::json!({
+        "email": email,
+        "password": TEST_PASSWORD,
+        "requires2FA": false
+    });
+    assert_eq!(
+        app.post_tenant(TEST_TENANT, "signup", &signup).await.status().as_u16(),
+        201
+    );
+
+    let login = |password: &str| serde_json::json!({ "email": email, "password": password });
+    assert_eq!(
+        app.post_tenant(TEST_TENANT, "login", &login(TEST_PASSWORD))
+            .await
+            .status()
+            .as_u16(),
+        200
+    );
+    assert_eq!(
+        app.post_tenant(TEST_TENANT, "login", &login(DIRECTORY_PASSWORD))
+            .await
+            .status()
+            .as_u16(),
+        401
+    );
+}
+
+#[tokio::test]
+async fn should_not_email_login_links_to_directory_users() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    app.ldap.add_person(&email, DIRECTORY_PASSWORD, &[]);
+    assert_eq!(login(&app, &email, DIRECTORY_PASSWORD).await.status().as_u16(), 200);
+
+    // Answered as for anyone else, but a link would bypass the directory password
+    let response = app
+        .post_magic_link(&serde_json::json!({ "email": email }))
+        .await;
+    assert_eq!(response.status().as_u16(), 202);
+    assert!(app.email_client.sent().iter().all(|sent| sent.recipient != email));
+
+    let requested = app
+        .wait_for_audit_event(&email, AuditEventType::MagicLinkRequested)
+        .await
+        .expect("No link request recorded");
+    assert_eq!(requested.outcome, AuditOutcome::Failure);
+    assert_eq!(requested.detail.as_deref(), Some("Managed by a directory"));
+}
+
+#[tokio::test]
+async fn should_return_403_if_identity_provider_vouches_for_a_directory_email() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    app.ldap.add_person(&email, DIRECTORY_PASSWORD, &[]);
+
+    let response = oidc_login(&app, MockIdentity::verified(&email)).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "Accounts with this email are managed by a directory".to_owned()
+    );
+    assert_eq!(app.user_store.read().await.count_users().await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn should_return_403_if_identity_was_linked_before_the_directory() {
+    let app = TestApp::new().await;
+    let email = get_random_directory_email();
+    let identity = MockIdentity::verified(&email);
+
+    // A user who signed in with the provider before the domain moved to the directory
+    let user = User::from_identity(
+        Email::parse(email.clone()).unwrap(),
+        ExternalIdentity::new(
+            ProviderId::parse(MOCK_IDP_ID.to_owned()).unwrap(),
+            identity.subject.clone(),
+        ),
+    );
+    app.user_store.write().await.add_user(user).await.unwrap();
+
+    let response = oidc_login(&app, identity).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert!(response
+        .cookies()
+        .all(|cookie| cookie.name() != JWT_COOKIE_NAME || cookie.value().is_empty()));
+}
@@ -1,14 +1,159 @@
-use crate::helpers::TestApp;
-use serde_json::json;
+use auth_service::{routes::TwoFactorAuthResponse, utils::constants::JWT_COOKIE_NAME, ErrorResponse};
+use auth_service::domain::{email::Email, tenant::TenantId};
+
+use crate::helpers::{get_random_email, TestApp, TEST_PASSWORD};
+
+#[tokio::test]
+async fn should_return_422_if_malformed_credentials() {
+    let app = TestApp::new().await;
+
+    let random_email = get_random_email();
+
+    let test_cases = [
+        serde_json::json!({
+            "password": TEST_PASSWORD,
+        }),
+        serde_json::json!({
+            "email": random_email,
+        }),
+        serde_json::json!({
+            "email": random_email,
+            "secret": TEST_PASSWORD,
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_login(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            422,
+            "Failed for input: {:?}",
+            test_case
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_input() {
+    let app = TestApp::new().await;
+
+    let test_cases = [
+        serde_json::json!({
+            "email": "invalid_email",
+            "password": TEST_PASSWORD,
+        }),
+        serde_json::json!({
+            "email": get_random_email(),
+            "password": "",
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_login(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "Failed for input: {:?}",
+            test_case
+        );
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            "Invalid credentials".to_owned()
+        );
+    }
+}
 
 #[tokio::test]
-async fn login_returns_http_200_status() {
+async fn should_return_401_if_incorrect_credentials() {
     let app = TestApp::new().await;
 
-    let response = app.post_login(&json!({
-        "email": "user@example.com",
-        "password": "string"
-    })).await;
+    let random_email = get_random_email();
+
+    let signup_body = serde_json::json!({
+        "email": random_email,
+        "password": TEST_PASSWORD,
+        "requires2FA": false
+    });
+    app.post_signup(&signup_body).await;
+
+    let test_cases = [
+        serde_json::json!({
+            "email": random_email,
+            "password": "wrong_password",
+        }),
+        serde_json::json!({
+            "email": get_random_email(),
+            "password": TEST_PASSWORD,
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_login(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            401,
+            "Failed for input: {:?}",
+            test_case
+        );
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            "Incorrect credentials".to_owned()
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_return_200_if_valid_credentials_and_2fa_disabled() {
+    let app = TestApp::new().await;
+
+    let response = app.signup_and_login(&get_random_email(), false).await;
 
     assert_eq!(response.status().as_u16(), 200);
-}
\ No newline at end of file
+
+    let auth_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found");
+
+    assert!(!auth_cookie.value().is_empty());
+    assert!(auth_cookie.http_only());
+}
+
+#[tokio::test]
+async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
+    let app = TestApp::new().await;
+
+    let random_email = get_random_email();
+
+    let response = app.signup_and_login(&random_email, true).await;
+
+    assert_eq!(response.status().as_u16(), 206);
+    assert!(response
+        .cookies()
+        .all(|cookie| cookie.name() != JWT_COOKIE_NAME));
+
+    let json_body = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse");
+
+    assert_eq!(json_body.message, "2FA required".to_owned());
+
+    let (login_attempt_id, _) = app
+        .two_fa_code_store
+        .read()
+        .await
+        .get_code(&TenantId::default(), &Email::parse(random_email).unwrap())
+        .await
+        .expect("No 2FA code stored");
+
+    assert_eq!(json_body.login_attempt_id, login_attempt_id.as_ref());
+}
@@ -1,10 +1,144 @@
-use crate::helpers::TestApp;
+use auth_sdk::AUTH_SERVICE_CLIENT_ID;
+use auth_service::{
+    utils::{auth::validate_service_token, constants::JWT_COOKIE_NAME},
+    ErrorResponse,
+};
+use reqwest::Url;
+
+use crate::helpers::{get_random_email, TestApp};
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let app = TestApp::new().await;
+
+    let response = app.post_logout().await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        "Missing auth token".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let app = TestApp::new().await;
+
+    // add invalid cookie
+    app.cookie_jar.add_cookie_str(
+        &format!(
+            "{}=invalid; HttpOnly; SameSite=Lax; Secure; Path=/",
+            JWT_COOKIE_NAME
+        ),
+        &Url::parse(&app.address).expect("Failed to parse URL"),
+    );
+
+    let response = app.post_logout().await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_return_200_if_valid_jwt_cookie() {
+    let app = TestApp::new().await;
+
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    let response = app.post_logout().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found");
+    assert!(auth_cookie.value().is_empty());
+
+    let is_banned = app
+        .banned_token_store
+        .read()
+        .await
+        .contains_token(&token)
+        .await
+        .expect("Failed to check banned token store");
+    assert!(is_banned);
+}
+
+#[tokio::test]
+async fn should_return_400_if_logout_called_twice_in_a_row() {
+    let app = TestApp::new().await;
+
+    app.signup_and_login(&get_random_email(), false).await;
+
+    let response = app.post_logout().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_logout().await;
+    assert_eq!(response.status().as_u16(), 400);
+}
 
 #[tokio::test]
-async fn logout_returns_http_200_status() {
+async fn should_accept_token_as_bearer_header() {
     let app = TestApp::new().await;
 
-    let response = app.post_logout("string".to_string()).await;
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    // A client without the cookie, e.g. a native app holding the token itself
+    let response = reqwest::Client::new()
+        .post(format!("{}/logout", &app.address))
+        .bearer_auth(&token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
 
     assert_eq!(response.status().as_u16(), 200);
-}
\ No newline at end of file
+
+    let is_banned = app
+        .banned_token_store
+        .read()
+        .await
+        .contains_token(&token)
+        .await
+        .expect("Failed to check banned token store");
+    assert!(is_banned);
+}
+
+#[tokio::test]
+async fn should_push_the_logged_out_token_to_app_service() {
+    let app = TestApp::new().await;
+
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    assert_eq!(app.post_logout().await.status().as_u16(), 200);
+
+    // Signed in as auth-service, so app-service can tell the push is genuine
+    let pushed = app
+        .app_service
+        .wait_for_push(&token)
+        .await
+        .expect("Logged out token was not pushed");
+    let claims = validate_service_token(&pushed.bearer).expect("Push without a service token");
+    assert_eq!(claims.sub, AUTH_SERVICE_CLIENT_ID);
+}
@@ -0,0 +1,257 @@
+use std::time::{Duration, Instant};
+
+use auth_service::{
+    domain::{
+        audit::{AuditEventType, AuditOutcome},
+        email::Email,
+        tenant::TenantId,
+    },
+    routes::{MagicLinkResponse, TwoFactorAuthResponse},
+    utils::constants::JWT_COOKIE_NAME,
+    ErrorResponse,
+};
+
+use crate::helpers::{get_random_email, TestApp, TEST_PASSWORD};
+
+async fn signup(app: &TestApp, email: &str, requires_2fa: bool) {
+    let body = serde_json::json!({
+        "email": email,
+        "password": TEST_PASSWORD,
+        "requires2FA": requires_2fa
+    });
+    assert_eq!(app.post_signup(&body).await.status().as_u16(), 201);
+}
+
+// Request a link for `email` and return the token from the link that was "emailed"
+async fn request_link_token(app: &TestApp, email: &str) -> String {
+    let response = app
+        .post_magic_link(&serde_json::json!({ "email": email }))
+        .await;
+    assert_eq!(response.status().as_u16(), 202);
+
+    let link = app
+        .email_client
+        .wait_for(email)
+        .await
+        .expect("No link was emailed")
+        .content;
+    link.split_once("magic_token=")
+        .expect("No token in the link")
+        .1
+        .to_owned()
+}
+
+#[tokio::test]
+async fn should_respond_the_same_for_unknown_accounts() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    signup(&app, &random_email, false).await;
+
+    let known = app
+        .post_magic_link(&serde_json::json!({ "email": random_email }))
+        .await;
+    assert_eq!(known.status().as_u16(), 202);
+    let known = known.json::<MagicLinkResponse>().await.unwrap();
+
+    let unknown = app
+        .post_magic_link(&serde_json::json!({ "email": get_random_email() }))
+        .await;
+    assert_eq!(unknown.status().as_u16(), 202);
+    let unknown = unknown.json::<MagicLinkResponse>().await.unwrap();
+
+    assert_eq!(known, unknown);
+
+    // Only the existing account got an email
+    app.email_client.wait_for(&random_email).await;
+    let sent = app.email_client.sent();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].recipient, random_email);
+    assert_eq!(sent[0].subject, "Your login link");
+    assert!(sent[0].content.starts_with("http://localhost:3000/?magic_token="));
+}
+
+#[tokio::test]
+async fn should_answer_before_the_email_is_sent() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    signup(&app, &random_email, false).await;
+    app.email_client.set_delay(Duration::from_millis(500));
+
+    // Waiting for the email would tell known accounts from unknown ones
+    let started = Instant::now();
+    let response = app
+        .post_magic_link(&serde_json::json!({ "email": random_email }))
+        .await;
+    assert_eq!(response.status().as_u16(), 202);
+    assert!(started.elapsed() < Duration::from_millis(500));
+
+    assert!(app.email_client.wait_for(&random_email).await.is_some());
+}
+
+#[tokio::test]
+async fn should_answer_known_and_unknown_accounts_alike() {
+    let app = TestApp::new().await;
+    let known_email = get_random_email();
+    let unknown_email = get_random_email();
+    signup(&app, &known_email, false).await;
+    app.email_client.set_delay(Duration::from_millis(500));
+
+    // Both are recorded in the background, so neither waits for the audit log or the email
+    for email in [&known_email, &unknown_email] {
+        let started = Instant::now();
+        let response = app
+            .post_magic_link(&serde_json::json!({ "email": email }))
+            .await;
+        assert_eq!(response.status().as_u16(), 202);
+        assert!(started.elapsed() < Duration::from_millis(500));
+    }
+
+    let known = app
+        .wait_for_audit_event(&known_email, AuditEventType::MagicLinkRequested)
+        .await
+        .expect("No link request recorded for the known account");
+    assert_eq!(known.outcome, AuditOutcome::Success);
+
+    let unknown = app
+        .wait_for_audit_event(&unknown_email, AuditEventType::MagicLinkRequested)
+        .await
+        .expect("No link request recorded for the unknown account");
+    assert_eq!(unknown.outcome, AuditOutcome::Failure);
+    assert_eq!(unknown.detail.as_deref(), Some("Unknown account"));
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_email() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_magic_link(&serde_json::json!({ "email": "invalid_email" }))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "Invalid credentials".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let app = TestApp::new().await;
+
+    let response = app.post_magic_link(&serde_json::json!({})).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    let response = app.post_verify_magic_link(&serde_json::json!({})).await;
+    assert_eq!(response.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn should_log_in_with_an_emailed_link() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    signup(&app, &random_email, false).await;
+    let token = request_link_token(&app, &random_email).await;
+
+    let response = app
+        .post_verify_magic_link(&serde_json::json!({ "token": token }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found");
+    assert!(!auth_cookie.value().is_empty());
+
+    // The session works like one started with a password
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": auth_cookie.value() }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_return_401_if_link_used_twice() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    signup(&app, &random_email, false).await;
+    let token = request_link_token(&app, &random_email).await;
+    let body = serde_json::json!({ "token": token });
+
+    assert_eq!(app.post_verify_magic_link(&body).await.status().as_u16(), 200);
+
+    let response = app.post_verify_magic_link(&body).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "Invalid auth token".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn should_return_206_if_account_requires_2fa() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    signup(&app, &random_email, true).await;
+    let token = request_link_token(&app, &random_email).await;
+
+    let response = app
+        .post_verify_magic_link(&serde_json::json!({ "token": token }))
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+    assert!(response
+        .cookies()
+        .all(|cookie| cookie.name() != JWT_COOKIE_NAME));
+
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+
+    // The login continues at `/verify-2fa`, as after a password
+    let (stored_id, code) = app
+        .two_fa_code_store
+        .read()
+        .await
+        .get_code(&TenantId::default(), &Email::parse(random_email.clone()).unwrap())
+        .await
+        .expect("No 2FA code stored");
+    assert_eq!(stored_id.as_ref(), login_attempt_id);
+
+    let response = app
+        .post_verify_2fa(&serde_json::json!({
+            "email": random_email,
+            "loginAttemptId": login_attempt_id,
+            "2FACode": code.as_ref(),
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+
+    // An auth token is no login link
+    let response = app.signup_and_login(&random_email, false).await;
+    let auth_token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    for token in ["", "invalid", auth_token.as_str()] {
+        let response = app
+            .post_verify_magic_link(&serde_json::json!({ "token": token }))
+            .await;
+        assert_eq!(
+            response.status().as_u16(),
+            401,
+            "Failed for token: {:?}",
+            token
+        );
+    }
+}
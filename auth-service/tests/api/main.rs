@@ -1,7 +1,27 @@
+mod api_keys;
+mod audit_events;
+mod cors;
+mod csrf;
+mod health;
+mod grpc;
 mod helpers;
+mod introspect;
+mod jwks;
+mod ldap;
 mod login;
 mod logout;
+mod magic_link;
+mod metrics;
+mod mock_app_service;
+mod mock_idp;
+mod mock_ldap;
+mod oidc;
+mod openapi;
+mod revoke;
 mod root;
+mod sdk;
 mod signup;
+mod tenants;
+mod token;
 mod verify_2fa;
 mod verify_token;
\ No newline at end of file
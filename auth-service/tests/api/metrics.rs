@@ -0,0 +1,60 @@
+use crate::helpers::{get_random_email, TestApp};
+
+#[tokio::test]
+async fn should_expose_prometheus_text_format() {
+    let app = TestApp::new().await;
+
+    let response = app.get_metrics().await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response
+        .headers()
+        .get("content-type")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("text/plain"));
+}
+
+#[tokio::test]
+async fn should_count_logins_signups_and_requests() {
+    let app = TestApp::new().await;
+    let email = get_random_email();
+    app.signup_and_login(&email, false).await;
+    app.signup_and_login(&get_random_email(), true).await;
+    app.post_login(&serde_json::json!({
+        "email": email,
+        "password": "wrong-password",
+    }))
+    .await;
+
+    let body = app.get_metrics().await.text().await.unwrap();
+
+    assert!(body.contains(r#"signups_total{result="success"} 2"#));
+    assert!(body.contains(r#"logins_total{result="success"} 1"#));
+    assert!(body.contains(r#"logins_total{result="2fa_required"} 1"#));
+    assert!(body.contains(r#"logins_total{result="failure"} 1"#));
+    assert!(body.contains(r#"http_requests_total{method="POST",route="/login",status="401"} 1"#));
+    assert!(body.contains(r#"http_request_duration_seconds_count{method="POST",route="/signup"} 2"#));
+    assert!(body.contains(r#"store_entries{store="users"} 2"#));
+    assert!(body.contains(r#"store_entries{store="two_fa_codes"} 1"#));
+}
+
+#[tokio::test]
+async fn should_count_token_verifications_by_result() {
+    let app = TestApp::new().await;
+
+    app.post_verify_token(&serde_json::json!({ "token": "invalid" }))
+        .await;
+    app.http_client
+        .post(format!("{}/verify-token", &app.address))
+        .json(&serde_json::json!({ "token": "invalid" }))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    let body = app.get_metrics().await.text().await.unwrap();
+
+    assert!(body.contains(r#"token_verifications_total{result="invalid"} 1"#));
+    assert!(body.contains(r#"token_verifications_total{result="unauthorized_service"} 1"#));
+}
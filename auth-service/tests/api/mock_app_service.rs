@@ -0,0 +1,80 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use auth_sdk::RevocationRequest;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+
+// A revoked token pushed to the mock, with the bearer token of the push
+#[derive(Debug, Clone)]
+pub struct PushedRevocation {
+    pub bearer: String,
+    pub token: String,
+}
+
+// Stands in for app-service's `POST /revocations`, recording what auth-service
+// pushes there
+#[derive(Clone)]
+pub struct MockAppService {
+    pub address: String,
+    pushed: Arc<Mutex<Vec<PushedRevocation>>>,
+}
+
+impl MockAppService {
+    pub async fn start() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let pushed = Arc::new(Mutex::new(Vec::new()));
+
+        let router = Router::new()
+            .route("/revocations", post(revocations))
+            .with_state(pushed.clone());
+        #[allow(clippy::let_underscore_future)]
+        let _ = tokio::spawn(async move { axum::serve(listener, router).await });
+
+        Self { address, pushed }
+    }
+
+    pub fn revocations_url(&self) -> String {
+        format!("{}/revocations", self.address)
+    }
+
+    // Pushes happen in the background, so wait a little for the one of `token`
+    pub async fn wait_for_push(&self, token: &str) -> Option<PushedRevocation> {
+        for _ in 0..50 {
+            if let Some(pushed) = self.pushed().into_iter().find(|pushed| pushed.token == token) {
+                return Some(pushed);
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        None
+    }
+
+    pub fn pushed(&self) -> Vec<PushedRevocation> {
+        self.pushed.lock().unwrap().clone()
+    }
+}
+
+async fn revocations(
+    State(pushed): State<Arc<Mutex<Vec<PushedRevocation>>>>,
+    headers: HeaderMap,
+    Json(request): Json<RevocationRequest>,
+) -> StatusCode {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default()
+        .to_owned();
+    pushed.lock().unwrap().push(PushedRevocation {
+        bearer,
+        token: request.token,
+    });
+    StatusCode::NO_CONTENT
+}
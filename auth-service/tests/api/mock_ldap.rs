@@ -0,0 +1,309 @@
+use std::sync::{Arc, Mutex};
+
+use auth_service::domain::ldap_directory::{parse_ldap_directories, LdapDirectory};
+use bytes::BytesMut;
+use ldap3::asn1::{
+    parse_tag, write, ASNTag, Enumerated, OctetString, Sequence, Set, StructureTag, Tag, TagClass,
+    PL,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+pub const MOCK_LDAP_ID: &str = "corp";
+pub const MOCK_LDAP_DOMAIN: &str = "corp.example.com";
+pub const MOCK_LDAP_ADMINS_GROUP: &str = "cn=admins,ou=groups,dc=example,dc=com";
+const MOCK_LDAP_BASE_DN: &str = "ou=people,dc=example,dc=com";
+const MOCK_LDAP_SERVICE_DN: &str = "cn=auth-service,dc=example,dc=com";
+const MOCK_LDAP_SERVICE_PASSWORD: &str = "mock-ldap-service-password";
+
+// LDAPMessage protocol operations, RFC 4511 section 4.2 onwards
+const BIND_REQUEST: u64 = 0;
+const BIND_RESPONSE: u64 = 1;
+const SEARCH_REQUEST: u64 = 3;
+const SEARCH_RESULT_ENTRY: u64 = 4;
+const SEARCH_RESULT_DONE: u64 = 5;
+
+// Result codes
+const SUCCESS: i64 = 0;
+const INVALID_CREDENTIALS: i64 = 49;
+const INSUFFICIENT_ACCESS_RIGHTS: i64 = 50;
+
+// A person in the mock directory
+#[derive(Debug, Clone)]
+struct MockEntry {
+    dn: String,
+    mail: String,
+    password: String,
+    groups: Vec<String>,
+}
+
+#[derive(Default)]
+struct MockLdapState {
+    entries: Vec<MockEntry>,
+    unavailable: bool,
+}
+
+// An LDAP directory running in the test process. It understands the simple
+// binds and equality searches a login needs, and like a real directory only
+// lets the service account search.
+#[derive(Clone)]
+pub struct MockLdap {
+    pub address: String,
+    state: Arc<Mutex<MockLdapState>>,
+}
+
+impl MockLdap {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("ldap://{}", listener.local_addr().unwrap());
+        let state = Arc::new(Mutex::new(MockLdapState::default()));
+
+        let server_state = state.clone();
+        #[allow(clippy::let_underscore_future)]
+        let _ = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                // An unavailable directory hangs up at once
+                if server_state.lock().unwrap().unavailable {
+                    continue;
+                }
+                tokio::spawn(serve(stream, server_state.clone()));
+            }
+        });
+
+        Self { address, state }
+    }
+
+    // How auth-service is configured to use the mock, for the default tenant
+    pub fn directory(&self) -> LdapDirectory {
+        let json = serde_json::json!([{
+            "id": MOCK_LDAP_ID,
+            "url": self.address,
+            "domains": [MOCK_LDAP_DOMAIN],
+            "baseDn": MOCK_LDAP_BASE_DN,
+            "bindDn": MOCK_LDAP_SERVICE_DN,
+            "bindPassword": MOCK_LDAP_SERVICE_PASSWORD,
+            "userFilter": "(&(objectClass=person)(mail={email}))",
+            "groupRoles": { MOCK_LDAP_ADMINS_GROUP: "admin" },
+        }]);
+        parse_ldap_directories(&json.to_string()).unwrap().remove(0)
+    }
+
+    // Adds a person, or replaces the one with the same email
+    pub fn add_person(&self, email: &str, password: &str, groups: &[&str]) {
+        let uid = email.split('@').next().unwrap();
+        let entry = MockEntry {
+            dn: format!("uid={},{}", uid, MOCK_LDAP_BASE_DN),
+            mail: email.to_owned(),
+            password: password.to_owned(),
+            groups: groups.iter().map(|group| group.to_string()).collect(),
+        };
+
+        let mut state = self.state.lock().unwrap();
+        state
+            .entries
+            .retain(|existing| !existing.mail.eq_ignore_ascii_case(email));
+        state.entries.push(entry);
+    }
+
+    pub fn set_unavailable(&self, unavailable: bool) {
+        self.state.lock().unwrap().unavailable = unavailable;
+    }
+}
+
+async fn serve(mut stream: TcpStream, state: Arc<Mutex<MockLdapState>>) {
+    let mut buffer = Vec::new();
+    // The DN the connection is bound as, empty while anonymous
+    let mut bound_as = String::new();
+
+    loop {
+        let message = loop {
+            match parse_tag(&buffer) {
+                Ok((rest, message)) => {
+                    let message = message.clone();
+                    buffer = rest.to_vec();
+                    break message;
+                }
+                Err(_) => {
+                    let mut chunk = [0u8; 4096];
+                    match stream.read(&mut chunk).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(read) => buffer.extend_from_slice(&chunk[..read]),
+                    }
+                }
+            }
+        };
+
+        let mut parts = constructed(message).into_iter();
+        let (Some(message_id), Some(operation)) = (parts.next(), parts.next()) else {
+            return;
+        };
+
+        let responses = match operation.id {
+            BIND_REQUEST => vec![bind(operation, &state, &mut bound_as)],
+            SEARCH_REQUEST => search(operation, &state, &bound_as),
+            // Unbinds, and anything a login does not need
+            _ => return,
+        };
+
+        for response in responses {
+            let envelope = Tag::Sequence(Sequence {
+                inner: vec![Tag::StructureTag(message_id.clone()), response],
+                ..Default::default()
+            });
+            let mut out = BytesMut::new();
+            write::encode_into(&mut out, envelope.into_structure()).unwrap();
+            if stream.write_all(&out).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+fn bind(request: StructureTag, state: &Mutex<MockLdapState>, bound_as: &mut String) -> Tag {
+    let mut parts = constructed(request).into_iter().skip(1);
+    let name = text(parts.next());
+    let password = text(parts.next());
+
+    let valid = if name.is_empty() {
+        password.is_empty()
+    } else if name.eq_ignore_ascii_case(MOCK_LDAP_SERVICE_DN) {
+        password == MOCK_LDAP_SERVICE_PASSWORD
+    } else {
+        state
+            .lock()
+            .unwrap()
+            .entries
+            .iter()
+            .any(|entry| entry.dn.eq_ignore_ascii_case(&name) && entry.password == password)
+    };
+
+    match valid {
+        true => {
+            *bound_as = name;
+            ldap_result(BIND_RESPONSE, SUCCESS)
+        }
+        false => {
+            bound_as.clear();
+            ldap_result(BIND_RESPONSE, INVALID_CREDENTIALS)
+        }
+    }
+}
+
+fn search(request: StructureTag, state: &Mutex<MockLdapState>, bound_as: &str) -> Vec<Tag> {
+    if !bound_as.eq_ignore_ascii_case(MOCK_LDAP_SERVICE_DN) {
+        return vec![ldap_result(SEARCH_RESULT_DONE, INSUFFICIENT_ACCESS_RIGHTS)];
+    }
+
+    let parts = constructed(request);
+    let base = text(parts.first().cloned()).to_lowercase();
+    let filter = parts[6].clone();
+    let attributes: Vec<String> = constructed(parts[7].clone())
+        .into_iter()
+        .map(|attribute| text(Some(attribute)))
+        .collect();
+
+    let mut responses: Vec<Tag> = state
+        .lock()
+        .unwrap()
+        .entries
+        .iter()
+        .filter(|entry| entry.dn.to_lowercase().ends_with(&base) && matches(&filter, entry))
+        .map(|entry| {
+            let mut values = Vec::new();
+            if attributes
+                .iter()
+                .any(|attribute| attribute.eq_ignore_ascii_case("memberOf"))
+            {
+                values.push(attribute("memberOf", &entry.groups));
+            }
+            Tag::Sequence(Sequence {
+                id: SEARCH_RESULT_ENTRY,
+                class: TagClass::Application,
+                inner: vec![
+                    octet_string(&entry.dn),
+                    Tag::Sequence(Sequence {
+                        inner: values,
+                        ..Default::default()
+                    }),
+                ],
+            })
+        })
+        .collect();
+    responses.push(ldap_result(SEARCH_RESULT_DONE, SUCCESS));
+    responses
+}
+
+// Supports `&`, `|`, `!`, equality and presence filters, which is what
+// directories are configured with for logins
+fn matches(filter: &StructureTag, entry: &MockEntry) -> bool {
+    let values = |attribute: &str| -> Vec<String> {
+        match attribute.to_lowercase().as_str() {
+            "objectclass" => vec!["top".to_owned(), "person".to_owned()],
+            "mail" => vec![entry.mail.clone()],
+            "memberof" => entry.groups.clone(),
+            _ => Vec::new(),
+        }
+    };
+
+    match (filter.id, filter.payload.clone()) {
+        (0, PL::C(filters)) => filters.iter().all(|filter| matches(filter, entry)),
+        (1, PL::C(filters)) => filters.iter().any(|filter| matches(filter, entry)),
+        (2, PL::C(filters)) => !filters.iter().all(|filter| matches(filter, entry)),
+        (3, PL::C(pair)) => {
+            let attribute = text(pair.first().cloned());
+            let value = text(pair.get(1).cloned());
+            values(&attribute)
+                .iter()
+                .any(|candidate| candidate.eq_ignore_ascii_case(&value))
+        }
+        (7, PL::P(attribute)) => !values(&String::from_utf8_lossy(&attribute)).is_empty(),
+        _ => false,
+    }
+}
+
+fn ldap_result(operation: u64, code: i64) -> Tag {
+    Tag::Sequence(Sequence {
+        id: operation,
+        class: TagClass::Application,
+        inner: vec![
+            Tag::Enumerated(Enumerated {
+                inner: code,
+                ..Default::default()
+            }),
+            octet_string(""),
+            octet_string(""),
+        ],
+    })
+}
+
+fn attribute(name: &str, values: &[String]) -> Tag {
+    Tag::Sequence(Sequence {
+        inner: vec![
+            octet_string(name),
+            Tag::Set(Set {
+                inner: values.iter().map(|value| octet_string(value)).collect(),
+                ..Default::default()
+            }),
+        ],
+        ..Default::default()
+    })
+}
+
+fn octet_string(value: &str) -> Tag {
+    Tag::OctetString(OctetString {
+        inner: value.as_bytes().to_vec(),
+        ..Default::default()
+    })
+}
+
+fn constructed(tag: StructureTag) -> Vec<StructureTag> {
+    tag.expect_constructed().unwrap_or_default()
+}
+
+fn text(tag: Option<StructureTag>) -> String {
+    tag.and_then(StructureTag::expect_primitive)
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,311 @@
+use auth_service::{
+    domain::{
+        audit::{AuditEvent, AuditEventType, AuditOutcome, AuditQuery},
+        email::Email,
+        tenant::TenantId,
+    },
+    routes::IdentityProviderResponse,
+    utils::{auth::Claims, constants::JWT_COOKIE_NAME},
+    ErrorResponse,
+};
+use reqwest::{header::LOCATION, Url};
+
+use crate::{
+    helpers::{get_random_email, TestApp, TEST_PASSWORD, TEST_SECURE_TENANT, TEST_TENANT},
+    mock_idp::{MockIdentity, MOCK_IDP_ID},
+};
+
+// Start a login at the mock and return where it sent the browser back to
+async fn redirect_back(response: reqwest::Response) -> Url {
+    assert_eq!(response.status().as_u16(), 303);
+    let location = response.headers()[LOCATION].to_str().unwrap();
+    Url::parse(location).unwrap()
+}
+
+// What the login page posts to `/login/oidc/callback`
+fn callback_body(url: &Url) -> serde_json::Value {
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+            .unwrap_or_default()
+    };
+    serde_json::json!({ "code": param("code"), "state": param("state") })
+}
+
+// Signs in at the mock as `identity` and completes the login, also used by other tests
+pub async fn oidc_login(app: &TestApp, identity: MockIdentity) -> reqwest::Response {
+    app.idp.sign_in_as(identity);
+    let back = redirect_back(app.get_oidc_login(MOCK_IDP_ID).await).await;
+    app.post_oidc_callback(&callback_body(&back)).await
+}
+
+fn auth_token(response: &reqwest::Response) -> String {
+    response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned()
+}
+
+async fn events_for(app: &TestApp, email: &str) -> Vec<AuditEvent> {
+    app.audit_sink
+        .query(&AuditQuery {
+            email: Some(email.to_owned()),
+            limit: 100,
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn should_list_identity_providers() {
+    let app = TestApp::new().await;
+
+    let response = app.get_identity_providers().await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.json::<Vec<IdentityProviderResponse>>().await.unwrap(),
+        vec![IdentityProviderResponse {
+            id: MOCK_IDP_ID.to_owned(),
+            name: "Mock IdP".to_owned(),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn should_return_404_if_unknown_provider() {
+    let app = TestApp::new().await;
+
+    for provider in ["unknown", "Not-A-Provider"] {
+        let response = app.get_oidc_login(provider).await;
+        assert_eq!(response.status().as_u16(), 404, "Failed for provider: {}", provider);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.unwrap().error,
+            "Unknown identity provider".to_owned()
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_create_an_account_on_first_login() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+
+    app.idp.sign_in_as(MockIdentity::verified(&random_email));
+    let back = redirect_back(app.get_oidc_login(MOCK_IDP_ID).await).await;
+    assert!(back.as_str().starts_with("http://localhost:3000/?code="));
+
+    let response = app.post_oidc_callback(&callback_body(&back)).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = auth_token(&response);
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await;
+    assert_eq!(response.json::<Claims>().await.unwrap().sub, random_email);
+
+    // The account has no password to log in with
+    let response = app
+        .post_login(&serde_json::json!({ "email": random_email, "password": TEST_PASSWORD }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let events = events_for(&app, &random_email).await;
+    let signup = events
+        .iter()
+        .find(|event| event.event_type == AuditEventType::Signup)
+        .expect("No signup recorded");
+    assert_eq!(signup.outcome, AuditOutcome::Success);
+    assert_eq!(signup.detail.as_deref(), Some("Identity provider mock"));
+    let login = events
+        .iter()
+        .find(|event| event.event_type == AuditEventType::Login && event.outcome == AuditOutcome::Success)
+        .expect("No login recorded");
+    assert_eq!(login.detail.as_deref(), Some("Identity provider mock"));
+}
+
+#[tokio::test]
+async fn should_log_in_to_the_linked_account_later() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let mut identity = MockIdentity::verified(&random_email);
+    assert_eq!(oidc_login(&app, identity.clone()).await.status().as_u16(), 200);
+
+    // The account at the provider is what counts, not its current email
+    identity.email = Some(get_random_email());
+    let response = oidc_login(&app, identity).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": auth_token(&response) }))
+        .await;
+    assert_eq!(response.json::<Claims>().await.unwrap().sub, random_email);
+}
+
+#[tokio::test]
+async fn should_link_an_existing_account_with_the_verified_email() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    app.signup_and_login(&random_email, false).await;
+
+    let response = oidc_login(&app, MockIdentity::verified(&random_email)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user_store = app.user_store.read().await;
+    let user = user_store
+        .get_user(&TenantId::default(), &Email::parse(random_email.clone()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(user.identities().len(), 1);
+    assert!(user.password().is_some());
+    drop(user_store);
+
+    let linked = events_for(&app, &random_email)
+        .await
+        .into_iter()
+        .find(|event| event.event_type == AuditEventType::IdentityLinked)
+        .expect("No link recorded");
+    assert_eq!(linked.outcome, AuditOutcome::Success);
+}
+
+#[tokio::test]
+async fn should_return_403_if_email_not_verified() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    app.signup_and_login(&random_email, false).await;
+
+    let new_email = get_random_email();
+    for email in [&random_email, &new_email] {
+        let identity = MockIdentity {
+            email_verified: false,
+            ..MockIdentity::verified(email)
+        };
+        let response = oidc_login(&app, identity).await;
+        assert_eq!(response.status().as_u16(), 403);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.unwrap().error,
+            "The identity provider did not verify the email".to_owned()
+        );
+    }
+
+    // Neither created nor linked an account
+    let user_store = app.user_store.read().await;
+    let new_email = Email::parse(new_email).unwrap();
+    assert!(user_store.get_user(&TenantId::default(), &new_email).await.is_err());
+    let existing = Email::parse(random_email).unwrap();
+    let user = user_store.get_user(&TenantId::default(), &existing).await.unwrap();
+    assert!(user.identities().is_empty());
+}
+
+#[tokio::test]
+async fn should_return_401_if_login_state_is_missing_or_mismatched() {
+    let app = TestApp::new().await;
+    app.idp.sign_in_as(MockIdentity::verified(&get_random_email()));
+    let back = redirect_back(app.get_oidc_login(MOCK_IDP_ID).await).await;
+    let body = callback_body(&back);
+
+    let mut wrong_state = body.clone();
+    wrong_state["state"] = serde_json::json!("another-state");
+    assert_eq!(app.post_oidc_callback(&wrong_state).await.status().as_u16(), 401);
+
+    // The failed attempt used up the login state
+    let response = app.post_oidc_callback(&body).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "Invalid auth token".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn should_reject_a_code_issued_for_another_login() {
+    let app = TestApp::new().await;
+    app.idp.sign_in_as(MockIdentity::verified(&get_random_email()));
+    let first = callback_body(&redirect_back(app.get_oidc_login(MOCK_IDP_ID).await).await);
+    let second = callback_body(&redirect_back(app.get_oidc_login(MOCK_IDP_ID).await).await);
+
+    // The state matches the login in progress, but PKCE ties the code to the first one
+    let body = serde_json::json!({ "code": first["code"], "state": second["state"] });
+    let response = app.post_oidc_callback(&body).await;
+    assert_eq!(response.status().as_u16(), 502);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "Login with the identity provider failed".to_owned()
+    );
+}
+
+#[tokio::test]
+async fn should_return_502_if_id_token_has_another_issuer() {
+    let app = TestApp::new().await;
+    app.idp.set_issuer("https://rogue.example.com");
+
+    let response = oidc_login(&app, MockIdentity::verified(&get_random_email())).await;
+    assert_eq!(response.status().as_u16(), 502);
+}
+
+#[tokio::test]
+async fn should_log_in_to_the_tenant_the_login_started_at() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+
+    app.idp.sign_in_as(MockIdentity::verified(&random_email));
+    let back = redirect_back(
+        app.get_tenant(TEST_TENANT, &format!("login/oidc/{}", MOCK_IDP_ID))
+            .await,
+    )
+    .await;
+    assert!(back.as_str().starts_with("http://localhost:3000/?tenant=acme&code="));
+    let body = callback_body(&back);
+
+    // The login state is only good for its tenant
+    let wrong_tenant = app.post_oidc_callback(&body).await;
+    assert_eq!(wrong_tenant.status().as_u16(), 401);
+
+    let back = redirect_back(
+        app.get_tenant(TEST_TENANT, &format!("login/oidc/{}", MOCK_IDP_ID))
+            .await,
+    )
+    .await;
+    let response = app
+        .post_tenant(TEST_TENANT, "login/oidc/callback", &callback_body(&back))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": auth_token(&response) }))
+        .await;
+    assert_eq!(
+        response.json::<Claims>().await.unwrap().tenant.as_deref(),
+        Some(TEST_TENANT)
+    );
+    let email = Email::parse(random_email).unwrap();
+    assert!(app
+        .user_store
+        .read()
+        .await
+        .get_user(&TenantId::default(), &email)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn should_return_206_if_tenant_requires_2fa() {
+    let app = TestApp::new().await;
+
+    app.idp.sign_in_as(MockIdentity::verified(&get_random_email()));
+    let back = redirect_back(
+        app.get_tenant(TEST_SECURE_TENANT, &format!("login/oidc/{}", MOCK_IDP_ID))
+            .await,
+    )
+    .await;
+    let response = app
+        .post_tenant(TEST_SECURE_TENANT, "login/oidc/callback", &callback_body(&back))
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+    assert!(response
+        .cookies()
+        .all(|cookie| cookie.name() != JWT_COOKIE_NAME));
+}
@@ -0,0 +1,84 @@
+use auth_service::api_router;
+
+use crate::helpers::TestApp;
+
+async fn get_openapi(app: &TestApp) -> serde_json::Value {
+    let response = app
+        .http_client
+        .get(format!("{}/openapi.json", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(response.status().as_u16(), 200);
+
+    response
+        .json::<serde_json::Value>()
+        .await
+        .expect("Could not deserialize OpenAPI document")
+}
+
+#[tokio::test]
+async fn should_serve_openapi_3_1_document_generated_from_router() {
+    let app = TestApp::new().await;
+
+    let served = get_openapi(&app).await;
+
+    assert!(served["openapi"].as_str().unwrap().starts_with("3.1"));
+
+    let (_, generated) = api_router().split_for_parts();
+    assert_eq!(served, serde_json::to_value(generated).unwrap());
+}
+
+#[tokio::test]
+async fn every_documented_operation_should_be_routed() {
+    let app = TestApp::new().await;
+
+    let served = get_openapi(&app).await;
+    let paths = served["paths"].as_object().unwrap();
+    assert!(!paths.is_empty());
+
+    for (path, operations) in paths {
+        // Path parameters only need to be well-formed for routing to happen
+        let url = format!(
+            "{}{}",
+            &app.address,
+            path.replace("{id}", "00000000-0000-0000-0000-000000000000")
+                .replace("{tenant}", "default")
+                .replace("{provider}", "mock")
+        );
+
+        for method in operations.as_object().unwrap().keys() {
+            let method = reqwest::Method::from_bytes(method.to_uppercase().as_bytes()).unwrap();
+            let response = app
+                .http_client
+                .request(method.clone(), &url)
+                .send()
+                .await
+                .expect("Failed to execute request.");
+
+            // Unrouted requests fall through to the static assets, which answer 404 or 405
+            assert!(
+                ![404, 405].contains(&response.status().as_u16()),
+                "{} {} is documented but not routed ({})",
+                method,
+                path,
+                response.status()
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn should_serve_swagger_ui() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .http_client
+        .get(format!("{}/docs/", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.unwrap().contains("swagger"));
+}
@@ -0,0 +1,145 @@
+use auth_service::{
+    routes::{CreateApiKeyResponse, IntrospectResponse},
+    utils::constants::JWT_COOKIE_NAME,
+    ErrorResponse,
+};
+
+use crate::helpers::{
+    get_random_email, TestApp, TEST_CLIENT_ID, TEST_OTHER_CLIENT_ID, TEST_OTHER_CLIENT_SECRET,
+};
+
+async fn login_and_get_token(app: &TestApp, email: &str) -> String {
+    let response = app.signup_and_login(email, false).await;
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+    token
+}
+
+async fn is_active(app: &TestApp, token: &str) -> bool {
+    app.post_introspect(&[("token", token)], None)
+        .await
+        .json::<IntrospectResponse>()
+        .await
+        .unwrap()
+        .active
+}
+
+#[tokio::test]
+async fn should_return_401_if_client_not_authenticated() {
+    let app = TestApp::new().await;
+    let token = login_and_get_token(&app, &get_random_email()).await;
+
+    let response = app
+        .post_revoke(&[("token", &token)], Some((TEST_CLIENT_ID, "wrong secret")))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "invalid_client"
+    );
+    assert!(is_active(&app, &token).await);
+}
+
+#[tokio::test]
+async fn should_return_400_if_token_parameter_missing() {
+    let app = TestApp::new().await;
+
+    let response = app.post_revoke(&[("token_type_hint", "access_token")], None).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "invalid_request"
+    );
+}
+
+#[tokio::test]
+async fn should_revoke_session_token() {
+    let app = TestApp::new().await;
+    let token = login_and_get_token(&app, &get_random_email()).await;
+
+    let response = app.post_revoke(&[("token", &token)], None).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    assert!(!is_active(&app, &token).await);
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // Services caching the token are told at once
+    assert!(app.app_service.wait_for_push(&token).await.is_some());
+}
+
+#[tokio::test]
+async fn should_revoke_api_key_whatever_the_hint() {
+    let app = TestApp::new().await;
+    app.signup_and_login(&get_random_email(), false).await;
+
+    for hint in ["api_key", "access_token", "refresh_token"] {
+        let created = app
+            .post_api_key(&serde_json::json!({
+                "name": "ci",
+                "scopes": [],
+                "expiresInDays": 1
+            }))
+            .await
+            .json::<CreateApiKeyResponse>()
+            .await
+            .unwrap();
+
+        let response = app
+            .post_revoke(&[("token", &created.secret), ("token_type_hint", hint)], None)
+            .await;
+        assert_eq!(response.status().as_u16(), 200, "Failed for hint: {}", hint);
+        assert!(!is_active(&app, &created.secret).await, "Failed for hint: {}", hint);
+    }
+}
+
+#[tokio::test]
+async fn should_return_200_for_invalid_tokens() {
+    let app = TestApp::new().await;
+    let token = login_and_get_token(&app, &get_random_email()).await;
+    app.post_revoke(&[("token", &token)], None).await;
+
+    // Revoking twice, or revoking what was never a token, looks like success
+    for token in [token.as_str(), "invalid", "lgr_invalid"] {
+        let response = app.post_revoke(&[("token", token)], None).await;
+        assert_eq!(response.status().as_u16(), 200, "Failed for token: {}", token);
+    }
+}
+
+#[tokio::test]
+async fn should_leave_tokens_of_users_to_trusted_clients() {
+    let app = TestApp::new().await;
+    let token = login_and_get_token(&app, &get_random_email()).await;
+
+    // Answered as if revoked, but the client has no `revoke` scope
+    let response = app
+        .post_revoke(
+            &[("token", &token)],
+            Some((TEST_OTHER_CLIENT_ID, TEST_OTHER_CLIENT_SECRET)),
+        )
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(is_active(&app, &token).await);
+}
+
+#[tokio::test]
+async fn should_refuse_to_revoke_service_tokens() {
+    let app = TestApp::new().await;
+
+    let response = app.post_revoke(&[("token", &app.service_token)], None).await;
+
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(
+        response.json::<ErrorResponse>().await.unwrap().error,
+        "unsupported_token_type"
+    );
+}
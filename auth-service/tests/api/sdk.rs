@@ -0,0 +1,183 @@
+use auth_sdk::{
+    AuthServiceClient, ClientError, CreateApiKeyRequest, ErrorKind, LoginOutcome, LoginRequest,
+    SignupRequest, Verify2FARequest,
+};
+use auth_service::domain::{email::Email, tenant::TenantId};
+
+use crate::helpers::{get_random_email, TestApp, TEST_CLIENT_ID, TEST_CLIENT_SECRET, TEST_PASSWORD};
+
+async fn signed_up_client(app: &TestApp, email: &str, requires_2fa: bool) -> AuthServiceClient {
+    let client = AuthServiceClient::new(&app.address).unwrap();
+    client
+        .signup(&SignupRequest {
+            email: email.to_owned(),
+            password: TEST_PASSWORD.to_owned(),
+            requires_2fa,
+        })
+        .await
+        .expect("Signup failed");
+    client
+}
+
+fn login_request(email: &str, password: &str) -> LoginRequest {
+    LoginRequest {
+        email: email.to_owned(),
+        password: password.to_owned(),
+    }
+}
+
+#[tokio::test]
+async fn should_keep_the_session_between_calls() {
+    let app = TestApp::new().await;
+    let email = get_random_email();
+    let client = signed_up_client(&app, &email, false).await;
+
+    let outcome = client.login(&login_request(&email, TEST_PASSWORD)).await.unwrap();
+    assert!(matches!(outcome, LoginOutcome::LoggedIn));
+    assert!(client.auth_token().is_some());
+
+    // Cookie-authenticated and CSRF-protected, so this only works if both cookies are kept
+    let created = client
+        .create_api_key(&CreateApiKeyRequest {
+            name: "ci".to_owned(),
+            scopes: vec!["user".to_owned()],
+            expires_in_days: 30,
+        })
+        .await
+        .unwrap();
+    assert_eq!(client.list_api_keys().await.unwrap(), vec![created.key.clone()]);
+    client.revoke_api_key(&created.key.id).await.unwrap();
+    assert!(client.list_api_keys().await.unwrap().is_empty());
+
+    client.logout().await.unwrap();
+    assert_eq!(client.auth_token(), None);
+
+    let error = client.logout().await.unwrap_err();
+    assert_eq!(error.kind(), Some(ErrorKind::MissingToken));
+    assert_eq!(error.status(), Some(400));
+}
+
+#[tokio::test]
+async fn should_complete_a_2fa_login() {
+    let app = TestApp::new().await;
+    let email = get_random_email();
+    let client = signed_up_client(&app, &email, true).await;
+
+    let attempt = match client.login(&login_request(&email, TEST_PASSWORD)).await.unwrap() {
+        LoginOutcome::TwoFactorRequired(attempt) => attempt,
+        LoginOutcome::LoggedIn => panic!("Expected 2FA to be required"),
+    };
+    assert_eq!(client.auth_token(), None);
+
+    let (_, code) = app
+        .two_fa_code_store
+        .read()
+        .await
+        .get_code(&TenantId::default(), &Email::parse(email.clone()).unwrap())
+        .await
+        .expect("No 2FA code stored");
+
+    client
+        .verify_2fa(&Verify2FARequest {
+            email,
+            login_attempt_id: attempt.login_attempt_id,
+            two_fa_code: code.as_ref().to_owned(),
+        })
+        .await
+        .unwrap();
+    assert!(client.auth_token().is_some());
+}
+
+#[tokio::test]
+async fn should_map_error_bodies_to_error_kinds() {
+    let app = TestApp::new().await;
+    let email = get_random_email();
+    let client = signed_up_client(&app, &email, false).await;
+
+    let error = client
+        .signup(&SignupRequest {
+            email: email.clone(),
+            password: TEST_PASSWORD.to_owned(),
+            requires_2fa: false,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), Some(ErrorKind::UserAlreadyExists));
+    assert_eq!(error.status(), Some(409));
+
+    let error = client
+        .signup(&SignupRequest {
+            email: get_random_email(),
+            password: "password".to_owned(),
+            requires_2fa: false,
+        })
+        .await
+        .unwrap_err();
+    match error {
+        ClientError::Api {
+            kind: ErrorKind::WeakPassword,
+            reasons,
+            ..
+        } => assert!(!reasons.is_empty()),
+        other => panic!("Expected a weak password error, got {:?}", other),
+    }
+
+    let error = client
+        .login(&login_request(&email, "wrong-Lantern-orbit-57"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), Some(ErrorKind::IncorrectCredentials));
+
+    let error = client
+        .client_credentials(TEST_CLIENT_ID, "wrong secret", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        ClientError::Rejected { status: 401, ref error } if error == "invalid_client"
+    ));
+}
+
+#[tokio::test]
+async fn should_verify_tokens_as_a_service() {
+    let app = TestApp::new().await;
+    let email = get_random_email();
+    let client = signed_up_client(&app, &email, false).await;
+    client.login(&login_request(&email, TEST_PASSWORD)).await.unwrap();
+    let token = client.auth_token().unwrap();
+
+    let service = AuthServiceClient::new(&app.address).unwrap();
+    let service_token = service
+        .client_credentials(TEST_CLIENT_ID, TEST_CLIENT_SECRET, None)
+        .await
+        .unwrap();
+
+    let claims = service
+        .verify_token(&service_token.access_token, &token)
+        .await
+        .unwrap();
+    assert_eq!(claims.sub, email);
+
+    let error = service
+        .verify_token(&service_token.access_token, "not-a-token")
+        .await
+        .unwrap_err();
+    assert_eq!(error.kind(), Some(ErrorKind::InvalidToken));
+
+    let introspection = service
+        .introspect_token(TEST_CLIENT_ID, TEST_CLIENT_SECRET, &token)
+        .await
+        .unwrap();
+    assert!(introspection.active);
+    assert_eq!(introspection.sub, Some(email));
+
+    service
+        .revoke_token(TEST_CLIENT_ID, TEST_CLIENT_SECRET, &token, Some("access_token"))
+        .await
+        .unwrap();
+    let introspection = service
+        .introspect_token(TEST_CLIENT_ID, TEST_CLIENT_SECRET, &token)
+        .await
+        .unwrap();
+    assert!(!introspection.active);
+}
@@ -1,6 +1,6 @@
-use auth_service::{ErrorResponse, routes::SignupResponse};
+use auth_service::{ErrorResponse, domain::password_policy::PasswordRejection, routes::SignupResponse};
 
-use crate::helpers::{TestApp, get_random_email};
+use crate::helpers::{TestApp, get_random_email, TEST_PASSWORD};
 
 #[tokio::test]
 async fn should_return_422_if_malformed_input() {
@@ -10,22 +10,22 @@ async fn should_return_422_if_malformed_input() {
 
     let test_cases = [
         serde_json::json!({
-            "password": "password123",
+            "password": TEST_PASSWORD,
             "requires2FA": true
         }),
         serde_json::json!({
             "email": random_email,
-            "secret": "password123",
+            "secret": TEST_PASSWORD,
             "requires2FA": true
         }),
         serde_json::json!({
             "email": random_email,
-            "password": "password123",
+            "password": TEST_PASSWORD,
             "requires2FA": "true"
         }),
         serde_json::json!({
             "email": random_email,
-            "password": "password123"
+            "password": TEST_PASSWORD
         }),
     ];
 
@@ -54,7 +54,7 @@ async fn should_return_201_if_valid_input() {
 
     let request_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": TEST_PASSWORD,
         "requires2FA": true
     });
 
@@ -74,8 +74,8 @@ async fn should_return_201_if_valid_input() {
 async fn should_return_400_if_invalid_input() {
     // The signup route should return a 400 HTTP status code if an invalid input is sent.
     // The input is considered invalid if:
-    // - The email is empty or does not contain '@'
-    // - The password is less than 8 characters
+    // - The email is not a valid RFC 5322 address
+    // - The password is empty
 
     // Create an array of invalid inputs. Then, iterate through the array and 
     // make HTTP calls to the signup route. Assert a 400 HTTP status code is returned.
@@ -84,12 +84,12 @@ async fn should_return_400_if_invalid_input() {
     let test_cases = [
         serde_json::json!({
             "email": "invalid_email",
-            "password": "password123",
+            "password": TEST_PASSWORD,
             "requires2FA": true
         }),
         serde_json::json!({
             "email": "valid@test.com",
-            "password": "short",
+            "password": "",
             "requires2FA": true
         }),
         serde_json::json!({
@@ -97,6 +97,21 @@ async fn should_return_400_if_invalid_input() {
             "password": "short",
             "requires2FA": true
         }),
+        serde_json::json!({
+            "email": "@",
+            "password": TEST_PASSWORD,
+            "requires2FA": true
+        }),
+        serde_json::json!({
+            "email": "a@@test.com",
+            "password": TEST_PASSWORD,
+            "requires2FA": true
+        }),
+        serde_json::json!({
+            "email": "with space@test.com",
+            "password": TEST_PASSWORD,
+            "requires2FA": true
+        }),
     ];
 
     for test_case in test_cases.iter() {
@@ -127,12 +142,12 @@ async fn should_return_409_if_email_already_exists() {
     let test_case_1 = 
         serde_json::json!({
             "email": "valid@test.com",
-            "password": "password123",
+            "password": TEST_PASSWORD,
             "requires2FA": true
         });
     let test_case_2 = serde_json::json!({
             "email": "valid@test.com",
-            "password": "password123",
+            "password": TEST_PASSWORD,
             "requires2FA": true
         });
 
@@ -160,4 +175,66 @@ async fn should_return_409_if_email_already_exists() {
             .error,
         "User already exists".to_owned()
     );
-}
\ No newline at end of file
+}
+#[tokio::test]
+async fn should_return_409_if_email_differs_only_in_case() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": "Bob@Test.com",
+            "password": TEST_PASSWORD,
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": "bob@test.com",
+            "password": TEST_PASSWORD,
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    // Either spelling logs into the same account
+    let response = app
+        .post_login(&serde_json::json!({
+            "email": "BOB@test.COM",
+            "password": TEST_PASSWORD,
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_return_400_with_reasons_if_password_is_weak() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": get_random_email(),
+            "password": "short",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let body = response
+        .json::<ErrorResponse>()
+        .await
+        .expect("Could not deserialize response body to ErrorResponse");
+    assert_eq!(body.error, "Password does not meet the password policy".to_owned());
+    assert_eq!(body.reasons[0], PasswordRejection::TooShort { min_length: 8 });
+    assert!(matches!(body.reasons[1], PasswordRejection::TooWeak { .. }));
+
+    let response = app
+        .post_signup(&serde_json::json!({
+            "email": get_random_email(),
+            "password": "password123",
+            "requires2FA": false
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 400);
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeSet;
+
+use auth_service::{
+    domain::{
+        audit::{AuditEventType, AuditQuery},
+        email::Email,
+        role::Role,
+        tenant::TenantId,
+    },
+    utils::{auth::Claims, constants::JWT_COOKIE_NAME},
+    ErrorResponse,
+};
+
+use crate::helpers::{
+    get_random_email, TestApp, TEST_PASSWORD, TEST_SECURE_TENANT,
+    TEST_SECURE_TENANT_MIN_PASSWORD_LENGTH, TEST_TENANT,
+};
+
+async fn tenant_signup(app: &TestApp, tenant: &str, email: &str, password: &str) -> u16 {
+    let body = serde_json::json!({
+        "email": email,
+        "password": password,
+        "requires2FA": false
+    });
+    app.post_tenant(tenant, "signup", &body).await.status().as_u16()
+}
+
+// Log in at `tenant` and return the auth cookie; the jar keeps it too
+async fn tenant_login(app: &TestApp, tenant: &str, email: &str, password: &str) -> String {
+    let body = serde_json::json!({ "email": email, "password": password });
+    let response = app.post_tenant(tenant, "login", &body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+    token
+}
+
+#[tokio::test]
+async fn should_keep_accounts_of_tenants_apart() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let other_password = "another-Quiet-harbour-81";
+
+    // The same email signs up once per tenant, with its own password
+    assert_eq!(tenant_signup(&app, "default", &random_email, TEST_PASSWORD).await, 201);
+    assert_eq!(tenant_signup(&app, TEST_TENANT, &random_email, other_password).await, 201);
+    assert_eq!(tenant_signup(&app, TEST_TENANT, &random_email, other_password).await, 409);
+
+    let login = |password: &str| serde_json::json!({ "email": random_email, "password": password });
+    assert_eq!(app.post_login(&login(TEST_PASSWORD)).await.status().as_u16(), 200);
+    assert_eq!(app.post_login(&login(other_password)).await.status().as_u16(), 401);
+    assert_eq!(
+        app.post_tenant(TEST_TENANT, "login", &login(TEST_PASSWORD))
+            .await
+            .status()
+            .as_u16(),
+        401
+    );
+    assert_eq!(
+        app.post_tenant(TEST_TENANT, "login", &login(other_password))
+            .await
+            .status()
+            .as_u16(),
+        200
+    );
+}
+
+#[tokio::test]
+async fn should_issue_tokens_naming_the_tenant() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    assert_eq!(tenant_signup(&app, TEST_TENANT, &random_email, TEST_PASSWORD).await, 201);
+    let token = tenant_login(&app, TEST_TENANT, &random_email, TEST_PASSWORD).await;
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+    let claims = response.json::<Claims>().await.unwrap();
+    assert_eq!(claims.sub, random_email);
+    assert_eq!(claims.tenant.as_deref(), Some(TEST_TENANT));
+    assert_eq!(claims.iss.as_deref(), Some("auth-service/t/acme"));
+
+    // Audit events name the tenant the request was for
+    let events = app
+        .audit_sink
+        .query(&AuditQuery {
+            email: Some(random_email),
+            limit: 100,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let login = events
+        .iter()
+        .find(|event| event.event_type == AuditEventType::Login)
+        .expect("No login recorded");
+    assert_eq!(login.tenant.as_deref(), Some(TEST_TENANT));
+}
+
+#[tokio::test]
+async fn should_reject_tokens_of_other_tenants() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    assert_eq!(tenant_signup(&app, TEST_TENANT, &random_email, TEST_PASSWORD).await, 201);
+    tenant_login(&app, TEST_TENANT, &random_email, TEST_PASSWORD).await;
+
+    assert_eq!(app.get_tenant(TEST_TENANT, "api-keys").await.status().as_u16(), 200);
+
+    for response in [
+        app.get_api_keys().await,
+        app.get_tenant(TEST_SECURE_TENANT, "api-keys").await,
+    ] {
+        assert_eq!(response.status().as_u16(), 401);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.unwrap().error,
+            "Invalid auth token".to_owned()
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_return_404_if_unknown_tenant() {
+    let app = TestApp::new().await;
+    let body = serde_json::json!({
+        "email": get_random_email(),
+        "password": TEST_PASSWORD,
+        "requires2FA": false
+    });
+
+    for tenant in ["unknown", "Not-A-Tenant"] {
+        let response = app.post_tenant(tenant, "signup", &body).await;
+        assert_eq!(response.status().as_u16(), 404, "Failed for tenant: {}", tenant);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.unwrap().error,
+            "Unknown tenant".to_owned()
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_apply_the_tenant_password_policy() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    assert!(TEST_PASSWORD.len() < TEST_SECURE_TENANT_MIN_PASSWORD_LENGTH);
+
+    assert_eq!(
+        tenant_signup(&app, TEST_SECURE_TENANT, &random_email, TEST_PASSWORD).await,
+        400
+    );
+    assert_eq!(tenant_signup(&app, TEST_TENANT, &random_email, TEST_PASSWORD).await, 201);
+}
+
+#[tokio::test]
+async fn should_return_206_if_tenant_requires_2fa() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    let password = "a-much-longer-Quiet-lantern-orbit-57";
+
+    // The account did not ask for 2FA, but the tenant requires it
+    assert_eq!(tenant_signup(&app, TEST_SECURE_TENANT, &random_email, password).await, 201);
+
+    let response = app
+        .post_tenant(
+            TEST_SECURE_TENANT,
+            "login",
+            &serde_json::json!({ "email": random_email, "password": password }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 206);
+
+    let tenant = TenantId::parse(TEST_SECURE_TENANT.to_owned()).unwrap();
+    assert!(app
+        .two_fa_code_store
+        .read()
+        .await
+        .get_code(&tenant, &Email::parse(random_email).unwrap())
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn should_log_in_with_a_link_for_the_tenant() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    assert_eq!(tenant_signup(&app, TEST_TENANT, &random_email, TEST_PASSWORD).await, 201);
+
+    let response = app
+        .post_tenant(
+            TEST_TENANT,
+            "login/magic-link",
+            &serde_json::json!({ "email": random_email }),
+        )
+        .await;
+    assert_eq!(response.status().as_u16(), 202);
+
+    let link = app
+        .email_client
+        .wait_for(&random_email)
+        .await
+        .expect("No link was emailed")
+        .content;
+    let prefix = "http://localhost:3000/?tenant=acme&magic_token=";
+    assert!(link.starts_with(prefix), "Unexpected link: {}", link);
+    let body = serde_json::json!({ "token": &link[prefix.len()..] });
+
+    // The link only works for the tenant it was issued for
+    assert_eq!(app.post_verify_magic_link(&body).await.status().as_u16(), 401);
+    let response = app
+        .post_tenant(TEST_TENANT, "login/magic-link/verify", &body)
+        .await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_return_403_if_admin_of_another_tenant_reads_audit_log() {
+    let app = TestApp::new().await;
+    let random_email = get_random_email();
+    assert_eq!(tenant_signup(&app, TEST_TENANT, &random_email, TEST_PASSWORD).await, 201);
+
+    app.user_store
+        .write()
+        .await
+        .set_roles(
+            &TenantId::parse(TEST_TENANT.to_owned()).unwrap(),
+            &Email::parse(random_email.clone()).unwrap(),
+            BTreeSet::from([Role::user(), Role::admin()]),
+        )
+        .await
+        .unwrap();
+    tenant_login(&app, TEST_TENANT, &random_email, TEST_PASSWORD).await;
+
+    let response = app.get_audit_events(&[]).await;
+    assert_eq!(response.status().as_u16(), 403);
+}
@@ -0,0 +1,155 @@
+use auth_service::{
+    domain::role::Role, routes::TokenResponse, utils::constants::JWT_COOKIE_NAME, ErrorResponse,
+};
+
+use crate::helpers::{
+    get_random_email, TestApp, TEST_CLIENT_ID, TEST_CLIENT_SCOPE, TEST_CLIENT_SECRET,
+};
+
+async fn assert_oauth_error(response: reqwest::Response, status: u16, error: &str) {
+    assert_eq!(response.status().as_u16(), status);
+    assert_eq!(
+        response
+            .json::<ErrorResponse>()
+            .await
+            .expect("Could not deserialize response body to ErrorResponse")
+            .error,
+        error
+    );
+}
+
+#[tokio::test]
+async fn should_issue_service_token_with_basic_auth() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .http_client
+        .post(format!("{}/token", &app.address))
+        .basic_auth(TEST_CLIENT_ID, Some(TEST_CLIENT_SECRET))
+        .form(&[("grant_type", "client_credentials")])
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+
+    let body = response.json::<TokenResponse>().await.unwrap();
+    assert_eq!(body.token_type, "Bearer");
+    assert_eq!(body.scope, format!("{} {}", Role::REVOKE, TEST_CLIENT_SCOPE));
+    assert!(body.expires_in > 0);
+}
+
+#[tokio::test]
+async fn should_issue_service_token_with_form_credentials() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_token(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", TEST_CLIENT_ID),
+            ("client_secret", TEST_CLIENT_SECRET),
+            ("scope", TEST_CLIENT_SCOPE),
+        ])
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn should_return_invalid_client_for_bad_credentials() {
+    let app = TestApp::new().await;
+
+    let test_cases = [
+        vec![("grant_type", "client_credentials")],
+        vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", TEST_CLIENT_ID),
+            ("client_secret", "wrong-secret"),
+        ],
+        vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", "unknown-service"),
+            ("client_secret", TEST_CLIENT_SECRET),
+        ],
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_token(test_case).await;
+        assert!(response.headers().contains_key("www-authenticate"));
+        assert_oauth_error(response, 401, "invalid_client").await;
+    }
+}
+
+#[tokio::test]
+async fn should_reject_unsupported_grant_and_scope() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_token(&[
+            ("grant_type", "password"),
+            ("client_id", TEST_CLIENT_ID),
+            ("client_secret", TEST_CLIENT_SECRET),
+        ])
+        .await;
+    assert_oauth_error(response, 400, "unsupported_grant_type").await;
+
+    let response = app
+        .post_token(&[
+            ("grant_type", "client_credentials"),
+            ("client_id", TEST_CLIENT_ID),
+            ("client_secret", TEST_CLIENT_SECRET),
+            ("scope", "admin"),
+        ])
+        .await;
+    assert_oauth_error(response, 400, "invalid_scope").await;
+}
+
+#[tokio::test]
+async fn verify_token_should_require_service_token() {
+    let app = TestApp::new().await;
+
+    let response = app.signup_and_login(&get_random_email(), false).await;
+    let user_token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    let body = serde_json::json!({ "token": user_token });
+
+    // Anonymous callers cannot verify other people's tokens
+    let response = app
+        .http_client
+        .post(format!("{}/verify-token", &app.address))
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+
+    // and a user token is no substitute for a service token
+    let response = app
+        .http_client
+        .post(format!("{}/verify-token", &app.address))
+        .bearer_auth(&user_token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.post_verify_token(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn service_token_is_not_a_user_token() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": app.service_token }))
+        .await;
+    assert_eq!(response.status().as_u16(), 401);
+}
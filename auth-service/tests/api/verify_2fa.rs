@@ -1,15 +1,183 @@
-use crate::helpers::TestApp;
-use serde_json::json;
+use auth_service::{
+    domain::{email::Email, tenant::TenantId},
+    routes::TwoFactorAuthResponse, utils::constants::JWT_COOKIE_NAME,
+    ErrorResponse,
+};
+
+use crate::helpers::{get_random_email, TestApp};
+
+// Log in a 2FA user and return the login attempt id and the code that was "emailed"
+async fn start_2fa_login(app: &TestApp, email: &str) -> (String, String) {
+    let response = app.signup_and_login(email, true).await;
+    assert_eq!(response.status().as_u16(), 206);
+
+    let login_attempt_id = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse")
+        .login_attempt_id;
+
+    let (_, code) = app
+        .two_fa_code_store
+        .read()
+        .await
+        .get_code(&TenantId::default(), &Email::parse(email.to_owned()).unwrap())
+        .await
+        .expect("No 2FA code stored");
+
+    (login_attempt_id, code.as_ref().to_owned())
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let app = TestApp::new().await;
+
+    let test_cases = [
+        serde_json::json!({
+            "email": get_random_email(),
+            "loginAttemptId": "string",
+        }),
+        serde_json::json!({
+            "loginAttemptId": "string",
+            "2FACode": "123456"
+        }),
+        serde_json::json!({
+            "email": get_random_email(),
+            "login_attempt_id": "string",
+            "2FACode": "123456"
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_verify_2fa(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            422,
+            "Failed for input: {:?}",
+            test_case
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_input() {
+    let app = TestApp::new().await;
+
+    let test_cases = [
+        serde_json::json!({
+            "email": "invalid_email",
+            "loginAttemptId": uuid::Uuid::new_v4().to_string(),
+            "2FACode": "123456"
+        }),
+        serde_json::json!({
+            "email": get_random_email(),
+            "loginAttemptId": "not-a-uuid",
+            "2FACode": "123456"
+        }),
+        serde_json::json!({
+            "email": get_random_email(),
+            "loginAttemptId": uuid::Uuid::new_v4().to_string(),
+            "2FACode": "12ab"
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_verify_2fa(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            400,
+            "Failed for input: {:?}",
+            test_case
+        );
+        assert_eq!(
+            response
+                .json::<ErrorResponse>()
+                .await
+                .expect("Could not deserialize response body to ErrorResponse")
+                .error,
+            "Invalid credentials".to_owned()
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_credentials() {
+    let app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    let (login_attempt_id, code) = start_2fa_login(&app, &random_email).await;
+    let wrong_code = if code == "000000" { "111111" } else { "000000" };
+
+    let test_cases = [
+        serde_json::json!({
+            "email": random_email,
+            "loginAttemptId": uuid::Uuid::new_v4().to_string(),
+            "2FACode": code
+        }),
+        serde_json::json!({
+            "email": random_email,
+            "loginAttemptId": login_attempt_id,
+            "2FACode": wrong_code
+        }),
+        serde_json::json!({
+            "email": get_random_email(),
+            "loginAttemptId": login_attempt_id,
+            "2FACode": code
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_verify_2fa(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            401,
+            "Failed for input: {:?}",
+            test_case
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_return_200_if_correct_code() {
+    let app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    let (login_attempt_id, code) = start_2fa_login(&app, &random_email).await;
+
+    let response = app
+        .post_verify_2fa(&serde_json::json!({
+            "email": random_email,
+            "loginAttemptId": login_attempt_id,
+            "2FACode": code
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 200);
+
+    let auth_cookie = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found");
+
+    assert!(!auth_cookie.value().is_empty());
+}
 
 #[tokio::test]
-async fn verify_2fa_returns_http_200_status() {
+async fn should_return_401_if_same_code_twice() {
     let app = TestApp::new().await;
 
-    let response = app.post_verify_2fa(&json!({
-        "email": "user@example.com",
-        "loginAttemptId": "string",
-        "2FACode": "string"
-    })).await;
+    let random_email = get_random_email();
+    let (login_attempt_id, code) = start_2fa_login(&app, &random_email).await;
 
+    let request_body = serde_json::json!({
+        "email": random_email,
+        "loginAttemptId": login_attempt_id,
+        "2FACode": code
+    });
+
+    let response = app.post_verify_2fa(&request_body).await;
     assert_eq!(response.status().as_u16(), 200);
-}
\ No newline at end of file
+
+    let response = app.post_verify_2fa(&request_body).await;
+    assert_eq!(response.status().as_u16(), 401);
+}
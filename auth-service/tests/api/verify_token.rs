@@ -1,13 +1,177 @@
-use crate::helpers::TestApp;
-use serde_json::json;
+use std::collections::BTreeSet;
+
+use auth_service::{
+    domain::{email::Email, role::Role, tenant::TenantId},
+    utils::{auth::Claims, constants::JWT_COOKIE_NAME},
+    ErrorResponse,
+};
+
+use crate::helpers::{get_random_email, TestApp, TEST_PASSWORD};
+
+async fn login_and_get_token(app: &TestApp, email: &str) -> String {
+    let response = app.signup_and_login(email, false).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+    token
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let app = TestApp::new().await;
+
+    let test_cases = [
+        serde_json::json!({}),
+        serde_json::json!({
+            "jwt": "string"
+        }),
+        serde_json::json!({
+            "token": true
+        }),
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_verify_token(test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            422,
+            "Failed for input: {:?}",
+            test_case
+        );
+    }
+}
 
 #[tokio::test]
-async fn verify_token_returns_http_200_status() {
+async fn should_return_200_with_claims_for_valid_token() {
     let app = TestApp::new().await;
 
-    let response = app.post_verify_token(&json!({
-        "token": "string"
-    })).await;
+    let random_email = get_random_email();
+    let token = login_and_get_token(&app, &random_email).await;
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await;
 
     assert_eq!(response.status().as_u16(), 200);
-}
\ No newline at end of file
+
+    let claims = response
+        .json::<Claims>()
+        .await
+        .expect("Could not deserialize response body to Claims");
+
+    assert_eq!(claims.sub, random_email);
+    assert_eq!(claims.roles, vec![Role::USER.to_owned()]);
+    assert!(claims.exp > claims.iat);
+}
+
+#[tokio::test]
+async fn should_embed_assigned_roles_in_claims() {
+    let app = TestApp::new().await;
+
+    let random_email = get_random_email();
+
+    let signup_body = serde_json::json!({
+        "email": random_email,
+        "password": TEST_PASSWORD,
+        "requires2FA": false
+    });
+    app.post_signup(&signup_body).await;
+
+    app.user_store
+        .write()
+        .await
+        .set_roles(
+            &TenantId::default(),
+            &Email::parse(random_email.clone()).unwrap(),
+            BTreeSet::from([Role::user(), Role::admin()]),
+        )
+        .await
+        .unwrap();
+
+    let response = app
+        .post_login(&serde_json::json!({
+            "email": random_email,
+            "password": TEST_PASSWORD,
+        }))
+        .await;
+    let token = response
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned();
+
+    let claims = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await
+        .json::<Claims>()
+        .await
+        .expect("Could not deserialize response body to Claims");
+
+    assert!(claims.has_role(Role::ADMIN));
+    assert!(claims.has_role(Role::USER));
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": "invalid" }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_return_401_if_banned_token() {
+    let app = TestApp::new().await;
+
+    let token = login_and_get_token(&app, &get_random_email()).await;
+
+    let response = app.post_logout().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app
+        .post_verify_token(&serde_json::json!({ "token": token }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn should_return_401_if_no_body_and_no_service_token() {
+    let app = TestApp::new().await;
+
+    let token = login_and_get_token(&app, &get_random_email()).await;
+
+    // A user token as bearer does not verify itself
+    for token in [token.as_str(), "invalid"] {
+        let response = app.post_verify_token_bearer(token).await;
+        assert_eq!(response.status().as_u16(), 401);
+        assert_eq!(
+            response.json::<ErrorResponse>().await.unwrap().error,
+            "Invalid auth token".to_owned()
+        );
+    }
+}
+
+#[tokio::test]
+async fn should_return_400_if_no_token_given() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .http_client
+        .post(format!("{}/verify-token", &app.address))
+        .bearer_auth(&app.service_token)
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 400);
+}